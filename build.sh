@@ -9,7 +9,19 @@ cargo build --examples
 cargo bench --no-run
 cargo test --release
 
-cargo doc --all-features
+# Run the tests and examples against the in-process mock HAL
+cargo test -p linuxcnc-hal --features mock,derive,serde
+
+# The `rtapi` and `ulapi` features are mutually exclusive, so check each mode separately
+cargo check -p linuxcnc-hal --all-targets --features rtapi
+cargo check -p linuxcnc-hal --all-targets --features ulapi
+
+# Load the HAL library at runtime instead of linking against it
+cargo check -p linuxcnc-hal --all-targets --features dlopen
+cargo check -p rtapi-logger --features dlopen
+cargo check -p rtapi-logger --features tracing
+
+cargo doc --features linuxcnc-hal/derive,linuxcnc-hal/serde,linuxcnc-hal-sys/bindgen,rtapi-logger/tracing
 linkchecker target/doc/linuxcnc_hal_sys/index.html
 linkchecker target/doc/linuxcnc_hal/index.html
 linkchecker target/doc/rtapi_logger/index.html
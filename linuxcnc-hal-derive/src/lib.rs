@@ -0,0 +1,371 @@
+//! Derive macros for [`linuxcnc-hal`](https://docs.rs/linuxcnc-hal).
+//!
+//! This crate should not be used directly. Enable the `derive` feature of `linuxcnc-hal` and use
+//! the re-exported `linuxcnc_hal::Resources` and `linuxcnc_hal::stream::StreamSample` derives
+//! instead.
+//!
+//! # `#[derive(Resources)]`
+//!
+//! Implements `Resources` for a struct with named fields. Every field is registered with the
+//! component in declaration order. Fields of type `Parameter<'comp, T>` are registered as read/write
+//! parameters and every other field is registered as a pin or port.
+//!
+//! The HAL name of each resource defaults to the field name with underscores replaced by dashes,
+//! so a field called `input_1` becomes `<component name>.input-1`.
+//!
+//! Each field can be configured with a `#[hal(...)]` attribute:
+//!
+//! | Attribute                | Description                                                      |
+//! | ------------------------ | ---------------------------------------------------------------- |
+//! | `name = "..."`           | Override the HAL name of the resource                            |
+//! | `alias = "..."`          | Set an alias, used as-is without the component name prefix       |
+//! | `pin`                    | Register the field as a pin or port                              |
+//! | `parameter`              | Register the field as a read/write parameter                     |
+//! | `readonly`               | Register the field as a read only parameter. Implies `parameter` |
+//! | `init = <expr>`          | Set the value of the resource after it is registered             |
+//! | `skip`                   | Don't register the field. It is set to `Default::default()`      |
+//!
+//! `skip` can't be combined with any other attribute, and `readonly` can't be used on pins.
+//!
+//! The generated implementation uses `ResourcesError` as its `RegisterError`. Any error is wrapped
+//! in `ResourcesError::Field` which holds the name of the field that failed to register.
+//!
+//! Pins and parameters borrow the component they are registered with. If the struct has a
+//! lifetime parameter, the first one is used as the component lifetime, i.e. `Resources<'comp>`
+//! is implemented for `Comp<'comp>`.
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::{
+//!     hal_pin::{InputPin, OutputPin},
+//!     HalComponent, Parameter, Resources,
+//! };
+//!
+//! #[derive(Resources)]
+//! struct Comp<'comp> {
+//!     /// Registered as `<comp>.input-1`
+//!     input_1: InputPin<'comp, f64>,
+//!
+//!     /// Registered as `<comp>.home-sw` with the alias `x-axis.home-sw`
+//!     #[hal(alias = "x-axis.home-sw")]
+//!     home_sw: InputPin<'comp, bool>,
+//!
+//!     /// Registered as `<comp>.out` with an initial value of `true`
+//!     #[hal(name = "out", init = true)]
+//!     output: OutputPin<'comp, bool>,
+//!
+//!     /// Registered as the read only parameter `<comp>.scale`
+//!     #[hal(readonly, init = 2.5)]
+//!     scale: Parameter<'comp, f64>,
+//!
+//!     /// Not registered with the HAL
+//!     #[hal(skip)]
+//!     counter: u32,
+//! }
+//!
+//! let comp = HalComponent::new("demo-component").unwrap();
+//! let resources: Comp = comp.register().unwrap();
+//! ```
+//!
+//! # `#[derive(StreamSample)]`
+//!
+//! Implements `StreamSample` for a struct, so it can be sent through a HAL stream. Every field must
+//! be one of the stream element types `f64`, `bool`, `i32` or `u32`. Fields are written to and read
+//! from the stream in declaration order, so the struct has the same type string as the tuple of
+//! its field types. Structs with named fields and tuple structs are supported.
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::stream::StreamSample;
+//!
+//! /// Type string `"ffb"`
+//! #[derive(Copy, Clone, StreamSample)]
+//! struct Point {
+//!     x: f64,
+//!     y: f64,
+//!     spindle_on: bool,
+//! }
+//!
+//! assert_eq!(Point::type_string(), "ffb");
+//! ```
+
+mod stream_sample;
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::quote;
+use syn::{
+    parse_macro_input, spanned::Spanned, Data, DeriveInput, Error, Expr, Field, Fields,
+    GenericParam, Lifetime, LifetimeParam, LitStr, Type,
+};
+
+/// How a field is registered with the component
+enum Kind {
+    Pin,
+    Parameter { readonly: bool },
+    Skip,
+}
+
+/// Parsed `#[hal(...)]` attribute for a single field
+struct FieldConfig {
+    kind: Kind,
+    name: String,
+    alias: Option<String>,
+    init: Option<Expr>,
+}
+
+impl FieldConfig {
+    fn parse(field: &Field) -> Result<Self, Error> {
+        let ident = field.ident.as_ref().expect("named field");
+
+        let mut name = None;
+        let mut alias = None;
+        let mut init = None;
+        let mut pin = false;
+        let mut parameter = false;
+        let mut readonly = false;
+        let mut skip = false;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("hal"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    name = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("alias") {
+                    alias = Some(meta.value()?.parse::<LitStr>()?.value());
+                } else if meta.path.is_ident("init") {
+                    init = Some(meta.value()?.parse::<Expr>()?);
+                } else if meta.path.is_ident("pin") {
+                    pin = true;
+                } else if meta.path.is_ident("parameter") {
+                    parameter = true;
+                } else if meta.path.is_ident("readonly") {
+                    readonly = true;
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error("unknown hal attribute"));
+                }
+
+                Ok(())
+            })?;
+        }
+
+        let kind = if skip {
+            if pin || parameter || readonly || name.is_some() || alias.is_some() || init.is_some() {
+                return Err(Error::new(
+                    field.span(),
+                    "`skip` cannot be combined with other hal attributes",
+                ));
+            }
+
+            Kind::Skip
+        } else if pin && parameter {
+            return Err(Error::new(
+                field.span(),
+                "`pin` and `parameter` cannot be used together",
+            ));
+        } else if readonly && (pin || is_pin(&field.ty)) {
+            return Err(Error::new(
+                field.span(),
+                "`readonly` can only be used on parameters",
+            ));
+        } else if pin {
+            Kind::Pin
+        } else if parameter || readonly || is_parameter(&field.ty) {
+            Kind::Parameter { readonly }
+        } else {
+            Kind::Pin
+        };
+
+        let name =
+            name.unwrap_or_else(|| ident.to_string().trim_start_matches("r#").replace('_', "-"));
+
+        Ok(Self {
+            kind,
+            name,
+            alias,
+            init,
+        })
+    }
+}
+
+/// Whether the last segment of a type path is `Parameter`
+fn is_parameter(ty: &Type) -> bool {
+    type_name_is(ty, &["Parameter"])
+}
+
+/// Whether the last segment of a type path is one of the pin or port types
+fn is_pin(ty: &Type) -> bool {
+    type_name_is(
+        ty,
+        &[
+            "InputPin",
+            "OutputPin",
+            "BidirectionalPin",
+            "InputPort",
+            "OutputPort",
+        ],
+    )
+}
+
+/// Whether the last segment of a type path is one of `names`
+fn type_name_is(ty: &Type, names: &[&str]) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| names.iter().any(|name| segment.ident == name))
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+/// Derive `linuxcnc_hal::stream::StreamSample` for a struct
+///
+/// See the [crate documentation](crate) for details.
+#[proc_macro_derive(StreamSample)]
+pub fn derive_stream_sample(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    stream_sample::expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// Derive `linuxcnc_hal::Resources` for a struct
+///
+/// See the [crate documentation](crate) for details.
+#[proc_macro_derive(Resources, attributes(hal))]
+pub fn derive_resources(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+
+    expand(input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(Error::new(
+                    input.span(),
+                    "Resources can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "Resources can only be derived for structs",
+            ))
+        }
+    };
+
+    let registrations = fields
+        .iter()
+        .map(register_field)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let ident = &input.ident;
+    let (_, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // Resources borrow the component for the struct's first lifetime, or for any lifetime if the
+    // struct doesn't have one
+    let mut generics = input.generics.clone();
+    let comp_lifetime = match generics.lifetimes().next() {
+        Some(param) => param.lifetime.clone(),
+        None => {
+            let lifetime = Lifetime::new("'__comp", Span::call_site());
+
+            generics.params.insert(
+                0,
+                GenericParam::Lifetime(LifetimeParam::new(lifetime.clone())),
+            );
+
+            lifetime
+        }
+    };
+    let (impl_generics, _, _) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::linuxcnc_hal::Resources<#comp_lifetime> for #ident #ty_generics
+            #where_clause
+        {
+            type RegisterError = ::linuxcnc_hal::error::ResourcesError;
+
+            fn register_resources(
+                comp: &::linuxcnc_hal::RegisterResources<#comp_lifetime>,
+            ) -> ::std::result::Result<Self, Self::RegisterError> {
+                ::std::result::Result::Ok(Self {
+                    #(#registrations)*
+                })
+            }
+        }
+    })
+}
+
+fn register_field(field: &Field) -> Result<TokenStream2, Error> {
+    let config = FieldConfig::parse(field)?;
+
+    let ident = field.ident.as_ref().expect("named field");
+    let field_name = ident.to_string();
+    let ty = &field.ty;
+    let name = &config.name;
+
+    let wrap_error = quote! {
+        |e| ::linuxcnc_hal::error::ResourcesError::Field {
+            field: #field_name,
+            source: ::std::boxed::Box::new(e.into()),
+        }
+    };
+
+    let register = match (&config.kind, &config.alias) {
+        (Kind::Skip, _) => {
+            return Ok(quote! {
+                #ident: ::std::default::Default::default(),
+            })
+        }
+        (Kind::Pin, None) => quote! { comp.register_pin::<#ty>(#name) },
+        (Kind::Pin, Some(alias)) => quote! { comp.register_pin_with_alias::<#ty>(#name, #alias) },
+        (Kind::Parameter { readonly: false }, None) => {
+            quote! { comp.register_parameter::<#ty>(#name) }
+        }
+        (Kind::Parameter { readonly: false }, Some(alias)) => {
+            quote! { comp.register_parameter_with_alias::<#ty>(#name, #alias) }
+        }
+        (Kind::Parameter { readonly: true }, None) => {
+            quote! { comp.register_readonly_parameter::<#ty>(#name) }
+        }
+        (Kind::Parameter { readonly: true }, Some(alias)) => {
+            quote! { comp.register_readonly_parameter_with_alias::<#ty>(#name, #alias) }
+        }
+    };
+
+    let init = config.init.map(|init| {
+        let set_value = match config.kind {
+            Kind::Pin => quote! { ::linuxcnc_hal::hal_pin::PinWrite::set_value(&resource, #init) },
+            _ => quote! { resource.set_value(#init) },
+        };
+
+        quote! {
+            #set_value
+                .map_err(::linuxcnc_hal::error::ResourcesError::InitialValue)
+                .map_err(#wrap_error)?;
+        }
+    });
+
+    Ok(quote! {
+        #ident: {
+            let resource = #register.map_err(#wrap_error)?;
+
+            #init
+
+            resource
+        },
+    })
+}
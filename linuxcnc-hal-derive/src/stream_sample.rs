@@ -0,0 +1,69 @@
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{spanned::Spanned, Data, DeriveInput, Error, Fields, Index, Member};
+
+pub(crate) fn expand(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Err(Error::new(
+                input.span(),
+                "StreamSample can only be derived for structs",
+            ))
+        }
+    };
+
+    if fields.is_empty() {
+        return Err(Error::new(
+            input.span(),
+            "StreamSample can only be derived for structs with at least one field",
+        ));
+    }
+
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(idx, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(idx)),
+        })
+        .collect::<Vec<_>>();
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let len = fields.len();
+    let indices = (0..len).collect::<Vec<_>>();
+
+    let element = quote! { ::linuxcnc_hal::stream::StreamElement };
+    let data = quote! { ::linuxcnc_hal::stream::hal_stream_data };
+
+    let read = match fields {
+        Fields::Named(_) => quote! {
+            Self { #(#members: <#types as #element>::from_data(buf[#indices]),)* }
+        },
+        _ => quote! {
+            Self(#(<#types as #element>::from_data(buf[#indices]),)*)
+        },
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::linuxcnc_hal::stream::StreamSample for #ident #ty_generics
+            #where_clause
+        {
+            const LEN: usize = #len;
+
+            fn type_string() -> ::std::string::String {
+                [#(<#types as #element>::TYPE_CHAR),*].iter().collect()
+            }
+
+            fn write_data(&self, buf: &mut [#data]) {
+                #(buf[#indices] = #element::into_data(self.#members);)*
+            }
+
+            fn read_data(buf: &[#data]) -> Self {
+                #read
+            }
+        }
+    })
+}
@@ -0,0 +1,42 @@
+use linuxcnc_hal::stream::{hal_stream_data, StreamSample};
+
+#[derive(Debug, Copy, Clone, PartialEq, StreamSample)]
+struct Point {
+    x: f64,
+    y: f64,
+    spindle_on: bool,
+    tool: u32,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, StreamSample)]
+struct Offset(i32, f64);
+
+#[test]
+fn type_strings() {
+    assert_eq!(Point::type_string(), "ffbu");
+    assert_eq!(Point::LEN, 4);
+    assert_eq!(Offset::type_string(), <(i32, f64)>::type_string());
+}
+
+#[test]
+fn round_trip() {
+    let mut buf = [hal_stream_data { u: 0 }; 4];
+
+    let point = Point {
+        x: 1.5,
+        y: -2.0,
+        spindle_on: true,
+        tool: 3,
+    };
+
+    point.write_data(&mut buf);
+
+    assert_eq!(Point::read_data(&buf), point);
+
+    let offset = Offset(-7, 0.25);
+
+    offset.write_data(&mut buf[0..2]);
+
+    assert_eq!(Offset::read_data(&buf[0..2]), offset);
+    assert_eq!(<(i32, f64)>::read_data(&buf[0..2]), (-7, 0.25));
+}
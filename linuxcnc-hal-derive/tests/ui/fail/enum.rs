@@ -0,0 +1,8 @@
+use linuxcnc_hal::Resources;
+
+#[derive(Resources)]
+enum Comp {
+    A,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use linuxcnc_hal::{Parameter, Resources};
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    #[hal(pin, parameter)]
+    scale: Parameter<'comp, f64>,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use linuxcnc_hal::{hal_pin::InputPin, Resources};
+
+type Input<'comp> = InputPin<'comp, f64>;
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    #[hal(pin, readonly)]
+    input: Input<'comp>,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use linuxcnc_hal::Resources;
+
+#[derive(Resources)]
+struct Comp {
+    #[hal(skip, name = "counter")]
+    counter: u32,
+}
+
+fn main() {}
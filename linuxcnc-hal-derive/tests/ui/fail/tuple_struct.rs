@@ -0,0 +1,6 @@
+use linuxcnc_hal::{hal_pin::InputPin, Resources};
+
+#[derive(Resources)]
+struct Comp<'comp>(InputPin<'comp, f64>);
+
+fn main() {}
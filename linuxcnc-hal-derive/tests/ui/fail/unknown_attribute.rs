@@ -0,0 +1,9 @@
+use linuxcnc_hal::{hal_pin::InputPin, Resources};
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    #[hal(default = 1.0)]
+    input: InputPin<'comp, f64>,
+}
+
+fn main() {}
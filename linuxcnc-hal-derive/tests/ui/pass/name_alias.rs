@@ -0,0 +1,18 @@
+use linuxcnc_hal::{
+    hal_pin::{InputPin, OutputPin},
+    Parameter, Resources,
+};
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    #[hal(name = "in")]
+    input: InputPin<'comp, f64>,
+
+    #[hal(alias = "x-axis.out")]
+    output: OutputPin<'comp, f64>,
+
+    #[hal(name = "gain", alias = "x-axis.gain")]
+    scale: Parameter<'comp, f64>,
+}
+
+fn main() {}
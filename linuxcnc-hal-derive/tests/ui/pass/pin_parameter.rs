@@ -0,0 +1,21 @@
+use linuxcnc_hal::{
+    hal_pin::{BidirectionalPin, InputPort},
+    Parameter, Resources,
+};
+
+// Type aliases hide the field type from the derive, so the kind is given explicitly
+type Gain<'comp> = Parameter<'comp, f64>;
+type Enable<'comp> = BidirectionalPin<'comp, bool>;
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    #[hal(parameter)]
+    gain: Gain<'comp>,
+
+    #[hal(pin)]
+    enable: Enable<'comp>,
+
+    port: InputPort<'comp>,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use linuxcnc_hal::{hal_pin::OutputPin, Parameter, Resources};
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    #[hal(init = true)]
+    output: OutputPin<'comp, bool>,
+
+    #[hal(readonly, init = 2.5)]
+    scale: Parameter<'comp, f64>,
+
+    #[hal(parameter, readonly, init = 1u32 + 2)]
+    count: Parameter<'comp, u32>,
+}
+
+fn main() {}
@@ -0,0 +1,21 @@
+use linuxcnc_hal::{hal_pin::InputPin, Resources};
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    input: InputPin<'comp, i32>,
+
+    #[hal(skip)]
+    counter: u32,
+
+    #[hal(skip)]
+    history: Vec<i32>,
+}
+
+// Structs without pins or parameters don't need a lifetime
+#[derive(Resources)]
+struct Empty {
+    #[hal(skip)]
+    counter: u32,
+}
+
+fn main() {}
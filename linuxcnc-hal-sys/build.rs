@@ -1,31 +1,60 @@
-extern crate bindgen;
-
 use std::env;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Directories searched for `liblinuxcnchal` when neither `LINUXCNC_LIB_DIR` nor `LINUXCNC_SRC` is
+/// set and pkg-config can't find LinuxCNC
+const SYSTEM_LIB_DIRS: &[&str] = &[
+    "/usr/lib/linuxcnc",
+    "/usr/local/lib/linuxcnc",
+    "/usr/lib",
+    "/usr/local/lib",
+];
 
 fn main() {
-    if std::env::var("DOCS_RS").is_ok() {
+    println!("cargo:rerun-if-env-changed=LINUXCNC_SRC");
+    println!("cargo:rerun-if-env-changed=LINUXCNC_LIB_DIR");
+
+    #[cfg(feature = "bindgen")]
+    generate_bindings();
+
+    #[cfg(not(feature = "bindgen"))]
+    check_bindings();
+
+    // There is no LinuxCNC install to link against on docs.rs, and with `dlopen` the library is
+    // loaded at runtime instead
+    if env::var("DOCS_RS").is_ok()
+        || env::var("CARGO_FEATURE_NO_LINK").is_ok()
+        || env::var("CARGO_FEATURE_DLOPEN").is_ok()
+    {
         return;
     }
 
+    link();
+}
+
+/// Generate bindings from the LinuxCNC source tree at `LINUXCNC_SRC` into `$OUT_DIR/bindings.rs`
+#[cfg(feature = "bindgen")]
+fn generate_bindings() {
     println!("cargo:rerun-if-changed=wrapper.h");
-    println!("cargo:rerun-if-changed=src/generated.rs");
 
-    let linuxcnc_root = env::var("LINUXCNC_SRC").expect("LINUXCNC_SRC env var must be set and pointing to the root of the LinuxCNC source Git repository");
+    let linuxcnc_root = env::var("LINUXCNC_SRC").expect("LINUXCNC_SRC env var must be set and pointing to the root of the LinuxCNC source Git repository when the `bindgen` feature is enabled");
 
     let bindings = bindgen::Builder::default()
         // The input header we would like to generate
         // bindings for.
         .header("wrapper.h")
-        .clang_arg(&format!("-I{}", linuxcnc_root))
-        .clang_arg(&format!("-I{}/src/hal", linuxcnc_root))
-        .clang_arg(&format!("-I{}/src/rtapi", linuxcnc_root))
+        .clang_arg(format!("-I{}", linuxcnc_root))
+        .clang_arg(format!("-I{}/src/hal", linuxcnc_root))
+        .clang_arg(format!("-I{}/src/rtapi", linuxcnc_root))
         // Tell LinuxCNC build to run in realtime mode with `-DRTAPI` or non-realtime with `-DULAPI`.
         // See line ~114 in linuxcnc-src/src/hal/hal.h
-        .clang_arg("-DRTAPI")
+        .clang_arg(if cfg!(feature = "ulapi") {
+            "-DULAPI"
+        } else {
+            "-DRTAPI"
+        })
         // FIXME: This currently breaks the doc tests as Rust parses the broken C comment as code.
         .blocklist_function("hal_set_lock")
-        // .clang_arg("-DULAPI")
         // Tell cargo to invalidate the built crate whenever any of the
         // included header files changed.
         .parse_callbacks(Box::new(bindgen::CargoCallbacks::new()))
@@ -34,21 +63,105 @@ fn main() {
         // Unwrap the Result and panic on failure.
         .expect("Unable to generate bindings");
 
-    // Write the bindings to the $OUT_DIR/bindings.rs file.
-    // let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
-
-    let out_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src");
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap());
 
     bindings
-        .write_to_file(out_path.join("generated.rs"))
+        .write_to_file(out_path.join("bindings.rs"))
         .expect("Couldn't write bindings!");
+}
 
-    // Dynamically link LinuxCNC HAL, as per <https://github.com/rust-lang/rust-bindgen/issues/1974>
-    // and <https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-link-lib>
+/// Check that the pre-generated bindings for the selected LinuxCNC version exist
+///
+/// Bindings for LinuxCNC 2.8 aren't shipped yet, so this explains how to generate them instead of
+/// the `include!` in `lib.rs` failing to find the file.
+#[cfg(not(feature = "bindgen"))]
+fn check_bindings() {
+    // Enabling both versions is reported by a `compile_error!` in `lib.rs`
+    if !cfg!(feature = "linuxcnc-2-8") || cfg!(feature = "linuxcnc-2-9") {
+        return;
+    }
+
+    let path = format!(
+        "src/bindings/linuxcnc_2_8_{}.rs",
+        if cfg!(feature = "ulapi") {
+            "ulapi"
+        } else {
+            "rtapi"
+        }
+    );
+
+    println!("cargo:rerun-if-changed={}", path);
+
+    if !Path::new(&path).exists() {
+        panic!(
+            "Pre-generated bindings for LinuxCNC 2.8 are not shipped yet. Generate `{}` with `LINUXCNC_SRC=/path/to/linuxcnc-2.8 ./generate-bindings.sh 2_8`, or enable the `bindgen` feature",
+            path
+        );
+    }
+}
+
+/// Find `liblinuxcnchal` and dynamically link against it
+///
+/// See <https://github.com/rust-lang/rust-bindgen/issues/1974> and
+/// <https://doc.rust-lang.org/cargo/reference/build-scripts.html#rustc-link-lib>
+fn link() {
+    // An explicit library directory, e.g. from a packaged install in a non-standard location
+    if let Ok(lib_dir) = env::var("LINUXCNC_LIB_DIR") {
+        link_dir(Path::new(&lib_dir));
+
+        return;
+    }
+
+    // A LinuxCNC source tree built to run in place
+    if let Ok(linuxcnc_root) = env::var("LINUXCNC_SRC") {
+        link_dir(&Path::new(&linuxcnc_root).join("lib"));
+
+        return;
+    }
+
+    if pkg_config::Config::new()
+        .atleast_version("2.8")
+        .probe("linuxcnc")
+        .is_ok()
+    {
+        return;
+    }
+
+    match SYSTEM_LIB_DIRS
+        .iter()
+        .map(Path::new)
+        .find(|dir| lib_path(dir).is_some())
+    {
+        Some(dir) => link_dir(dir),
+        None => {
+            println!("cargo:warning=liblinuxcnchal not found. Set LINUXCNC_LIB_DIR to the directory containing it, or LINUXCNC_SRC to a LinuxCNC source tree.");
+            println!("cargo:rustc-link-lib=dylib=linuxcnchal");
+        }
+    }
+}
+
+/// Link against `liblinuxcnchal` in `dir`
+fn link_dir(dir: &Path) {
+    println!("cargo:rustc-link-search=native={}", dir.display());
+
+    // Packaged installs may only provide the versioned library, without the development symlink
+    match lib_path(dir) {
+        Some(lib) if lib.extension().is_some_and(|ext| ext != "so") => println!(
+            "cargo:rustc-link-lib=dylib:+verbatim={}",
+            lib.file_name().unwrap().to_string_lossy()
+        ),
+        _ => println!("cargo:rustc-link-lib=dylib=linuxcnchal"),
+    }
 
-    println!("cargo:rustc-link-search=native={linuxcnc_root}/lib");
-    println!("cargo:rustc-link-lib=dylib=linuxcnchal");
     // From
     // <https://stackoverflow.com/questions/40602708/linking-rust-application-with-a-dynamic-library-not-in-the-runtime-linker-search>
-    println!("cargo:rustc-link-arg=-Wl,-rpath,{linuxcnc_root}/lib");
+    println!("cargo:rustc-link-arg=-Wl,-rpath,{}", dir.display());
+}
+
+/// Get the path to `liblinuxcnchal.so` or `liblinuxcnchal.so.0` in `dir`, if either exists
+fn lib_path(dir: &Path) -> Option<PathBuf> {
+    ["liblinuxcnchal.so", "liblinuxcnchal.so.0"]
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.exists())
 }
@@ -24,9 +24,9 @@ fn main() {
         let mut signals =
             Signals::new(&[signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT]).unwrap();
 
-        let hal_data = hal_malloc(mem::size_of::<HalData>().try_into().unwrap()) as *mut HalData;
+        let storage = hal_malloc(mem::size_of::<HalData>().try_into().unwrap()) as *mut HalData;
 
-        println!("Storage {:?}", hal_data);
+        println!("Storage {:?}", storage);
 
         let pin_1_name = CString::new("struct.input-1").unwrap();
         let pin_2_name = CString::new("struct.input-2").unwrap();
@@ -34,7 +34,7 @@ fn main() {
         let ret = hal_pin_float_new(
             pin_1_name.as_ptr().cast(),
             hal_pin_dir_t_HAL_IN,
-            &mut (*hal_data).d1,
+            &mut (*storage).d1,
             id,
         );
 
@@ -45,7 +45,7 @@ fn main() {
         let ret = hal_pin_float_new(
             pin_2_name.as_ptr().cast(),
             hal_pin_dir_t_HAL_IN,
-            &mut (*hal_data).d2,
+            &mut (*storage).d2,
             id,
         );
 
@@ -64,14 +64,14 @@ fn main() {
             _ => false,
         }) {
             // Uncomment the following two lines to inspect memory addresses
-            // dbg!(hal_data);
-            // dbg!(&*hal_data);
+            // dbg!(storage);
+            // dbg!(&*storage);
 
             // Print pin values with null pointer checks
             println!(
                 "Pin values D1: {:?}, D2: {:?}",
-                *(*hal_data).d1,
-                *(*hal_data).d2
+                *(*storage).d1,
+                *(*storage).d2
             );
 
             thread::sleep(Duration::from_millis(500));
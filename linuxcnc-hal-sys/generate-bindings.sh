@@ -0,0 +1,32 @@
+#!/bin/bash
+#
+# Regenerate the pre-generated bindings in `src/bindings/` from a LinuxCNC source tree
+#
+# Bindings are generated twice, with `-DRTAPI` and with `-DULAPI`, by the build script's `bindgen`
+# feature. This needs `libclang`, see <https://rust-lang.github.io/rust-bindgen/requirements.html>.
+#
+# Usage:
+#
+#     LINUXCNC_SRC=/path/to/linuxcnc ./generate-bindings.sh 2_9
+
+set -euo pipefail
+
+version=${1:?Usage: LINUXCNC_SRC=/path/to/linuxcnc $0 <version, e.g. 2_9>}
+
+: "${LINUXCNC_SRC:?LINUXCNC_SRC must point to the root of a LinuxCNC source tree}"
+
+cd "$(dirname "$0")"
+
+for mode in rtapi ulapi; do
+    # The build script writes the bindings to its `OUT_DIR`, which is only reported by Cargo
+    out_dir=$(
+        cargo check --no-default-features --features "bindgen,no-link,$mode" --message-format=json |
+            grep '"reason":"build-script-executed"' |
+            grep 'linuxcnc-hal-sys' |
+            sed -E 's/.*"out_dir":"([^"]*)".*/\1/'
+    )
+
+    cp "$out_dir/bindings.rs" "src/bindings/linuxcnc_${version}_${mode}.rs"
+
+    echo "Generated src/bindings/linuxcnc_${version}_${mode}.rs"
+done
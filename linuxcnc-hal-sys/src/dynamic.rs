@@ -0,0 +1,925 @@
+//! Load the HAL library at runtime
+//!
+//! With the `dlopen` feature, this crate does not link against `liblinuxcnchal`. HAL and RTAPI
+//! functions are instead resolved with `dlopen` the first time one of them is called, so the same
+//! binary runs against a run-in-place LinuxCNC build or a packaged install without
+//! `LD_LIBRARY_PATH` or an rpath.
+//!
+//! # Search order
+//!
+//! [`load`] uses the first of the following that provides `hal_init`:
+//!
+//! 1. The current process. Realtime components loaded by `rtapi_app` use the HAL functions that
+//!    LinuxCNC has already loaded.
+//! 1. The file in the `LINUXCNC_HAL_LIBRARY` environment variable
+//! 1. `$EMC2_HOME/lib/liblinuxcnchal.so.0`. `EMC2_HOME` is set by LinuxCNC's `rip-environment`
+//!    script and the `linuxcnc` launcher.
+//! 1. `liblinuxcnchal.so.0`, found by the dynamic linker in the system library directories
+//! 1. `/usr/lib/linuxcnc/liblinuxcnchal.so.0`
+//!
+//! Use [`load_from`] to search a different list of paths. The library is loaded once and never
+//! unloaded. If no library has been loaded when a HAL function is called, [`load`] is called
+//! first. This is only tried once: if it fails, HAL functions behave as if they were
+//! [missing](#missing-functions) until [`load`] or [`load_from`] is called again and succeeds.
+//!
+//! # Missing functions
+//!
+//! Functions that the loaded library doesn't provide, e.g. 64 bit pins in older LinuxCNC versions,
+//! don't stop the library from loading. Calling one returns `-ENOSYS` from functions that return
+//! an error code, or a null pointer, zero or `false` from other functions. The same applies if no
+//! library could be loaded at all. [`Library::missing`] lists the unavailable functions.
+//!
+//! # Variadic functions
+//!
+//! Functions that take a variable number of arguments, like `rtapi_print_msg` and
+//! `hal_pin_bit_newf`, can't be wrapped in Rust. Call them through [`Library::functions`] instead.
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal_sys::{dynamic, msg_level_t_RTAPI_MSG_INFO};
+//!
+//! let library = dynamic::load()?;
+//!
+//! if let Some(rtapi_print_msg) = library.functions().rtapi_print_msg {
+//!     unsafe { rtapi_print_msg(msg_level_t_RTAPI_MSG_INFO, "Hello\n\0".as_ptr().cast()) };
+//! }
+//! # Ok::<(), dynamic::LoadError>(())
+//! ```
+
+use crate::*;
+use std::{
+    env,
+    error::Error,
+    fmt,
+    os::raw::*,
+    path::{Path, PathBuf},
+    ptr,
+    sync::OnceLock,
+};
+
+/// File name of the HAL library
+pub const LIBRARY_NAME: &str = "liblinuxcnchal.so.0";
+
+/// Environment variable containing the full path to the HAL library
+pub const LIBRARY_PATH_VAR: &str = "LINUXCNC_HAL_LIBRARY";
+
+static LIBRARY: OnceLock<Library> = OnceLock::new();
+
+/// The result of loading the library when a HAL function is first called, so a failed search is
+/// not repeated on every call
+static DEFAULT_LIBRARY: OnceLock<Option<&'static Library>> = OnceLock::new();
+
+/// Calls the given macro with every wrapped HAL and RTAPI function in the bindings
+///
+/// `hal_init` is wrapped by hand, so it is not in this list.
+macro_rules! hal_functions {
+    ($($callback:tt)*) => {
+        $($callback)*! {
+            functions {
+                fn rtapi_init(modname: *const c_char) -> c_int;
+                fn rtapi_exit(module_id: c_int) -> c_int;
+                fn rtapi_vsnprintf(
+                    buf: *mut c_char,
+                    size: c_ulong,
+                    fmt: *const c_char,
+                    ap: *mut __va_list_tag,
+                ) -> c_int;
+                fn rtapi_set_msg_level(level: c_int) -> c_int;
+                fn rtapi_get_msg_level() -> c_int;
+                fn rtapi_set_msg_handler(handler: rtapi_msg_handler_t);
+                fn rtapi_get_msg_handler() -> rtapi_msg_handler_t;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_clock_set_period(nsecs: c_long) -> c_long;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_delay(nsec: c_long);
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_delay_max() -> c_long;
+                fn rtapi_get_time() -> c_longlong;
+                fn rtapi_get_clocks() -> c_longlong;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_prio_highest() -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_prio_lowest() -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_prio_next_higher(prio: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_prio_next_lower(prio: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_task_new(
+                    taskcode: Option<unsafe extern "C" fn(arg1: *mut c_void)>,
+                    arg: *mut c_void,
+                    prio: c_int,
+                    owner: c_int,
+                    stacksize: c_ulong,
+                    uses_fp: c_int,
+                ) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_task_delete(task_id: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_task_start(task_id: c_int, period_nsec: c_ulong) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_wait();
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_task_resume(task_id: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_task_pause(task_id: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_task_self() -> c_int;
+                fn rtapi_shmem_new(key: c_int, module_id: c_int, size: c_ulong) -> c_int;
+                fn rtapi_shmem_delete(shmem_id: c_int, module_id: c_int) -> c_int;
+                fn rtapi_shmem_getptr(shmem_id: c_int, ptr: *mut *mut c_void) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_sem_new(key: c_int, module_id: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_sem_delete(sem_id: c_int, module_id: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_sem_give(sem_id: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_sem_take(sem_id: c_int) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_sem_try(sem_id: c_int) -> c_int;
+                fn rtapi_fifo_new(
+                    key: c_int,
+                    module_id: c_int,
+                    size: c_ulong,
+                    mode: c_char,
+                ) -> c_int;
+                fn rtapi_fifo_delete(fifo_id: c_int, module_id: c_int) -> c_int;
+                fn rtapi_fifo_read(fifo_id: c_int, buf: *mut c_char, size: c_ulong) -> c_int;
+                fn rtapi_fifo_write(fifo_id: c_int, buf: *mut c_char, size: c_ulong) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_irq_new(
+                    irq_num: c_uint,
+                    owner: c_int,
+                    handler: Option<unsafe extern "C" fn()>,
+                ) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_irq_delete(irq_num: c_uint) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_enable_interrupt(irq: c_uint) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn rtapi_disable_interrupt(irq: c_uint) -> c_int;
+                fn rtapi_outb(byte: c_uchar, port: c_uint);
+                fn rtapi_inb(port: c_uint) -> c_uchar;
+                fn rtapi_spawn_as_root(
+                    pid: *mut pid_t,
+                    path: *const c_char,
+                    file_actions: *const posix_spawn_file_actions_t,
+                    attrp: *const posix_spawnattr_t,
+                    argv: *const *mut c_char,
+                    envp: *const *mut c_char,
+                ) -> c_int;
+                fn rtapi_spawnp_as_root(
+                    pid: *mut pid_t,
+                    path: *const c_char,
+                    file_actions: *const posix_spawn_file_actions_t,
+                    attrp: *const posix_spawnattr_t,
+                    argv: *const *mut c_char,
+                    envp: *const *mut c_char,
+                ) -> c_int;
+                fn rtapi_is_kernelspace() -> c_int;
+                fn rtapi_is_realtime() -> c_int;
+                fn rtapi_open_as_root(filename: *const c_char, mode: c_int) -> c_int;
+                fn hal_exit(comp_id: c_int) -> c_int;
+                fn hal_malloc(size: c_long) -> *mut c_void;
+                fn hal_ready(comp_id: c_int) -> c_int;
+                fn hal_set_unready(comp_id: c_int) -> c_int;
+                fn hal_unready(comp_id: c_int) -> c_int;
+                fn hal_comp_name(comp_id: c_int) -> *mut c_char;
+                fn hal_get_lock() -> c_uchar;
+                fn hal_pin_bit_new(
+                    name: *const c_char,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_bit_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_float_new(
+                    name: *const c_char,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut real_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_u32_new(
+                    name: *const c_char,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_u32_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_s32_new(
+                    name: *const c_char,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_s32_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_u64_new(
+                    name: *const c_char,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_u64_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_s64_new(
+                    name: *const c_char,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_s64_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_port_new(
+                    name: *const c_char,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_port_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_new(
+                    name: *const c_char,
+                    type_: hal_type_t,
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut c_void,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_pin_alias(pin_name: *const c_char, alias: *const c_char) -> c_int;
+                fn hal_signal_new(name: *const c_char, type_: hal_type_t) -> c_int;
+                fn hal_signal_delete(name: *const c_char) -> c_int;
+                fn hal_link(pin_name: *const c_char, sig_name: *const c_char) -> c_int;
+                fn hal_unlink(pin_name: *const c_char) -> c_int;
+                fn hal_param_bit_new(
+                    name: *const c_char,
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_bit_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_param_float_new(
+                    name: *const c_char,
+                    dir: hal_param_dir_t,
+                    data_addr: *mut real_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_param_u32_new(
+                    name: *const c_char,
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_u32_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_param_s32_new(
+                    name: *const c_char,
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_s32_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_param_u64_new(
+                    name: *const c_char,
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_u64_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_param_s64_new(
+                    name: *const c_char,
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_s64_t,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_param_new(
+                    name: *const c_char,
+                    type_: hal_type_t,
+                    dir: hal_param_dir_t,
+                    data_addr: *mut c_void,
+                    comp_id: c_int,
+                ) -> c_int;
+                fn hal_param_bit_set(name: *const c_char, value: c_int) -> c_int;
+                fn hal_param_float_set(name: *const c_char, value: f64) -> c_int;
+                fn hal_param_u32_set(name: *const c_char, value: c_ulong) -> c_int;
+                fn hal_param_s32_set(name: *const c_char, value: c_long) -> c_int;
+                fn hal_param_u64_set(name: *const c_char, value: c_ulong) -> c_int;
+                fn hal_param_s64_set(name: *const c_char, value: c_long) -> c_int;
+                fn hal_param_alias(pin_name: *const c_char, alias: *const c_char) -> c_int;
+                fn hal_param_set(
+                    name: *const c_char,
+                    type_: hal_type_t,
+                    value_addr: *mut c_void,
+                ) -> c_int;
+                fn hal_get_pin_value_by_name(
+                    name: *const c_char,
+                    type_: *mut hal_type_t,
+                    data: *mut *mut hal_data_u,
+                    connected: *mut bool,
+                ) -> c_int;
+                fn hal_get_signal_value_by_name(
+                    name: *const c_char,
+                    type_: *mut hal_type_t,
+                    data: *mut *mut hal_data_u,
+                    has_writers: *mut bool,
+                ) -> c_int;
+                fn hal_get_param_value_by_name(
+                    name: *const c_char,
+                    type_: *mut hal_type_t,
+                    data: *mut *mut hal_data_u,
+                ) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn hal_export_funct(
+                    name: *const c_char,
+                    funct: Option<unsafe extern "C" fn(arg1: *mut c_void, arg2: c_long)>,
+                    arg: *mut c_void,
+                    uses_fp: c_int,
+                    reentrant: c_int,
+                    comp_id: c_int,
+                ) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn hal_create_thread(
+                    name: *const c_char,
+                    period_nsec: c_ulong,
+                    uses_fp: c_int,
+                ) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn hal_thread_delete(name: *const c_char) -> c_int;
+                fn hal_add_funct_to_thread(
+                    funct_name: *const c_char,
+                    thread_name: *const c_char,
+                    position: c_int,
+                ) -> c_int;
+                fn hal_del_funct_from_thread(
+                    funct_name: *const c_char,
+                    thread_name: *const c_char,
+                ) -> c_int;
+                fn hal_start_threads() -> c_int;
+                fn hal_stop_threads() -> c_int;
+                fn hal_set_constructor(comp_id: c_int, make: constructor) -> c_int;
+                fn hal_port_read(port: hal_port_t, dest: *mut c_char, count: c_uint) -> bool;
+                fn hal_port_peek(port: hal_port_t, dest: *mut c_char, count: c_uint) -> bool;
+                fn hal_port_peek_commit(port: hal_port_t, count: c_uint) -> bool;
+                fn hal_port_write(port: hal_port_t, src: *const c_char, count: c_uint) -> bool;
+                fn hal_port_readable(port: hal_port_t) -> c_uint;
+                fn hal_port_writable(port: hal_port_t) -> c_uint;
+                fn hal_port_buffer_size(port: hal_port_t) -> c_uint;
+                fn hal_port_clear(port: hal_port_t);
+                fn hal_stream_create(
+                    stream: *mut hal_stream_t,
+                    comp: c_int,
+                    key: c_int,
+                    depth: c_int,
+                    typestring: *const c_char,
+                ) -> c_int;
+                fn hal_stream_destroy(stream: *mut hal_stream_t);
+                fn hal_stream_attach(
+                    stream: *mut hal_stream_t,
+                    comp: c_int,
+                    key: c_int,
+                    typestring: *const c_char,
+                ) -> c_int;
+                fn hal_stream_detach(stream: *mut hal_stream_t) -> c_int;
+                fn hal_stream_element_count(stream: *mut hal_stream_t) -> c_int;
+                fn hal_stream_element_type(stream: *mut hal_stream_t, idx: c_int) -> hal_type_t;
+                fn hal_stream_read(
+                    stream: *mut hal_stream_t,
+                    buf: *mut hal_stream_data,
+                    sampleno: *mut c_uint,
+                ) -> c_int;
+                fn hal_stream_readable(stream: *mut hal_stream_t) -> bool;
+                fn hal_stream_depth(stream: *mut hal_stream_t) -> c_int;
+                fn hal_stream_maxdepth(stream: *mut hal_stream_t) -> c_int;
+                fn hal_stream_num_underruns(stream: *mut hal_stream_t) -> c_int;
+                fn hal_stream_num_overruns(stream: *mut hal_stream_t) -> c_int;
+                fn hal_stream_write(stream: *mut hal_stream_t, buf: *mut hal_stream_data) -> c_int;
+                fn hal_stream_writable(stream: *mut hal_stream_t) -> bool;
+            }
+            variadic {
+                fn rtapi_snprintf(
+                    buf: *mut c_char,
+                    size: c_ulong,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn rtapi_print(fmt: *const c_char, ...);
+                fn rtapi_print_msg(level: msg_level_t, fmt: *const c_char, ...);
+                fn hal_pin_bit_newf(
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_bit_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_pin_float_newf(
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut real_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_pin_u32_newf(
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_u32_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_pin_s32_newf(
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_s32_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_pin_u64_newf(
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_u64_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_pin_s64_newf(
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_s64_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_pin_port_newf(
+                    dir: hal_pin_dir_t,
+                    data_ptr_addr: *mut *mut hal_port_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_param_bit_newf(
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_bit_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_param_float_newf(
+                    dir: hal_param_dir_t,
+                    data_addr: *mut real_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_param_u32_newf(
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_u32_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_param_s32_newf(
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_s32_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_param_u64_newf(
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_u64_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                fn hal_param_s64_newf(
+                    dir: hal_param_dir_t,
+                    data_addr: *mut hal_s64_t,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+                #[cfg(not(feature = "ulapi"))]
+                fn hal_export_functf(
+                    funct: Option<unsafe extern "C" fn(arg1: *mut c_void, arg2: c_long)>,
+                    arg: *mut c_void,
+                    uses_fp: c_int,
+                    reentrant: c_int,
+                    comp_id: c_int,
+                    fmt: *const c_char,
+                    ...
+                ) -> c_int;
+            }
+        }
+    };
+}
+
+/// Define [`Functions`] and how to load it from a library
+macro_rules! define_functions {
+    (
+        functions {
+            $(
+                $(#[$attr:meta])*
+                fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;
+            )*
+        }
+        variadic {
+            $(
+                $(#[$variadic_attr:meta])*
+                fn $variadic_name:ident($($variadic_arg:ident: $variadic_ty:ty,)+ ...) $(-> $variadic_ret:ty)?;
+            )*
+        }
+    ) => {
+        /// Pointers to the functions in a HAL library loaded at runtime
+        ///
+        /// Each function is `None` if the library doesn't provide it.
+        pub struct Functions {
+            /// `hal_init`
+            pub hal_init: Option<unsafe extern "C" fn(name: *const c_char) -> c_int>,
+            $(
+                $(#[$attr])*
+                #[doc = concat!("`", stringify!($name), "`")]
+                pub $name: Option<unsafe extern "C" fn($($arg: $ty),*) $(-> $ret)?>,
+            )*
+            $(
+                $(#[$variadic_attr])*
+                #[doc = concat!("`", stringify!($variadic_name), "`")]
+                pub $variadic_name:
+                    Option<unsafe extern "C" fn($($variadic_arg: $variadic_ty,)+ ...) $(-> $variadic_ret)?>,
+            )*
+        }
+
+        impl Functions {
+            /// Look up every function in `library`, adding the names of any that are missing to
+            /// `missing`
+            unsafe fn load(library: &libloading::Library, missing: &mut Vec<&'static str>) -> Self {
+                Self {
+                    hal_init: symbol(library, "hal_init", missing),
+                    $(
+                        $(#[$attr])*
+                        $name: symbol(library, stringify!($name), missing),
+                    )*
+                    $(
+                        $(#[$variadic_attr])*
+                        $variadic_name: symbol(library, stringify!($variadic_name), missing),
+                    )*
+                }
+            }
+        }
+    };
+}
+
+/// Define a wrapper with the same signature as each function in the bindings, which calls the
+/// function in the loaded library
+macro_rules! define_wrappers {
+    (
+        functions {
+            $(
+                $(#[$attr:meta])*
+                fn $name:ident($($arg:ident: $ty:ty),* $(,)?) $(-> $ret:ty)?;
+            )*
+        }
+        variadic { $($variadic:tt)* }
+    ) => {
+        $(
+            $(#[$attr])*
+            #[doc = concat!("Call `", stringify!($name), "` in the HAL library loaded at runtime")]
+            ///
+            /// See the [`dynamic`](crate::dynamic) module for what is returned if the library or
+            /// function is not available.
+            ///
+            /// # Safety
+            ///
+            #[doc = concat!("The same as calling `", stringify!($name), "` directly.")]
+            pub unsafe fn $name($($arg: $ty),*) $(-> $ret)? {
+                match $crate::dynamic::functions().and_then(|functions| functions.$name) {
+                    Some(function) => function($($arg),*),
+                    None => $crate::dynamic::Missing::missing(),
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use {define_wrappers, hal_functions};
+
+hal_functions!(define_functions);
+
+/// Pointer to the start of HAL shared memory
+///
+/// Statics can't be wrapped like functions, so this is copied from the loaded library by
+/// [`hal_init`]. It is null until a component has been initialised.
+#[cfg(feature = "linuxcnc-2-9")]
+pub static mut hal_shmem_base: *mut c_char = ptr::null_mut();
+
+/// Pointer to the HAL data structure in shared memory
+///
+/// Statics can't be wrapped like functions, so this is copied from the loaded library by
+/// [`hal_init`]. It is null until a component has been initialised.
+#[cfg(feature = "linuxcnc-2-9")]
+pub static mut hal_data: *mut hal_data_t = ptr::null_mut();
+
+/// Call `hal_init` in the HAL library loaded at runtime
+///
+/// On success, `hal_shmem_base` and `hal_data` are updated from the loaded library.
+///
+/// # Safety
+///
+/// The same as calling `hal_init` directly.
+pub unsafe fn hal_init(name: *const c_char) -> c_int {
+    let Some(library) = library() else {
+        return Missing::missing();
+    };
+
+    let Some(init) = library.functions.hal_init else {
+        return Missing::missing();
+    };
+
+    let id = init(name);
+
+    #[cfg(feature = "linuxcnc-2-9")]
+    if id > 0 {
+        hal_shmem_base = library.shmem_base.map_or(ptr::null_mut(), |base| *base);
+        hal_data = library.data.map_or(ptr::null_mut(), |data| *data);
+    }
+
+    id
+}
+
+/// A HAL library loaded at runtime
+pub struct Library {
+    /// Path the library was loaded from, or `None` for the current process
+    path: Option<PathBuf>,
+
+    functions: Functions,
+
+    /// Names of the functions the library doesn't provide
+    missing: Vec<&'static str>,
+
+    /// Address of `hal_shmem_base` in the library
+    #[cfg(feature = "linuxcnc-2-9")]
+    shmem_base: Option<*mut *mut c_char>,
+
+    /// Address of `hal_data` in the library
+    #[cfg(feature = "linuxcnc-2-9")]
+    data: Option<*mut *mut hal_data_t>,
+
+    /// Kept open so the function pointers above stay valid
+    _library: libloading::Library,
+}
+
+// SAFETY: The library is never unloaded, so the function pointers and addresses it holds are valid
+// for the life of the program. They are never modified after loading.
+unsafe impl Send for Library {}
+unsafe impl Sync for Library {}
+
+impl Library {
+    /// Open the library at `path`, or the current process if `path` is `None`
+    unsafe fn open(path: Option<&Path>) -> Result<Self, LoadFailure> {
+        let library: libloading::Library = match path {
+            Some(path) => libloading::Library::new(path).map_err(LoadFailure::Open)?,
+            None => libloading::os::unix::Library::this().into(),
+        };
+
+        let mut missing = Vec::new();
+
+        let functions = Functions::load(&library, &mut missing);
+
+        if functions.hal_init.is_none() {
+            return Err(LoadFailure::NotHal);
+        }
+
+        Ok(Self {
+            path: path.map(Path::to_path_buf),
+            functions,
+            missing,
+            #[cfg(feature = "linuxcnc-2-9")]
+            shmem_base: library
+                .get::<*mut *mut c_char>(b"hal_shmem_base")
+                .ok()
+                .map(|symbol| *symbol),
+            #[cfg(feature = "linuxcnc-2-9")]
+            data: library
+                .get::<*mut *mut hal_data_t>(b"hal_data")
+                .ok()
+                .map(|symbol| *symbol),
+            _library: library,
+        })
+    }
+
+    /// Get the path the library was loaded from
+    ///
+    /// Returns `None` if the HAL functions were found in the current process.
+    pub fn path(&self) -> Option<&Path> {
+        self.path.as_deref()
+    }
+
+    /// Get pointers to the functions in the library
+    pub fn functions(&self) -> &Functions {
+        &self.functions
+    }
+
+    /// Get the names of the functions the library doesn't provide
+    pub fn missing(&self) -> &[&'static str] {
+        &self.missing
+    }
+}
+
+impl fmt::Debug for Library {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Library")
+            .field("path", &self.path)
+            .field("missing", &self.missing)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The reason a path could not be used as the HAL library
+#[derive(Debug)]
+pub enum LoadFailure {
+    /// The library could not be opened
+    Open(libloading::Error),
+
+    /// The library was opened, but does not contain `hal_init`
+    NotHal,
+}
+
+impl fmt::Display for LoadFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Open(e) => write!(f, "{}", e),
+            Self::NotHal => f.write_str("not a HAL library"),
+        }
+    }
+}
+
+impl Error for LoadFailure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Open(e) => Some(e),
+            Self::NotHal => None,
+        }
+    }
+}
+
+/// The HAL library could not be loaded from any of the searched paths
+#[derive(Debug)]
+pub struct LoadError {
+    attempts: Vec<(PathBuf, LoadFailure)>,
+}
+
+impl LoadError {
+    /// Get every path that was tried, and why it could not be used
+    pub fn attempts(&self) -> &[(PathBuf, LoadFailure)] {
+        &self.attempts
+    }
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("the HAL library could not be loaded")?;
+
+        for (i, (path, failure)) in self.attempts.iter().enumerate() {
+            let separator = if i == 0 { ". Tried " } else { ", " };
+
+            write!(f, "{}{} ({})", separator, path.display(), failure)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Error for LoadError {}
+
+/// Get the default list of paths searched by [`load`]
+///
+/// See the [module documentation](self) for the search order.
+pub fn default_search_path() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+
+    if let Some(path) = env::var_os(LIBRARY_PATH_VAR) {
+        paths.push(PathBuf::from(path));
+    }
+
+    if let Some(home) = env::var_os("EMC2_HOME") {
+        paths.push(Path::new(&home).join("lib").join(LIBRARY_NAME));
+    }
+
+    paths.push(PathBuf::from(LIBRARY_NAME));
+    paths.push(Path::new("/usr/lib/linuxcnc").join(LIBRARY_NAME));
+
+    paths
+}
+
+/// Load the HAL library from the [default search path](default_search_path)
+///
+/// If a library has already been loaded, it is returned instead.
+pub fn load() -> Result<&'static Library, LoadError> {
+    load_from(default_search_path())
+}
+
+/// Load the HAL library from the first of `paths` that provides `hal_init`
+///
+/// The current process is checked before `paths`. If a library has already been loaded, it is
+/// returned instead.
+pub fn load_from<P>(paths: impl IntoIterator<Item = P>) -> Result<&'static Library, LoadError>
+where
+    P: AsRef<Path>,
+{
+    if let Some(library) = LIBRARY.get() {
+        return Ok(library);
+    }
+
+    if let Ok(library) = unsafe { Library::open(None) } {
+        log::debug!("Using HAL functions from the current process");
+
+        return Ok(LIBRARY.get_or_init(|| library));
+    }
+
+    let mut attempts = Vec::new();
+
+    for path in paths {
+        let path = path.as_ref();
+
+        match unsafe { Library::open(Some(path)) } {
+            Ok(library) => {
+                log::debug!(
+                    "Loaded HAL library from {}, missing functions: {:?}",
+                    path.display(),
+                    library.missing
+                );
+
+                return Ok(LIBRARY.get_or_init(|| library));
+            }
+            Err(failure) => attempts.push((path.to_path_buf(), failure)),
+        }
+    }
+
+    Err(LoadError { attempts })
+}
+
+/// Get the HAL library, if one has been loaded
+pub fn loaded() -> Option<&'static Library> {
+    LIBRARY.get()
+}
+
+/// Get the loaded HAL library, loading it from the default search path if necessary
+///
+/// The default search path is only tried once, as this is called by every HAL function.
+fn library() -> Option<&'static Library> {
+    LIBRARY
+        .get()
+        .or_else(|| *DEFAULT_LIBRARY.get_or_init(|| load().map_err(|e| log::error!("{}", e)).ok()))
+}
+
+/// Get the functions in the loaded HAL library, loading it if necessary
+pub(crate) fn functions() -> Option<&'static Functions> {
+    library().map(|library| &library.functions)
+}
+
+/// Look up a function in `library`, adding its name to `missing` if it doesn't exist
+unsafe fn symbol<T: Copy>(
+    library: &libloading::Library,
+    name: &'static str,
+    missing: &mut Vec<&'static str>,
+) -> Option<T> {
+    match library.get::<T>(name.as_bytes()) {
+        Ok(symbol) => Some(*symbol),
+        Err(_) => {
+            missing.push(name);
+
+            None
+        }
+    }
+}
+
+/// The value a wrapper returns when the function it calls is not available
+pub(crate) trait Missing {
+    fn missing() -> Self;
+}
+
+impl Missing for i32 {
+    fn missing() -> Self {
+        -(ENOSYS as i32)
+    }
+}
+
+impl Missing for i64 {
+    fn missing() -> Self {
+        -(ENOSYS as i64)
+    }
+}
+
+impl Missing for u32 {
+    fn missing() -> Self {
+        0
+    }
+}
+
+impl Missing for u8 {
+    fn missing() -> Self {
+        0
+    }
+}
+
+impl Missing for bool {
+    fn missing() -> Self {
+        false
+    }
+}
+
+impl Missing for () {
+    fn missing() -> Self {}
+}
+
+impl<T> Missing for *mut T {
+    fn missing() -> Self {
+        ptr::null_mut()
+    }
+}
+
+impl<T> Missing for Option<T> {
+    fn missing() -> Self {
+        None
+    }
+}
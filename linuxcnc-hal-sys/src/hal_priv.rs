@@ -0,0 +1,186 @@
+//! HAL shared memory structures from `src/hal/hal_priv.h`
+//!
+//! These definitions are maintained by hand, not generated by bindgen, as `hal_priv.h` is private
+//! to LinuxCNC and not part of the public HAL API covered by the generated bindings. They match
+//! LinuxCNC 2.9 and must be checked against `hal_priv.h` whenever bindings for another LinuxCNC
+//! version are added.
+//!
+//! Everything in HAL shared memory refers to other objects by their offset from
+//! [`hal_shmem_base`](crate::hal_shmem_base), where an offset of `0` means null. The HAL mutex in
+//! [`hal_data_t`] must be held while walking any of the lists, see
+//! [`rtapi_mutex_get`](crate::rtapi_mutex_get).
+
+use crate::{
+    constructor, hal_bit_t, hal_data_u, hal_param_dir_t, hal_pin_dir_t, hal_s32_t, hal_type_t,
+    HAL_NAME_LEN,
+};
+use std::os::raw::{c_char, c_int, c_long, c_uchar, c_ulong, c_void};
+
+/// Length of the name buffers, including the nul terminator
+const NAME_LEN: usize = HAL_NAME_LEN as usize + 1;
+
+/// A node in a circular doubly linked list, e.g. the functions in a thread
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_list_t {
+    pub next: c_int,
+    pub prev: c_int,
+}
+
+/// The header at the start of HAL shared memory
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_data_t {
+    pub version: c_int,
+    /// The HAL mutex, see [`rtapi_mutex_get`](crate::rtapi_mutex_get)
+    pub mutex: c_ulong,
+    pub shmem_avail: hal_s32_t,
+    pub pending_constructor: constructor,
+    pub constructor_prefix: [c_char; NAME_LEN],
+    pub constructor_arg: [c_char; NAME_LEN],
+    pub shmem_bot: c_int,
+    pub shmem_top: c_int,
+    pub comp_list_ptr: c_int,
+    pub pin_list_ptr: c_int,
+    pub sig_list_ptr: c_int,
+    pub param_list_ptr: c_int,
+    pub funct_list_ptr: c_int,
+    pub thread_list_ptr: c_int,
+    pub base_period: c_long,
+    pub threads_running: c_int,
+    pub oldname_free_ptr: c_int,
+    pub comp_free_ptr: c_int,
+    pub pin_free_ptr: c_int,
+    pub sig_free_ptr: c_int,
+    pub param_free_ptr: c_int,
+    pub funct_free_ptr: c_int,
+    pub funct_entry_free: hal_list_t,
+    pub thread_free_ptr: c_int,
+    pub exact_base_period: c_int,
+    pub lock: c_uchar,
+}
+
+/// A component
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_comp_t {
+    pub next_ptr: c_int,
+    pub comp_id: c_int,
+    pub mem_id: c_int,
+    /// `0` for userspace components, `1` for realtime components
+    pub type_: c_int,
+    pub ready: c_int,
+    pub pid: c_int,
+    pub shmem_base: *mut c_void,
+    pub name: [c_char; NAME_LEN],
+    pub make: constructor,
+    pub insmod_args: c_int,
+}
+
+/// A pin
+#[repr(C)]
+#[derive(Copy, Clone)]
+pub struct hal_pin_t {
+    pub next_ptr: c_int,
+    pub data_ptr_addr: c_int,
+    pub owner_ptr: c_int,
+    /// The signal the pin is linked to, or `0` if it is unlinked
+    pub signal: c_int,
+    /// The value an unlinked pin points to
+    pub dummysig: hal_data_u,
+    /// The [`hal_oldname_t`] holding the original name of an aliased pin, or `0`
+    pub oldname: c_int,
+    pub type_: hal_type_t,
+    pub dir: hal_pin_dir_t,
+    pub name: [c_char; NAME_LEN],
+}
+
+/// A signal
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_sig_t {
+    pub next_ptr: c_int,
+    pub data_ptr: c_int,
+    pub type_: hal_type_t,
+    pub readers: c_int,
+    pub writers: c_int,
+    pub bidirs: c_int,
+    pub name: [c_char; NAME_LEN],
+}
+
+/// A parameter
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_param_t {
+    pub next_ptr: c_int,
+    pub data_ptr: c_int,
+    pub owner_ptr: c_int,
+    /// The [`hal_oldname_t`] holding the original name of an aliased parameter, or `0`
+    pub oldname: c_int,
+    pub type_: hal_type_t,
+    pub dir: hal_param_dir_t,
+    pub name: [c_char; NAME_LEN],
+}
+
+/// An exported function
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_funct_t {
+    pub next_ptr: c_int,
+    pub uses_fp: c_int,
+    pub owner_ptr: c_int,
+    pub reentrant: c_int,
+    pub users: c_int,
+    pub arg: *mut c_void,
+    pub funct: Option<unsafe extern "C" fn(arg1: *mut c_void, arg2: c_long)>,
+    pub runtime: *mut hal_s32_t,
+    pub maxtime: hal_s32_t,
+    pub maxtime_increased: hal_bit_t,
+    pub name: [c_char; NAME_LEN],
+}
+
+/// A function added to a thread, linked into the thread's `funct_list`
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_funct_entry_t {
+    pub links: hal_list_t,
+    pub arg: *mut c_void,
+    pub funct: Option<unsafe extern "C" fn(arg1: *mut c_void, arg2: c_long)>,
+    /// The [`hal_funct_t`] this entry calls
+    pub funct_ptr: c_int,
+}
+
+/// A realtime thread
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_thread_t {
+    pub next_ptr: c_int,
+    pub uses_fp: c_int,
+    pub period: c_long,
+    pub priority: c_int,
+    pub task_id: c_int,
+    pub runtime: *mut hal_s32_t,
+    pub maxtime: hal_s32_t,
+    /// The head of the circular list of [`hal_funct_entry_t`]s, in the order they are called
+    pub funct_list: hal_list_t,
+    pub cpu_id: c_int,
+    pub name: [c_char; NAME_LEN],
+}
+
+/// The original name of a pin or parameter that has been given an alias
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct hal_oldname_t {
+    pub next_ptr: c_int,
+    pub name: [c_char; NAME_LEN],
+}
+
+// With the `dlopen` feature, these are replaced by the globals in `dynamic`
+#[cfg(not(feature = "dlopen"))]
+extern "C" {
+    /// The base address of HAL shared memory, set by `hal_init`
+    pub static mut hal_shmem_base: *mut c_char;
+
+    /// The [`hal_data_t`] at the start of HAL shared memory, set by `hal_init`
+    pub static mut hal_data: *mut hal_data_t;
+}
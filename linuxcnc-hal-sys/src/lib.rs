@@ -4,8 +4,18 @@
 //!
 //! # Development setup
 //!
-//! [`bindgen`](https://github.com/rust-lang/rust-bindgen) must be set up correctly. Follow the
-//! [requirements section of its docs](https://rust-lang.github.io/rust-bindgen/requirements.html).
+//! Nothing needs to be installed to build against the pre-generated bindings. Only the `bindgen`
+//! feature runs [`bindgen`](https://github.com/rust-lang/rust-bindgen), which needs `libclang` and the
+//! LinuxCNC source code. Follow the
+//! [requirements section of its docs](https://rust-lang.github.io/rust-bindgen/requirements.html) if
+//! you enable it.
+//!
+//! The pre-generated bindings in `src/bindings/` are regenerated for both build modes with
+//! `generate-bindings.sh`, which also needs `libclang`:
+//!
+//! ```bash
+//! LINUXCNC_SRC=/path/to/linuxcnc/source/code ./generate-bindings.sh 2_9
+//! ```
 //!
 //! To run and debug any HAL components, the LinuxCNC simulator can be set up. There's a guide
 //! [here](https://wapl.es/cnc/2020/01/25/linuxcnc-simulator-build-linux-mint.html) for Linux Mint
@@ -13,19 +23,38 @@
 //!
 //! # Project setup
 //!
-//! The `LINUXCNC_SRC` environment variable is required to build this crate. The value must be the
-//! absolute path to the root of the LinuxCNC source code.
-//!
-//! **The version of the LinuxCNC sources must match the LinuxCNC version used in the machine
-//! control.**
+//! Pre-generated bindings are shipped for each supported LinuxCNC version, so neither the LinuxCNC
+//! source code nor `libclang` is required to build this crate. The LinuxCNC version is selected
+//! with a Cargo feature:
+//!
+//! | Feature                  | Bindings                                                       |
+//! | ------------------------ | -------------------------------------------------------------- |
+//! | `linuxcnc-2-9` (default) | LinuxCNC 2.9                                                   |
+//! | `linuxcnc-2-8`           | LinuxCNC 2.8, not shipped yet (see below)                      |
+//! | `bindgen`                | Generated at build time from the source tree at `LINUXCNC_SRC` |
+//!
+//! **The version of the bindings must match the LinuxCNC version used in the machine control.**
+//! The version features are mutually exclusive, so disable the default features to select
+//! `linuxcnc-2-8`.
+//!
+//! Bindings are only shipped for LinuxCNC 2.9 so far. The build fails with `linuxcnc-2-8` until
+//! `src/bindings/linuxcnc_2_8_rtapi.rs` and `src/bindings/linuxcnc_2_8_ulapi.rs` have been generated
+//! from a LinuxCNC 2.8 source tree with `generate-bindings.sh 2_8`. Until then, and for any other
+//! version, enable the `bindgen` feature and set `LINUXCNC_SRC` to the absolute path to the root of
+//! the LinuxCNC source code, checked out to the matching version. `linuxcnc-hal` uses HAL functions
+//! added in 2.9, such as 64 bit pins.
+//!
+//! The HAL shared memory structures from `hal_priv.h`, such as `hal_data_t`, and the ports of the
+//! RTAPI mutex functions aren't generated by bindgen. They are maintained by hand to match LinuxCNC
+//! 2.9, so are only available with the `linuxcnc-2-9` feature. Only enable it alongside `bindgen`
+//! if `LINUXCNC_SRC` is a LinuxCNC 2.9 source tree, otherwise disable the default features:
+//!
+//! ```toml
+//! [dependencies]
+//! linuxcnc-hal-sys = { version = "*", default-features = false, features = [ "bindgen" ] }
+//! ```
 //!
 //! ```bash
-//! # Clone LinuxCNC source code into linuxcnc/
-//! git clone https://github.com/LinuxCNC/linuxcnc.git
-//!
-//! # Check out a specific version tag. This may also be a commit, but must match the version in use by the machine control.
-//! cd linuxcnc && git checkout v2.8.0 && cd ..
-//!
 //! # Create your component lib
 //! cargo new --lib my_comp
 //!
@@ -34,9 +63,59 @@
 //! # Add LinuxCNC HAL bindings as a Cargo dependency with cargo-edit
 //! cargo add linuxcnc-hal-sys
 //!
+//! cargo build
+//! ```
+//!
+//! ## Build modes
+//!
+//! LinuxCNC's headers expose different APIs to realtime components loaded with `loadrt` (compiled
+//! with `-DRTAPI`) and userspace components loaded with `loadusr` (compiled with `-DULAPI`). The
+//! mode is selected with a Cargo feature:
+//!
+//! | Feature | Mode                                                                 |
+//! | ------- | -------------------------------------------------------------------- |
+//! | `rtapi` | Realtime                                                             |
+//! | `ulapi` | Userspace. Functions only available in realtime modules are omitted. |
+//! | Neither | The realtime bindings are used, as in previous versions              |
+//!
+//! The functions omitted in userspace mode are the RTAPI task, semaphore, interrupt, delay and
+//! clock functions, `hal_export_funct`, `hal_create_thread` and `hal_thread_delete`.
+//!
+//! Userspace bindings for LinuxCNC 2.9 aren't shipped yet, so with `ulapi` and the pre-generated
+//! bindings these functions are still declared, as in the realtime bindings. They aren't exported
+//! by the userspace `liblinuxcnchal`, so using them fails to link. Enable the `bindgen` feature to
+//! omit them.
+//!
+//! The two features are mutually exclusive. Cargo unifies features across a workspace, so
+//! userspace and realtime components that should be built in different modes must be built
+//! separately, e.g. with `cargo build -p`.
+//!
+//! ## Linking
+//!
+//! Components are dynamically linked against `liblinuxcnchal`. The build script looks for it in
+//! the following places, in order:
+//!
+//! 1. The directory in the `LINUXCNC_LIB_DIR` environment variable
+//! 1. `$LINUXCNC_SRC/lib`, for a LinuxCNC source tree built to run in place
+//! 1. The `linuxcnc` [pkg-config](https://www.freedesktop.org/wiki/Software/pkg-config/) package
+//! 1. `/usr/lib/linuxcnc`, `/usr/local/lib/linuxcnc`, `/usr/lib` and `/usr/local/lib`
+//!
+//! A run-in-place LinuxCNC build can be used like this:
+//!
+//! ```bash
 //! LINUXCNC_SRC=/path/to/linuxcnc/source/code cargo build
 //! ```
 //!
+//! ## Runtime loading
+//!
+//! With the `dlopen` feature, `liblinuxcnchal` is not linked at build time. It is loaded with `dlopen`
+//! the first time a HAL function is called, from the current process, `$LINUXCNC_HAL_LIBRARY`,
+//! `$EMC2_HOME/lib` or the system library directories. The same binary then runs against both
+//! run-in-place and packaged LinuxCNC installs, without `LD_LIBRARY_PATH`.
+//!
+//! Functions missing from the loaded library, e.g. in older LinuxCNC versions, return `-ENOSYS`
+//! instead of stopping the library from loading. See the `dynamic` module for details.
+//!
 //! # Examples
 //!
 //! ## Running the examples in the LinuxCNC simulator
@@ -78,7 +157,8 @@
 //! ```
 //!
 //! If LinuxCNC is configured to run in place, `liblinuxcnchal.so.0` may not be found on startup. To
-//! fix, try setting the library path with e.g. `export LD_LIBRARY_PATH=~/Repositories/linuxcnc/lib`
+//! fix, try setting the library path with e.g. `export LD_LIBRARY_PATH=~/Repositories/linuxcnc/lib`,
+//! or enable the `dlopen` feature to load it at runtime.
 //!
 //! ## Create an input pin
 //!
@@ -217,4 +297,81 @@
 #![allow(non_camel_case_types)]
 #![allow(non_snake_case)]
 
-include!("generated.rs");
+/// The generated bindings
+///
+/// With the `dlopen` feature, the functions declared here are shadowed by the wrappers in
+/// [`dynamic`] and never linked.
+#[cfg_attr(feature = "dlopen", allow(dead_code))]
+mod bindings {
+    #[cfg(feature = "bindgen")]
+    include!(concat!(env!("OUT_DIR"), "/bindings.rs"));
+
+    // Bindings generated with `-DULAPI` aren't shipped for LinuxCNC 2.9 yet, so userspace
+    // components use the realtime bindings. Include `linuxcnc_2_9_ulapi.rs` with `ulapi` once it
+    // has been generated with `generate-bindings.sh 2_9`.
+    #[cfg(all(not(feature = "bindgen"), feature = "linuxcnc-2-9"))]
+    include!("bindings/linuxcnc_2_9_rtapi.rs");
+
+    // Not shipped yet. The build script fails with instructions if these haven't been generated.
+    // Enabling both versions is reported by the `compile_error!` below instead.
+    #[cfg(all(
+        not(feature = "bindgen"),
+        feature = "linuxcnc-2-8",
+        not(feature = "linuxcnc-2-9"),
+        not(feature = "ulapi")
+    ))]
+    include!("bindings/linuxcnc_2_8_rtapi.rs");
+
+    #[cfg(all(
+        not(feature = "bindgen"),
+        feature = "linuxcnc-2-8",
+        not(feature = "linuxcnc-2-9"),
+        feature = "ulapi"
+    ))]
+    include!("bindings/linuxcnc_2_8_ulapi.rs");
+}
+
+pub use bindings::*;
+
+// Written by hand to match LinuxCNC 2.9, so they aren't available for any other version
+#[cfg(feature = "linuxcnc-2-9")]
+mod hal_priv;
+#[cfg(feature = "linuxcnc-2-9")]
+mod rtapi_mutex;
+
+#[cfg(feature = "linuxcnc-2-9")]
+pub use hal_priv::*;
+#[cfg(feature = "linuxcnc-2-9")]
+pub use rtapi_mutex::*;
+
+#[cfg(feature = "dlopen")]
+pub mod dynamic;
+
+#[cfg(feature = "dlopen")]
+pub use dynamic::hal_init;
+
+#[cfg(all(feature = "dlopen", feature = "linuxcnc-2-9"))]
+pub use dynamic::{hal_data, hal_shmem_base};
+
+#[cfg(feature = "dlopen")]
+use std::os::raw::*;
+
+#[cfg(feature = "dlopen")]
+dynamic::hal_functions!(dynamic::define_wrappers);
+
+#[cfg(all(feature = "rtapi", feature = "ulapi"))]
+compile_error!("The `rtapi` and `ulapi` features are mutually exclusive");
+
+#[cfg(all(feature = "linuxcnc-2-8", feature = "linuxcnc-2-9"))]
+compile_error!(
+    "The `linuxcnc-2-8` and `linuxcnc-2-9` features are mutually exclusive. Disable the default features to use `linuxcnc-2-8`"
+);
+
+#[cfg(not(any(
+    feature = "bindgen",
+    feature = "linuxcnc-2-8",
+    feature = "linuxcnc-2-9"
+)))]
+compile_error!(
+    "A LinuxCNC version feature (e.g. `linuxcnc-2-9`) or the `bindgen` feature must be enabled"
+);
@@ -0,0 +1,65 @@
+//! Ports of the mutex functions in `src/rtapi/rtapi_mutex.h`
+//!
+//! The C functions are `static inline`, so they aren't exported by `liblinuxcnchal` and bindgen
+//! can't generate bindings for them. These are line-for-line ports of the LinuxCNC 2.9 versions,
+//! including the `__sync` builtins used by `rtapi_bitops.h`, which are sequentially consistent.
+//!
+//! The mutex is a single bit in an `unsigned long`, such as [`hal_data_t::mutex`].
+//!
+//! [`hal_data_t::mutex`]: crate::hal_data_t::mutex
+
+use std::{
+    os::raw::{c_int, c_ulong},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+/// Equivalent to `test_and_set_bit(0, mutex)`
+unsafe fn test_and_set_bit(mutex: *mut c_ulong) -> c_ulong {
+    (atomic(mutex).fetch_or(1, Ordering::SeqCst) & 1) as c_ulong
+}
+
+/// Equivalent to `test_and_clear_bit(0, mutex)`
+unsafe fn test_and_clear_bit(mutex: *mut c_ulong) -> c_ulong {
+    (atomic(mutex).fetch_and(!1, Ordering::SeqCst) & 1) as c_ulong
+}
+
+unsafe fn atomic<'a>(mutex: *mut c_ulong) -> &'a AtomicUsize {
+    // `unsigned long` is the same size and alignment as `usize` on every platform LinuxCNC
+    // supports
+    AtomicUsize::from_ptr(mutex.cast())
+}
+
+/// Release a mutex
+///
+/// # Safety
+///
+/// `mutex` must be valid, aligned and only accessed atomically.
+pub unsafe fn rtapi_mutex_give(mutex: *mut c_ulong) {
+    test_and_clear_bit(mutex);
+}
+
+/// Try to take a mutex without blocking
+///
+/// Returns `0` if the mutex was taken, or non-zero if it is held by someone else.
+///
+/// # Safety
+///
+/// `mutex` must be valid, aligned and only accessed atomically.
+pub unsafe fn rtapi_mutex_try(mutex: *mut c_ulong) -> c_int {
+    test_and_set_bit(mutex) as c_int
+}
+
+/// Take a mutex, yielding to the scheduler until it is available
+///
+/// This spins, so it must not be called from a realtime thread.
+///
+/// # Safety
+///
+/// `mutex` must be valid, aligned and only accessed atomically.
+pub unsafe fn rtapi_mutex_get(mutex: *mut c_ulong) {
+    while test_and_set_bit(mutex) != 0 {
+        // `sched_yield()` on Linux
+        thread::yield_now();
+    }
+}
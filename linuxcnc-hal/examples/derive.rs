@@ -0,0 +1,63 @@
+//! Create a component called `derive` with pins and parameters registered by
+//! `#[derive(Resources)]`.
+//!
+//! Pin and parameter names:
+//!
+//! * Input `derive.input-1`
+//! * Output `derive.out`
+//! * Read only parameter `derive.scale`
+//!
+//! Run with `cargo run --example derive --features derive`. The component can be loaded and
+//! connected using something like the following `.hal` file:
+//!
+//! ```ini
+//! loadusr -Wn derive /path/to/hal/derive
+//! net input-1 motion.spindle-speed-out derive.input-1
+//! net output derive.out
+//! ```
+
+// Userspace components poll `HalComponent::should_exit`, which is not available with the `rtapi`
+// feature, so this example is empty in realtime mode
+#![cfg_attr(feature = "rtapi", allow(unused))]
+
+use linuxcnc_hal::{
+    hal_pin::{InputPin, OutputPin},
+    prelude::*,
+    HalComponent, Parameter, Resources,
+};
+use std::{error::Error, thread, time::Duration};
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    input_1: InputPin<'comp, f64>,
+
+    #[hal(name = "out", init = 0.0)]
+    output: OutputPin<'comp, f64>,
+
+    #[hal(readonly, init = 2.0)]
+    scale: Parameter<'comp, f64>,
+}
+
+#[cfg(not(feature = "rtapi"))]
+fn main() -> Result<(), Box<dyn Error>> {
+    rtapi_logger::init().ok();
+
+    let comp = HalComponent::new("derive")?;
+
+    let Comp {
+        input_1,
+        output,
+        scale,
+    } = comp.register()?;
+
+    while !comp.should_exit() {
+        output.set_value(input_1.value()? * scale.value()?)?;
+
+        thread::sleep(Duration::from_millis(1000));
+    }
+
+    Ok(())
+}
+
+#[cfg(feature = "rtapi")]
+fn main() {}
@@ -2,6 +2,10 @@
 //!
 //! This component doesn't register any pins or other resources.
 
+// Userspace components poll `HalComponent::should_exit`, which is not available with the `rtapi`
+// feature, so this example is empty in realtime mode
+#![cfg_attr(feature = "rtapi", allow(unused))]
+
 use linuxcnc_hal::{error::ResourcesError, HalComponent, RegisterResources, Resources};
 use std::{error::Error, thread, time::Duration};
 
@@ -9,17 +13,21 @@ use std::{error::Error, thread, time::Duration};
 #[derive(Debug)]
 struct EmptyResources {}
 
-impl Resources for EmptyResources {
+impl<'comp> Resources<'comp> for EmptyResources {
     type RegisterError = ResourcesError;
 
-    fn register_resources(_comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+    fn register_resources(_comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
         Ok(Self {})
     }
 }
 
+#[cfg(not(feature = "rtapi"))]
 fn main() -> Result<(), Box<dyn Error>> {
     // Create a new HAL component
-    let comp = HalComponent::<EmptyResources>::new("empty")?;
+    let comp = HalComponent::new("empty")?;
+
+    // Mark the component as ready without registering any resources
+    let _resources: EmptyResources = comp.register()?;
 
     while !comp.should_exit() {
         // Main control loop code goes here. This example prints `Poll` every 1000ms. This code can
@@ -34,3 +42,6 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(feature = "rtapi")]
+fn main() {}
@@ -13,23 +13,27 @@
 //! setp params.rw 321
 //! ```
 
+// Userspace components poll `HalComponent::should_exit`, which is not available with the `rtapi`
+// feature, so this example is empty in realtime mode
+#![cfg_attr(feature = "rtapi", allow(unused))]
+
 use linuxcnc_hal::{
     error::ParameterRegisterError, HalComponent, Parameter, RegisterResources, Resources,
 };
 use std::{error::Error, thread, time::Duration};
 
-struct Comp {
+struct Comp<'comp> {
     /// LinuxCNC can only read this parameter.
-    ro: Parameter<f64>,
+    ro: Parameter<'comp, f64>,
 
     /// LinuxCNC can read and set this parameter.
-    rw: Parameter<u32>,
+    rw: Parameter<'comp, u32>,
 }
 
-impl Resources for Comp {
+impl<'comp> Resources<'comp> for Comp<'comp> {
     type RegisterError = ParameterRegisterError;
 
-    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
         Ok(Comp {
             ro: comp.register_readonly_parameter("ro")?,
             rw: comp.register_parameter("rw")?,
@@ -37,14 +41,15 @@ impl Resources for Comp {
     }
 }
 
+#[cfg(not(feature = "rtapi"))]
 fn main() -> Result<(), Box<dyn Error>> {
     rtapi_logger::init().ok();
 
     // Create a new HAL component called `rust-comp`
-    let comp: HalComponent<Comp> = HalComponent::new("params")?;
+    let comp = HalComponent::new("params")?;
 
-    // Get a reference to the `Comp` struct
-    let resources = comp.resources();
+    // Register the parameters in the `Comp` struct and mark the component as ready
+    let resources: Comp = comp.register()?;
 
     // Main control loop
     while !comp.should_exit() {
@@ -57,3 +62,6 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(feature = "rtapi")]
+fn main() {}
@@ -6,77 +6,81 @@
 //! To build this example, run `LINUXCNC_SRC=... cargo build --examples`. The resulting file is
 //! placed in `target/debug/examples/librtapi.so`. This can be loaded into LinuxCNC with
 //! `loadrt /path/to/librtapi`. Note that the `.so` is added for you.
+//!
+//! The component exports a function called `rtapi.update` which copies `rtapi.input-1` to
+//! `rtapi.output-1` each time it is called. It must be added to a thread to run, e.g.
+//!
+//! ```ini
+//! loadrt /path/to/librtapi
+//! addf rtapi.update servo-thread
+//! ```
+
+// Userspace components can't export functions, so this example is empty with the `ulapi` feature
+#![cfg(not(feature = "ulapi"))]
 
 use linuxcnc_hal::{
-    error::PinRegisterError,
+    error::ResourcesError,
+    hal_function::{FunctionOptions, HalFunction},
     hal_pin::{InputPin, OutputPin},
     prelude::*,
-    HalComponent, RegisterResources, Resources,
-};
-use std::{
-    error::Error,
-    thread,
-    time::{Duration, Instant},
+    rtapi_app, HalComponent, RegisterResources, Resources,
 };
+use std::error::Error;
 
+/// State passed to the exported function every time it is called.
 struct Pins {
-    input_1: InputPin<f64>,
-    output_1: OutputPin<f64>,
+    input_1: InputPin<'static, f64>,
+    output_1: OutputPin<'static, f64>,
+}
+
+struct Comp {
+    update: HalFunction,
 }
 
-impl Resources for Pins {
-    type RegisterError = PinRegisterError;
+// Exported functions run until the component exits, so their state must borrow the component for
+// `'static`. `rtapi_app!` keeps the component alive until the module is unloaded.
+impl Resources<'static> for Comp {
+    type RegisterError = ResourcesError;
 
-    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
-        Ok(Pins {
+    fn register_resources(comp: &RegisterResources<'static>) -> Result<Self, Self::RegisterError> {
+        let pins = Pins {
             input_1: comp.register_pin::<InputPin<f64>>("input-1")?,
             output_1: comp.register_pin::<OutputPin<f64>>("output-1")?,
-        })
-    }
-}
+        };
 
-/// Component entry point.
-#[no_mangle]
-pub extern "C" fn rtapi_app_main() -> i32 {
-    // Simple wrapper around inner function that turns result into integer.
-    match inner() {
-        // NOTE: The error code here should be a lot more descriptive in real code.
-        Err(e) => -1,
-        Ok(_) => 0,
+        // Called from the realtime thread. Try not to allocate in here.
+        let update = comp.export_function(
+            "update",
+            FunctionOptions::default(),
+            pins,
+            |pins, _period| {
+                if let Ok(value) = pins.input_1.value() {
+                    pins.output_1.set_value(*value).ok();
+                }
+            },
+        )?;
+
+        Ok(Comp { update })
     }
 }
 
-/// Inner function that can return a `Result`.
-///
-/// This is a convenience wrapper so that the `?` syntax sugar can be used.
-fn inner() -> Result<(), Box<dyn Error>> {
+/// Called once the component has been created and its resources registered.
+fn init(comp: &HalComponent, resources: &Comp) -> Result<(), Box<dyn Error>> {
     rtapi_logger::init().ok();
 
-    // Create a new HAL component called `rust-comp`
-    let comp: HalComponent<Pins> = HalComponent::new("rust-comp")?;
-
-    // Get a reference to the `Pins` struct
-    let pins = comp.resources();
-
-    let start = Instant::now();
-
-    // Main control loop
-    while !comp.should_exit() {
-        let time = start.elapsed().as_secs() as i32;
-
-        // Set output pin to elapsed seconds since component started
-        pins.output_1.set_value(time.into())?;
-
-        // Print the current value of the input pin
-        println!("Input: {:?}", pins.input_1.value());
-
-        // Sleep for 1000ms. This should be a lower time if the component needs to update more
-        // frequently.
-        thread::sleep(Duration::from_millis(1000));
-    }
-
-    // The custom implementation of `Drop` for `HalComponent` ensures that `hal_exit()` is called
-    // at this point. Registered signal handlers are also deregistered.
+    log::info!(
+        "Component {} exported {}",
+        comp.name(),
+        resources.update.name()
+    );
 
     Ok(())
 }
+
+// Generates the `rtapi_app_main` and `rtapi_app_exit` functions LinuxCNC calls to load and unload
+// the component. The component name must match the library name.
+rtapi_app! {
+    name: "rtapi",
+    resources: Comp,
+    init: init,
+}
@@ -13,6 +13,10 @@
 //! net input-1 motion.spindle-speed-out rust-comp.input-1
 //! ```
 
+// Userspace components poll `HalComponent::should_exit`, which is not available with the `rtapi`
+// feature, so this example is empty in realtime mode
+#![cfg_attr(feature = "rtapi", allow(unused))]
+
 use linuxcnc_hal::{
     error::PinRegisterError,
     hal_pin::{InputPin, OutputPin},
@@ -25,15 +29,15 @@ use std::{
     time::{Duration, Instant},
 };
 
-struct Pins {
-    input_1: InputPin<f64>,
-    output_1: OutputPin<f64>,
+struct Pins<'comp> {
+    input_1: InputPin<'comp, f64>,
+    output_1: OutputPin<'comp, f64>,
 }
 
-impl Resources for Pins {
+impl<'comp> Resources<'comp> for Pins<'comp> {
     type RegisterError = PinRegisterError;
 
-    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
         Ok(Pins {
             input_1: comp.register_pin::<InputPin<f64>>("input-1")?,
             output_1: comp.register_pin::<OutputPin<f64>>("output-1")?,
@@ -41,14 +45,15 @@ impl Resources for Pins {
     }
 }
 
+#[cfg(not(feature = "rtapi"))]
 fn main() -> Result<(), Box<dyn Error>> {
     rtapi_logger::init().ok();
 
     // Create a new HAL component called `rust-comp`
-    let comp: HalComponent<Pins> = HalComponent::new("rust-comp")?;
+    let comp = HalComponent::new("rust-comp")?;
 
-    // Get a reference to the `Pins` struct
-    let pins = comp.resources();
+    // Register the pins in the `Pins` struct and mark the component as ready
+    let pins: Pins = comp.register()?;
 
     let start = Instant::now();
 
@@ -72,3 +77,6 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[cfg(feature = "rtapi")]
+fn main() {}
@@ -1,12 +1,23 @@
-use crate::{error::ComponentInitError, RegisterResources, Resources};
-use linuxcnc_hal_sys::{hal_exit, hal_init, hal_ready, EINVAL, ENOMEM, HAL_NAME_LEN};
+use crate::ffi::{hal_exit, hal_init, hal_ready, HAL_NAME_LEN};
+use crate::{
+    error::{ComponentInitError, HalErrno, ResourcesError},
+    instance::{Instance, InstanceNames, Instances},
+    RegisterResources, Resources,
+};
+#[cfg(not(feature = "rtapi"))]
 use signal_hook::iterator::Signals;
-use std::{cell::RefCell, ffi::CString};
+#[cfg(not(feature = "rtapi"))]
+use std::cell::RefCell;
+use std::ffi::CString;
 
 /// HAL component
 ///
 /// The main HAL component interface. See the [crate documentation](./index.html) for examples.
 ///
+/// A component is created in two steps. [`HalComponent::new`] creates the component in the HAL,
+/// then [`HalComponent::register`] registers its resources (pins, parameters, etc) and marks the
+/// component as ready.
+///
 /// During registraton, all resource names are prefixed with the component name and a `.` full stop
 /// character. For example, a component named `rust-comp` with a pin named `input-1` will show up in
 /// LinuxCNC as a pin called `rust-comp.input-1`.
@@ -14,49 +25,149 @@ use std::{cell::RefCell, ffi::CString};
 /// `HalComponent` has a custom `Drop` implementation which calls [`hal_exit`] (among other things)
 /// when the variable holding the component goes out of scope. Due to this, the component should be
 /// initialised in `main()` so it lives for the entire life of the program.
+///
+/// [`hal_exit`] frees the HAL shared memory that pins and parameters point into, so every resource
+/// borrows the component it was registered with. The compiler rejects any use of a resource after
+/// its component has been dropped:
+///
+/// ```rust,compile_fail,E0505
+/// use linuxcnc_hal::{hal_pin::InputPin, prelude::*, HalComponent, RegisterResources, Resources};
+/// # use linuxcnc_hal::error::PinRegisterError;
+/// #
+/// # struct Pins<'comp> {
+/// #     input: InputPin<'comp, f64>,
+/// # }
+/// #
+/// # impl<'comp> Resources<'comp> for Pins<'comp> {
+/// #     type RegisterError = PinRegisterError;
+/// #
+/// #     fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+/// #         Ok(Pins { input: comp.register_pin("input")? })
+/// #     }
+/// # }
+///
+/// let comp = HalComponent::new("comp").unwrap();
+/// let pins: Pins = comp.register().unwrap();
+///
+/// let value = pins.input.value().unwrap();
+///
+/// drop(comp);
+///
+/// println!("{}", value);
+/// ```
 #[derive(Debug)]
-pub struct HalComponent<R> {
+pub struct HalComponent {
     /// Component name
-    name: &'static str,
+    name: String,
 
     /// Component ID
     id: i32,
 
-    /// Handles to Unix exit signals
-    signals: RefCell<Signals>,
-
-    /// Handle to resources (pins, signals, etc) used in the component
-    ///
-    /// This is an `Option` so that it can be `Drop`ped before the component itself is dropped.
-    /// Resources references to shared memory in LinuxCNC's HAL must be freed before [`hal_exit`] is
-    /// called.
-    resources: Option<R>,
+    /// Handles to Unix exit signals, if they were registered
+    #[cfg(not(feature = "rtapi"))]
+    signals: RefCell<Option<Signals>>,
 }
 
-impl<R> HalComponent<R>
-where
-    R: Resources,
-{
+impl HalComponent {
     /// Create a new HAL component
     ///
-    /// `new` registers a new HAL component with LinuxCNC, registers the required UNIX signal
-    /// handlers and allocates resources (pins, signals, etc) required by the component.
-    pub fn new(name: &'static str) -> Result<Self, ComponentInitError> {
-        let id = Self::create_component(name)?;
+    /// `new` registers a new HAL component with LinuxCNC and registers the required UNIX signal
+    /// handlers. Resources must then be registered with [`HalComponent::register`], which also
+    /// marks the component as ready.
+    ///
+    /// Signal handlers are not registered with the `rtapi` feature, as a realtime component runs
+    /// inside LinuxCNC's own process.
+    ///
+    /// The component takes ownership of its name, so it can be created at runtime from e.g.
+    /// command line arguments or a config file.
+    pub fn new(name: impl Into<String>) -> Result<Self, ComponentInitError> {
+        let name = name.into();
 
-        let resources = R::register_resources(&RegisterResources { id, name })
-            .map_err(|e| ComponentInitError::ResourceRegistration(e.into()))?;
+        let id = Self::create_component(&name)?;
 
+        #[cfg(not(feature = "rtapi"))]
         let signals = Self::register_signals()?;
 
-        let comp = Self {
+        Ok(Self {
             name,
             id,
-            resources: Some(resources),
-            signals: RefCell::new(signals),
-        };
+            #[cfg(not(feature = "rtapi"))]
+            signals: RefCell::new(Some(signals)),
+        })
+    }
+
+    /// Create a new HAL component without registering signal handlers
+    ///
+    /// Used by [`rtapi_app!`](crate::rtapi_app), as a realtime component runs inside LinuxCNC's own
+    /// process, whose signal handling must not be replaced.
+    #[cfg(not(feature = "ulapi"))]
+    pub(crate) fn new_realtime(name: impl Into<String>) -> Result<Self, ComponentInitError> {
+        let name = name.into();
+
+        let id = Self::create_component(&name)?;
+
+        Ok(Self {
+            name,
+            id,
+            #[cfg(not(feature = "rtapi"))]
+            signals: RefCell::new(None),
+        })
+    }
+
+    /// Register resources (pins, signals, etc) with the component and mark it as ready
+    ///
+    /// The returned resources borrow the component, so they can't outlive it. LinuxCNC doesn't
+    /// allow resources to be added to a component once it is ready, so this method can only be
+    /// called once.
+    ///
+    /// # Errors
+    ///
+    /// * [`ComponentInitError::ResourceRegistration`] - if any resource failed to register
+    /// * [`ComponentInitError::Ready`] - if the call to [`hal_ready`] returned an error, e.g.
+    ///   because the component is already ready
+    pub fn register<'comp, R>(&'comp self) -> Result<R, ComponentInitError>
+    where
+        R: Resources<'comp>,
+    {
+        let resources = R::register_resources(&RegisterResources::new(self, self.name.clone()))
+            .map_err(|e| ComponentInitError::ResourceRegistration(e.into()))?;
+
+        self.ready()?;
 
-        comp.ready()
+        Ok(resources)
+    }
+
+    /// Register multiple instances of the same resources with the component and mark it as ready
+    ///
+    /// `R` is registered once for each of the given instances, with the instance name as the
+    /// prefix of every pin, parameter and function name instead of the component name. See the
+    /// [`instance`](crate::instance) module for how instances are named.
+    ///
+    /// See [`HalComponent::register`] for errors.
+    pub fn register_instances<'comp, R>(
+        &'comp self,
+        instances: InstanceNames,
+    ) -> Result<Instances<R>, ComponentInitError>
+    where
+        R: Resources<'comp>,
+    {
+        let instances = instances
+            .prefixes(&self.name)
+            .into_iter()
+            .map(|name| {
+                let resources = R::register_resources(&RegisterResources::new(self, name.clone()))
+                    .map_err(Into::into)?;
+
+                debug!("Registered instance {}", name);
+
+                Ok(Instance { name, resources })
+            })
+            .collect::<Result<Vec<_>, ResourcesError>>()
+            .map_err(ComponentInitError::ResourceRegistration)?;
+
+        self.ready()?;
+
+        Ok(Instances { instances })
     }
 
     /// Register signal handlers so component closes cleanly
@@ -64,6 +175,7 @@ where
     /// These are also required for the component to pass initialisation in LinuxCNC. If LinuxCNC
     /// hangs during starting waiting for the component to become ready, it might be due to signal
     /// handlers not being registered.
+    #[cfg(not(feature = "rtapi"))]
     fn register_signals() -> Result<Signals, ComponentInitError> {
         let signals = Signals::new(&[signal_hook::consts::SIGTERM, signal_hook::consts::SIGINT])
             .map_err(ComponentInitError::Signals)?;
@@ -80,9 +192,18 @@ where
     /// * [`ComponentInitError::NameLength`] - If the component name is longer than [`HAL_NAME_LEN`]
     /// * [`ComponentInitError::InvalidName`] - If the component name cannot be converted to a
     ///   [`std::ffi::CString`]
-    /// * [`ComponentInitError::Init`] - If the call to [`hal_init`] returned an [`EINVAL`] status
-    /// * [`ComponentInitError::Memory`] - If there is not enough memory to allocate the component
-    fn create_component(name: &'static str) -> Result<i32, ComponentInitError> {
+    /// * [`ComponentInitError::Init`] - If the call to [`hal_init`] returned an error, e.g.
+    ///   [`HalErrno::Memory`] if there is not enough memory to allocate the component
+    /// * `ComponentInitError::Library` - If the `dlopen` feature is enabled and the HAL library
+    ///   could not be loaded
+    fn create_component(name: &str) -> Result<i32, ComponentInitError> {
+        #[cfg(all(feature = "dlopen", not(feature = "mock")))]
+        {
+            let library = linuxcnc_hal_sys::dynamic::load().map_err(ComponentInitError::Library)?;
+
+            debug!("Using HAL library {:?}", library);
+        }
+
         if name.len() > HAL_NAME_LEN as usize {
             error!(
                 "Component name must be no longer than {} bytes",
@@ -95,32 +216,23 @@ where
 
             let id = unsafe { hal_init(name_c.as_ptr().cast()) };
 
-            match id {
-                x if x == -(EINVAL as i32) => Err(ComponentInitError::Init),
-                x if x == -(ENOMEM as i32) => Err(ComponentInitError::Memory),
-                id if id > 0 => {
-                    debug!("Init component {} with ID {}", name, id);
+            let id = HalErrno::check(id).map_err(ComponentInitError::Init)?;
 
-                    Ok(id)
-                }
-                code => unreachable!("Hit unreachable error code {}", code),
-            }
+            debug!("Init component {} with ID {}", name, id);
+
+            Ok(id)
         }
     }
 
     /// Signal to the HAL that the component is ready
-    fn ready(self) -> Result<Self, ComponentInitError> {
+    fn ready(&self) -> Result<(), ComponentInitError> {
         let ret = unsafe { hal_ready(self.id) };
 
-        match ret {
-            x if x == -(EINVAL as i32) => Err(ComponentInitError::Ready),
-            0 => {
-                debug!("Component is ready");
+        HalErrno::check(ret).map_err(ComponentInitError::Ready)?;
 
-                Ok(self)
-            }
-            ret => unreachable!("Unknown error status {} returned from hal_ready()", ret),
-        }
+        debug!("Component is ready");
+
+        Ok(())
     }
 
     /// Get the HAL-assigned ID for this component
@@ -130,38 +242,38 @@ where
 
     /// Get the component name
     pub fn name(&self) -> &str {
-        self.name
+        &self.name
     }
 
     /// Check whether the component was signalled to shut down
+    ///
+    /// Realtime components are unloaded by LinuxCNC instead, so this method is not available with
+    /// the `rtapi` feature. It always returns `false` for components created by
+    /// [`rtapi_app!`](crate::rtapi_app).
+    #[cfg(not(feature = "rtapi"))]
+    // `rtapi_app!` is not available with the `ulapi` feature
+    #[cfg_attr(feature = "ulapi", allow(rustdoc::broken_intra_doc_links))]
     pub fn should_exit(&self) -> bool {
-        self.signals.borrow_mut().pending().any(|signal| {
-            matches!(
-                signal,
-                signal_hook::consts::SIGTERM
-                    | signal_hook::consts::SIGINT
-                    | signal_hook::consts::SIGKILL
-            )
+        self.signals.borrow_mut().as_mut().is_some_and(|signals| {
+            signals.pending().any(|signal| {
+                matches!(
+                    signal,
+                    signal_hook::consts::SIGTERM
+                        | signal_hook::consts::SIGINT
+                        | signal_hook::consts::SIGKILL
+                )
+            })
         })
     }
-
-    /// Get a reference to the component's resources
-    pub fn resources(&self) -> &R {
-        // NOTE: Unwrap is safe here due to `Some(resources)` in HalComponent::new
-        &self.resources.as_ref().unwrap()
-    }
 }
 
-impl<R> Drop for HalComponent<R> {
-    /// Clean up resources, signals and HAL component
+impl Drop for HalComponent {
+    /// Clean up signals and HAL component
+    ///
+    /// Every resource borrows the component, so they have all been dropped by this point.
     fn drop(&mut self) {
-        // Force resources to be dropped before close
-        self.resources = None;
-
         debug!("Closing component ID {}, name {}", self.id, self.name);
 
-        // self.signals.close();
-
         unsafe {
             hal_exit(self.id);
         }
@@ -171,24 +283,11 @@ impl<R> Drop for HalComponent<R> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{
-        error::{ComponentInitError, PinRegisterError},
-        RegisterResources,
-    };
-
-    #[derive(Debug)]
-    struct EmptyResources {}
-    impl Resources for EmptyResources {
-        type RegisterError = PinRegisterError;
-
-        fn register_resources(_comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
-            Ok(Self {})
-        }
-    }
+    use crate::error::ComponentInitError;
 
     #[test]
     fn name_too_long() -> Result<(), ComponentInitError> {
-        let comp = HalComponent::<EmptyResources>::new(
+        let comp = HalComponent::new(
             "name-thats-way-too-long-for-linuxcnc-to-handle-wow-this-is-ridiculous",
         );
 
@@ -197,7 +296,7 @@ mod tests {
         match comp {
             Err(ComponentInitError::NameLength) => Ok(()),
             Err(e) => Err(e),
-            Ok(_) => Err(ComponentInitError::Init),
+            Ok(_) => Err(ComponentInitError::Init(HalErrno::Invalid)),
         }
     }
 }
@@ -1,8 +1,176 @@
 //! Error types
 
-use linuxcnc_hal_sys::HAL_NAME_LEN;
+use linuxcnc_hal_sys::{
+    EAGAIN, EBUSY, EEXIST, EINVAL, EIO, ENODEV, ENOENT, ENOMEM, ENOSPC, ENOSYS, EPERM,
+    HAL_NAME_LEN, HAL_STREAM_MAX_PINS,
+};
 
-/// Errors returned by LinuxCNC bindgen functions
+/// Error code returned by a HAL or RTAPI function
+///
+/// HAL functions return `0` or a positive value on success, and a negative errno value on failure.
+/// Codes that the HAL API is known to return have their own variant. Any other code is kept in
+/// [`HalErrno::Unknown`] instead of causing a panic.
+#[derive(thiserror::Error, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HalErrno {
+    /// `EINVAL`: an argument is invalid, a resource doesn't exist or is in the wrong state
+    #[error("invalid argument (EINVAL)")]
+    Invalid,
+
+    /// `ENOMEM`: there is not enough HAL shared memory
+    #[error("out of memory (ENOMEM)")]
+    Memory,
+
+    /// `EPERM`: the HAL is locked
+    #[error("HAL is locked (EPERM)")]
+    LockedHal,
+
+    /// `ENOENT`: a resource doesn't exist
+    #[error("not found (ENOENT)")]
+    NotFound,
+
+    /// `EEXIST`: a resource already exists
+    #[error("already exists (EEXIST)")]
+    Exists,
+
+    /// `EBUSY`: a resource is in use
+    #[error("resource busy (EBUSY)")]
+    Busy,
+
+    /// `ENOSPC`: a stream or port is full
+    #[error("no space left (ENOSPC)")]
+    NoSpace,
+
+    /// `EAGAIN`: a stream or port is empty
+    #[error("try again (EAGAIN)")]
+    Again,
+
+    /// `ENODEV`: the RTAPI or shared memory device is not available
+    #[error("no such device (ENODEV)")]
+    NoDevice,
+
+    /// `EIO`: an I/O error occurred in RTAPI
+    #[error("I/O error (EIO)")]
+    Io,
+
+    /// `ENOSYS`: the function is not provided by the loaded HAL library
+    #[error("function not implemented (ENOSYS)")]
+    Unsupported,
+
+    /// Any other error code
+    ///
+    /// Contains the positive errno value.
+    #[error("error code {0}")]
+    Unknown(i32),
+}
+
+impl HalErrno {
+    /// Get the error for a positive errno value
+    pub fn from_errno(errno: i32) -> Self {
+        match errno {
+            x if x == EINVAL as i32 => Self::Invalid,
+            x if x == ENOMEM as i32 => Self::Memory,
+            x if x == EPERM as i32 => Self::LockedHal,
+            x if x == ENOENT as i32 => Self::NotFound,
+            x if x == EEXIST as i32 => Self::Exists,
+            x if x == EBUSY as i32 => Self::Busy,
+            x if x == ENOSPC as i32 => Self::NoSpace,
+            x if x == EAGAIN as i32 => Self::Again,
+            x if x == ENODEV as i32 => Self::NoDevice,
+            x if x == EIO as i32 => Self::Io,
+            x if x == ENOSYS as i32 => Self::Unsupported,
+            errno => Self::Unknown(errno),
+        }
+    }
+
+    /// Get the positive errno value of this error
+    pub fn errno(self) -> i32 {
+        let errno = match self {
+            Self::Invalid => EINVAL,
+            Self::Memory => ENOMEM,
+            Self::LockedHal => EPERM,
+            Self::NotFound => ENOENT,
+            Self::Exists => EEXIST,
+            Self::Busy => EBUSY,
+            Self::NoSpace => ENOSPC,
+            Self::Again => EAGAIN,
+            Self::NoDevice => ENODEV,
+            Self::Io => EIO,
+            Self::Unsupported => ENOSYS,
+            Self::Unknown(errno) => return errno,
+        };
+
+        errno as i32
+    }
+
+    /// Decode the return value of a HAL function
+    ///
+    /// Returns the value unchanged if it is zero or positive, or the error if it is negative. Every
+    /// HAL call in this crate goes through this function.
+    pub fn check(ret: i32) -> Result<i32, Self> {
+        if ret >= 0 {
+            Ok(ret)
+        } else {
+            Err(Self::from_errno(-ret))
+        }
+    }
+}
+
+/// An error that can be returned to LinuxCNC as a negative errno value
+///
+/// Implemented by every error type in this module, so that [`rtapi_app!`](crate::rtapi_app) can
+/// return the errno value of any of them from `rtapi_app_main`.
+#[cfg(not(feature = "ulapi"))]
+pub(crate) trait Errno: std::error::Error + 'static {
+    /// Get a negative errno value that represents this error
+    ///
+    /// Named differently to the inherent `errno` methods so it can't shadow
+    /// [`HalErrno::errno`], which returns a positive value.
+    fn negative_errno(&self) -> i32;
+
+    /// Get the errno value of `e` if it is this error type
+    fn downcast_errno(e: &(dyn std::error::Error + 'static)) -> Option<i32>
+    where
+        Self: Sized,
+    {
+        e.downcast_ref::<Self>().map(Self::negative_errno)
+    }
+}
+
+#[cfg(not(feature = "ulapi"))]
+impl Errno for HalErrno {
+    fn negative_errno(&self) -> i32 {
+        -HalErrno::errno(*self)
+    }
+}
+
+/// Implement [`Errno`] for error types with an inherent `errno` method
+macro_rules! impl_errno {
+    ($($error:ty),+ $(,)?) => {
+        $(
+            #[cfg(not(feature = "ulapi"))]
+            impl Errno for $error {
+                fn negative_errno(&self) -> i32 {
+                    <$error>::errno(self)
+                }
+            }
+        )+
+    };
+}
+
+impl_errno!(
+    StorageError,
+    PinRegisterError,
+    ParameterRegisterError,
+    FunctionExportError,
+    StreamError,
+    SignalError,
+    QueryError,
+    SnapshotError,
+    ThreadError,
+    InstanceArgsError,
+    ComponentInitError,
+    ResourcesError,
+);
 
 /// Pointer error
 #[derive(thiserror::Error, Debug, PartialEq)]
@@ -16,79 +184,632 @@ pub enum StorageError {
     Alignment,
 }
 
+impl StorageError {
+    /// Get a negative errno value that represents this error
+    ///
+    /// A null pointer means the HAL could not allocate the storage, so is `-ENOMEM`.
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::Null => -(ENOMEM as i32),
+            Self::Alignment => -(EINVAL as i32),
+        }
+    }
+}
+
 /// Pin registration error
+///
+/// Every variant carries the full HAL name of the pin, so the error message shows which pin
+/// failed to register.
 #[derive(thiserror::Error, Debug, PartialEq)]
 pub enum PinRegisterError {
     /// Pin name is too long
     ///
     /// The maximum length is dictated by the [`HAL_NAME_LEN`] constant
-    #[error("pin name is too long. Must be no longer than {} bytes", HAL_NAME_LEN)]
-    NameLength,
+    #[error(
+        "pin name `{0}` is too long. Must be no longer than {} bytes",
+        HAL_NAME_LEN
+    )]
+    NameLength(String),
 
     /// Pin name could not be converted to C string
-    #[error("pin name could not be converted to a valid C string")]
-    NameConversion,
+    #[error("pin name {0:?} could not be converted to a valid C string")]
+    NameConversion(String),
 
     /// An error occurred allocating the HAL shared memory storage backing the pin
-    #[error("failed to allocate shared memory storage for pin")]
-    Storage(StorageError),
+    #[error("failed to allocate shared memory storage for pin `{name}`")]
+    Storage {
+        /// Full name of the pin
+        name: String,
 
-    /// An error occurred in the LinuxCNC HAL functions
-    ///
-    /// This variant is often returned when a HAL function returns
-    /// [`EINVAL`](linuxcnc_hal_sys::EINVAL). This error code is returned for various different
-    /// reasons. Check the LinuxCNC logs for error messages.
-    #[error("HAL method returned invalid (EINVAL) status code")]
-    Invalid,
+        /// The error that occurred when allocating storage
+        #[source]
+        source: StorageError,
+    },
 
-    /// The HAL is locked
+    /// The HAL function that registers the pin returned an error
     ///
-    /// Resources cannot be registered after a component is created
-    #[error("HAL is locked")]
-    LockedHal,
+    /// The most common causes are [`HalErrno::Invalid`] if a pin with the same name already
+    /// exists, [`HalErrno::LockedHal`] if the component is already running, and
+    /// [`HalErrno::Unsupported`] with the `dlopen` feature if the loaded HAL library doesn't
+    /// support the pin type. Check the LinuxCNC logs for more details.
+    #[error("failed to register pin `{name}`")]
+    Hal {
+        /// Full name of the pin
+        name: String,
 
-    /// There is not enough free memory available to allocate storage for this pin
-    #[error("not enough free memory to allocate storage")]
-    Memory,
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+
+    /// The HAL function that sets the alias of the pin returned an error
+    #[error("failed to set alias of pin `{name}`")]
+    Alias {
+        /// Full name of the pin
+        name: String,
+
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+}
+
+impl PinRegisterError {
+    /// Get the name of the pin that failed to register
+    pub fn name(&self) -> &str {
+        match self {
+            Self::NameLength(name)
+            | Self::NameConversion(name)
+            | Self::Storage { name, .. }
+            | Self::Hal { name, .. }
+            | Self::Alias { name, .. } => name,
+        }
+    }
+
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::NameLength(_) | Self::NameConversion(_) => -(EINVAL as i32),
+            Self::Storage { .. } => -(ENOMEM as i32),
+            Self::Hal { source, .. } | Self::Alias { source, .. } => -source.errno(),
+        }
+    }
 }
 
 /// Parameter registration error
+///
+/// Every variant carries the full HAL name of the parameter, so the error message shows which
+/// parameter failed to register.
 #[derive(thiserror::Error, Debug, PartialEq)]
 pub enum ParameterRegisterError {
     /// Parameter name is too long
     ///
     /// The maximum length is dictated by the [`HAL_NAME_LEN`] constant
     #[error(
-        "Parameter name is too long. Must be no longer than {} bytes",
+        "parameter name `{0}` is too long. Must be no longer than {} bytes",
         HAL_NAME_LEN
     )]
-    NameLength,
+    NameLength(String),
 
     /// Parameter name could not be converted to C string
-    #[error("Parameter name could not be converted to a valid C string")]
-    NameConversion,
+    #[error("parameter name {0:?} could not be converted to a valid C string")]
+    NameConversion(String),
 
     /// An error occurred allocating the HAL shared memory storage backing the parameter
-    #[error("failed to allocate shared memory storage for parameter")]
-    Storage(StorageError),
+    #[error("failed to allocate shared memory storage for parameter `{name}`")]
+    Storage {
+        /// Full name of the parameter
+        name: String,
+
+        /// The error that occurred when allocating storage
+        #[source]
+        source: StorageError,
+    },
 
-    /// An error occurred in the LinuxCNC HAL functions
+    /// The HAL function that registers the parameter returned an error
     ///
-    /// This variant is often returned when a HAL function returns
-    /// [`EINVAL`](linuxcnc_hal_sys::EINVAL). This error code is returned for various different
-    /// reasons. Check the LinuxCNC logs for error messages.
-    #[error("HAL method returned invalid (EINVAL) status code")]
-    Invalid,
+    /// The most common causes are [`HalErrno::Invalid`] if a parameter with the same name already
+    /// exists, [`HalErrno::LockedHal`] if the component is already running, and
+    /// [`HalErrno::Unsupported`] with the `dlopen` feature if the loaded HAL library doesn't
+    /// support the parameter type. Check the LinuxCNC logs for more details.
+    #[error("failed to register parameter `{name}`")]
+    Hal {
+        /// Full name of the parameter
+        name: String,
 
-    /// The HAL is locked
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+
+    /// The HAL function that sets the alias of the parameter returned an error
+    #[error("failed to set alias of parameter `{name}`")]
+    Alias {
+        /// Full name of the parameter
+        name: String,
+
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+}
+
+impl ParameterRegisterError {
+    /// Get the name of the parameter that failed to register
+    pub fn name(&self) -> &str {
+        match self {
+            Self::NameLength(name)
+            | Self::NameConversion(name)
+            | Self::Storage { name, .. }
+            | Self::Hal { name, .. }
+            | Self::Alias { name, .. } => name,
+        }
+    }
+
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::NameLength(_) | Self::NameConversion(_) => -(EINVAL as i32),
+            Self::Storage { .. } => -(ENOMEM as i32),
+            Self::Hal { source, .. } | Self::Alias { source, .. } => -source.errno(),
+        }
+    }
+}
+
+/// Function export error
+///
+/// Every variant carries the full HAL name of the function.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum FunctionExportError {
+    /// Function name is too long
+    ///
+    /// The maximum length is dictated by the [`HAL_NAME_LEN`] constant
+    #[error(
+        "function name `{0}` is too long. Must be no longer than {} bytes",
+        HAL_NAME_LEN
+    )]
+    NameLength(String),
+
+    /// Function name could not be converted to C string
+    #[error("function name {0:?} could not be converted to a valid C string")]
+    NameConversion(String),
+
+    /// An error occurred allocating the HAL shared memory storage for the function state
+    #[error("failed to allocate shared memory storage for function `{name}`")]
+    Storage {
+        /// Full name of the function
+        name: String,
+
+        /// The error that occurred when allocating storage
+        #[source]
+        source: StorageError,
+    },
+
+    /// `hal_export_funct` returned an error
+    ///
+    /// [`HalErrno::Invalid`] is returned for example if a function with the same name already
+    /// exists or the component is not a realtime component. Check the LinuxCNC logs for error
+    /// messages.
+    #[error("failed to export function `{name}`")]
+    Hal {
+        /// Full name of the function
+        name: String,
+
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+}
+
+impl FunctionExportError {
+    /// Get the name of the function that failed to export
+    pub fn name(&self) -> &str {
+        match self {
+            Self::NameLength(name)
+            | Self::NameConversion(name)
+            | Self::Storage { name, .. }
+            | Self::Hal { name, .. } => name,
+        }
+    }
+
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::NameLength(_) | Self::NameConversion(_) => -(EINVAL as i32),
+            Self::Storage { .. } => -(ENOMEM as i32),
+            Self::Hal { source, .. } => -source.errno(),
+        }
+    }
+}
+
+/// HAL stream error
+///
+/// Every variant carries the shared memory key of the stream, so the error message shows which
+/// stream failed.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum StreamError {
+    /// The stream sample type has too many or too few fields
+    ///
+    /// A sample must have at least one and at most [`HAL_STREAM_MAX_PINS`] fields.
+    #[error(
+        "samples of stream {key:#x} must have between 1 and {} fields",
+        HAL_STREAM_MAX_PINS
+    )]
+    Length {
+        /// Shared memory key of the stream
+        key: i32,
+    },
+
+    /// The stream type string contains a nul byte
+    ///
+    /// Only returned for hand written [`StreamSample`](crate::stream::StreamSample) implementations.
+    #[error("type string {types:?} of stream {key:#x} contains a nul byte")]
+    TypeString {
+        /// Shared memory key of the stream
+        key: i32,
+
+        /// The type string of the sample type
+        types: String,
+    },
+
+    /// The requested stream depth is too large
+    #[error("depth {depth} of stream {key:#x} is too large")]
+    Depth {
+        /// Shared memory key of the stream
+        key: i32,
+
+        /// The requested depth
+        depth: usize,
+    },
+
+    /// A sample could not be written because the stream is full
+    #[error("stream {key:#x} is full ({overruns} overruns)")]
+    Overrun {
+        /// Shared memory key of the stream
+        key: i32,
+
+        /// The total number of overruns recorded by the stream
+        overruns: u32,
+    },
+
+    /// A sample could not be read because the stream is empty
+    #[error("stream {key:#x} is empty ({underruns} underruns)")]
+    Underrun {
+        /// Shared memory key of the stream
+        key: i32,
+
+        /// The total number of underruns recorded by the stream
+        underruns: u32,
+    },
+
+    /// A HAL stream function returned an error
+    ///
+    /// The most common causes are [`HalErrno::Invalid`] when attaching to a stream whose type
+    /// string does not match the sample type, [`HalErrno::Memory`] if there is not enough memory
+    /// to create the stream, and [`HalErrno::Unsupported`] with the `dlopen` feature if the loaded
+    /// HAL library doesn't support streams. Check the LinuxCNC logs for more details.
+    #[error("HAL function failed for stream {key:#x}")]
+    Hal {
+        /// Shared memory key of the stream
+        key: i32,
+
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+}
+
+impl StreamError {
+    /// Get the shared memory key of the stream that failed
+    pub fn key(&self) -> i32 {
+        match self {
+            Self::Length { key }
+            | Self::TypeString { key, .. }
+            | Self::Depth { key, .. }
+            | Self::Overrun { key, .. }
+            | Self::Underrun { key, .. }
+            | Self::Hal { key, .. } => *key,
+        }
+    }
+
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::Length { .. } | Self::TypeString { .. } | Self::Depth { .. } => -(EINVAL as i32),
+            Self::Overrun { .. } => -(ENOSPC as i32),
+            Self::Underrun { .. } => -(EAGAIN as i32),
+            Self::Hal { source, .. } => -source.errno(),
+        }
+    }
+}
+
+/// Signal error
+///
+/// Every variant carries the name of the signal or pin that caused the error.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum SignalError {
+    /// Signal or pin name is too long
+    ///
+    /// The maximum length is dictated by the [`HAL_NAME_LEN`] constant
+    #[error(
+        "name `{0}` is too long. Must be no longer than {} bytes",
+        HAL_NAME_LEN
+    )]
+    NameLength(String),
+
+    /// Signal or pin name could not be converted to C string
+    #[error("name {0:?} could not be converted to a valid C string")]
+    NameConversion(String),
+
+    /// A signal with the same name already exists
+    #[error("signal `{0}` already exists")]
+    AlreadyExists(String),
+
+    /// The signal does not exist
+    #[error("signal `{0}` not found")]
+    SignalNotFound(String),
+
+    /// The pin does not exist
+    #[error("pin `{0}` not found")]
+    PinNotFound(String),
+
+    /// The signal type does not match the pin type or the requested value type
+    #[error("signal `{0}` has a different type")]
+    TypeMismatch(String),
+
+    /// The pin is already linked to a different signal
+    #[error("pin `{0}` is already linked to another signal")]
+    AlreadyLinked(String),
+
+    /// The pin is not linked to this signal
+    #[error("pin `{pin}` is not linked to signal `{signal}`")]
+    NotLinked {
+        /// Full name of the pin
+        pin: String,
+
+        /// Name of the signal
+        signal: String,
+    },
+
+    /// The signal already has a pin writing to it
+    ///
+    /// A signal can have at most one output pin, or any number of bidirectional pins.
+    #[error("signal `{0}` already has a writer")]
+    MultipleWriters(String),
+
+    /// A HAL signal function returned an error not covered by the other variants
     ///
-    /// Resources cannot be registered after a component is created
+    /// The most common causes are [`HalErrno::Invalid`] if no component has been created yet,
+    /// [`HalErrno::LockedHal`] if the HAL is locked, [`HalErrno::Memory`] if there is not enough
+    /// shared memory to create the signal, and [`HalErrno::Unsupported`] with the `dlopen` feature
+    /// if the loaded HAL library can't look up signals or pins. Check the LinuxCNC logs for more
+    /// details.
+    #[error("HAL function failed for `{name}`")]
+    Hal {
+        /// Name of the signal, or the pin for [`signal::unlink`](crate::signal::unlink)
+        name: String,
+
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+}
+
+impl SignalError {
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::AlreadyExists(_) => -(EEXIST as i32),
+            Self::Hal { source, .. } => -source.errno(),
+            _ => -(EINVAL as i32),
+        }
+    }
+}
+
+/// Error reading or writing a resource by name
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum QueryError {
+    /// Resource name is too long
+    ///
+    /// The maximum length is dictated by the [`HAL_NAME_LEN`] constant
+    #[error("name is too long. Must be no longer than {} bytes", HAL_NAME_LEN)]
+    NameLength,
+
+    /// Resource name could not be converted to C string
+    #[error("name could not be converted to a valid C string")]
+    NameConversion,
+
+    /// No resource with the given name exists
+    #[error("resource `{0}` not found")]
+    NotFound(String),
+
+    /// The value type does not match the type of the resource
+    #[error("value type does not match the type of `{0}`")]
+    TypeMismatch(String),
+
+    /// The parameter is read only
+    #[error("parameter `{0}` is read only")]
+    ReadOnly(String),
+
+    /// The signal has a writer, so setting its value would have no lasting effect
+    #[error("signal `{0}` already has a writer")]
+    HasWriters(String),
+
+    /// Port values cannot be read or written by name
+    #[error("port values cannot be set by name")]
+    Port,
+
+    /// The HAL is locked
     #[error("HAL is locked")]
     LockedHal,
 
-    /// There is not enough free memory available to allocate storage for this parameter
-    #[error("not enough free memory to allocate storage")]
-    Memory,
+    /// The loaded HAL library doesn't provide the function needed to look up this resource
+    ///
+    /// Only returned with the `dlopen` feature, when running against an older LinuxCNC version.
+    #[error("queries by name are not supported by the loaded HAL library")]
+    Unsupported,
+
+    /// HAL shared memory is not available because no component has been created
+    #[error("HAL is not initialised")]
+    NotInitialised,
+
+    /// A HAL function returned an error not covered by the other variants
+    #[error("HAL function failed for `{name}`")]
+    Hal {
+        /// Full name of the pin, parameter or signal
+        name: String,
+
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+}
+
+impl QueryError {
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        let errno = match self {
+            Self::NotFound(_) => ENOENT,
+            Self::LockedHal => EPERM,
+            Self::Unsupported => ENOSYS,
+            Self::NotInitialised => ENODEV,
+            Self::Hal { source, .. } => return -source.errno(),
+            _ => EINVAL,
+        };
+
+        -(errno as i32)
+    }
+}
+
+impl From<SnapshotError> for QueryError {
+    fn from(e: SnapshotError) -> Self {
+        match e {
+            SnapshotError::NotInitialised => Self::NotInitialised,
+        }
+    }
+}
+
+/// HAL snapshot error
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum SnapshotError {
+    /// HAL shared memory is not available because no component has been created
+    #[error("HAL is not initialised")]
+    NotInitialised,
+}
+
+impl SnapshotError {
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::NotInitialised => -(ENODEV as i32),
+        }
+    }
+}
+
+/// HAL thread error
+///
+/// Every variant carries the name of the thread or function that caused the error.
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum ThreadError {
+    /// Thread or function name is too long
+    ///
+    /// The maximum length is dictated by the [`HAL_NAME_LEN`] constant
+    #[error(
+        "name `{0}` is too long. Must be no longer than {} bytes",
+        HAL_NAME_LEN
+    )]
+    NameLength(String),
+
+    /// Thread or function name could not be converted to C string
+    #[error("name {0:?} could not be converted to a valid C string")]
+    NameConversion(String),
+
+    /// The thread period is zero or too long
+    #[error("invalid period for thread `{0}`")]
+    Period(String),
+
+    /// The function position is zero
+    ///
+    /// Positions count from `1` at the start of the thread or `-1` at the end.
+    #[error("position of function `{0}` must not be zero")]
+    Position(String),
+
+    /// The thread does not exist
+    #[error("thread `{0}` not found")]
+    ThreadNotFound(String),
+
+    /// The function does not exist
+    #[error("function `{0}` not found")]
+    FunctionNotFound(String),
+
+    /// The function is not reentrant and has already been added to a thread
+    #[error("function `{0}` is already in a thread")]
+    AlreadyAdded(String),
+
+    /// The function uses floating point but the thread does not allow it
+    #[error("function `{function}` uses floating point but thread `{thread}` does not")]
+    FloatingPoint {
+        /// Full name of the function
+        function: String,
+
+        /// Name of the thread
+        thread: String,
+    },
+
+    /// A HAL thread function returned an error not covered by the other variants
+    ///
+    /// The most common causes are [`HalErrno::Invalid`] if a thread with the same name already
+    /// exists or a function position is past the end of the thread, [`HalErrno::LockedHal`] if the
+    /// HAL is locked, and [`HalErrno::Memory`] if there is not enough shared memory. Check the
+    /// LinuxCNC logs for more details.
+    #[error("HAL function failed for thread `{name}`")]
+    Hal {
+        /// Name of the thread
+        name: String,
+
+        /// The error returned by the HAL
+        #[source]
+        source: HalErrno,
+    },
+}
+
+impl ThreadError {
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::Hal { source, .. } => -source.errno(),
+            _ => -(EINVAL as i32),
+        }
+    }
+}
+
+/// Error parsing the `count=` or `names=` instance arguments of a component
+#[derive(thiserror::Error, Debug, PartialEq)]
+pub enum InstanceArgsError {
+    /// Both `count` and `names` were given
+    #[error("count= and names= cannot be used together")]
+    Conflict,
+
+    /// The instance count is not a positive integer
+    #[error("invalid instance count {0:?}")]
+    Count(String),
+
+    /// The list of instance names is empty or contains an empty name
+    #[error("instance names must not be empty")]
+    Names,
+
+    /// An argument other than `count` or `names` was given
+    #[error("unknown argument {0:?}")]
+    Unknown(String),
+}
+
+impl InstanceArgsError {
+    /// Get a negative errno value that represents this error
+    ///
+    /// Every variant is an invalid argument, so this is always `-EINVAL`.
+    pub fn errno(&self) -> i32 {
+        -(EINVAL as i32)
+    }
 }
 
 /// HAL component initialisation error
@@ -107,48 +828,108 @@ pub enum ComponentInitError {
     #[error("component name cannot be converted to valid C string")]
     InvalidName,
 
-    /// There is not enough free memory available to allocate storage for this pin
-    #[error("not enough free memory to allocate storage")]
-    Memory,
-
     /// Failed to register signal handlers
     #[error("failed to register signal handlers")]
-    Signals(std::io::Error),
+    Signals(#[source] std::io::Error),
 
     /// Resource (pin, signal, etc) registration failed
     #[error("failed to register resources with component")]
-    ResourceRegistration(ResourcesError),
+    ResourceRegistration(#[from] ResourcesError),
 
-    /// An error occurred when initialising the component with
-    /// [`hal_init`](linuxcnc_hal_sys::hal_init)
+    /// [`hal_init`](linuxcnc_hal_sys::hal_init) returned an error
+    ///
+    /// [`HalErrno::Invalid`] usually means a component with the same name already exists, and
+    /// [`HalErrno::Memory`] that there is not enough shared memory for the component.
     #[error("failed to initialise component")]
-    Init,
+    Init(#[source] HalErrno),
 
-    /// An error occurred when calling [`hal_ready`](linuxcnc_hal_sys::hal_ready) on the component
+    /// [`hal_ready`](linuxcnc_hal_sys::hal_ready) returned an error
     #[error("failed to ready component")]
-    Ready,
+    Ready(#[source] HalErrno),
+
+    /// The HAL library could not be loaded at runtime
+    #[cfg(feature = "dlopen")]
+    #[error("failed to load the HAL library")]
+    Library(#[source] linuxcnc_hal_sys::dynamic::LoadError),
+}
+
+impl ComponentInitError {
+    /// Get a negative errno value that represents this error
+    ///
+    /// This is used as the return value of `rtapi_app_main` in realtime components.
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::NameLength | Self::InvalidName => -(EINVAL as i32),
+            Self::Signals(e) => -e.raw_os_error().unwrap_or(EIO as i32),
+            Self::ResourceRegistration(e) => e.errno(),
+            Self::Init(e) | Self::Ready(e) => -e.errno(),
+            #[cfg(feature = "dlopen")]
+            Self::Library(_) => -(ENOSYS as i32),
+        }
+    }
 }
 
 /// Resources registration error
+///
+/// Every error returned while registering resources converts into this type with `?`, so a
+/// [`Resources`](crate::Resources) implementation can register pins, ports, parameters, streams,
+/// functions and signals without any manual conversions.
 #[derive(thiserror::Error, Debug)]
 pub enum ResourcesError {
-    /// Failed to register a pin with the HAL
-    #[error("pin registration failed")]
-    Pin(PinRegisterError),
+    /// Failed to register a pin or port with the HAL
+    #[error(transparent)]
+    Pin(#[from] PinRegisterError),
 
-    /// Failed to register a pin with the HAL
-    #[error("parameter registration failed")]
-    Parameter(ParameterRegisterError),
-}
+    /// Failed to register a parameter with the HAL
+    #[error(transparent)]
+    Parameter(#[from] ParameterRegisterError),
 
-impl From<PinRegisterError> for ResourcesError {
-    fn from(e: PinRegisterError) -> Self {
-        Self::Pin(e)
-    }
+    /// Failed to create or attach to a stream
+    #[error(transparent)]
+    Stream(#[from] StreamError),
+
+    /// Failed to export a function
+    #[error(transparent)]
+    Function(#[from] FunctionExportError),
+
+    /// Failed to create a thread
+    #[error(transparent)]
+    Thread(#[from] ThreadError),
+
+    /// Failed to create or link a signal
+    #[error(transparent)]
+    Signal(#[from] SignalError),
+
+    /// Failed to set the initial value of a resource
+    #[error("failed to set initial value")]
+    InitialValue(#[source] StorageError),
+
+    /// Failed to register a field of a `Resources` struct
+    ///
+    /// Returned by implementations generated with `#[derive(Resources)]`.
+    #[error("failed to register field `{field}`")]
+    Field {
+        /// The name of the struct field
+        field: &'static str,
+
+        /// The error that occurred when registering the field
+        #[source]
+        source: Box<ResourcesError>,
+    },
 }
 
-impl From<ParameterRegisterError> for ResourcesError {
-    fn from(e: ParameterRegisterError) -> Self {
-        Self::Parameter(e)
+impl ResourcesError {
+    /// Get a negative errno value that represents this error
+    pub fn errno(&self) -> i32 {
+        match self {
+            Self::Pin(e) => e.errno(),
+            Self::Parameter(e) => e.errno(),
+            Self::Stream(e) => e.errno(),
+            Self::Function(e) => e.errno(),
+            Self::Thread(e) => e.errno(),
+            Self::Signal(e) => e.errno(),
+            Self::InitialValue(_) => -(EINVAL as i32),
+            Self::Field { source, .. } => source.errno(),
+        }
     }
 }
@@ -0,0 +1,36 @@
+//! HAL bindings used by this crate
+//!
+//! Everything is re-exported from [`linuxcnc_hal_sys`]. With the `mock` feature enabled, the HAL
+//! functions are replaced by the in-process implementations in [`mock`](crate::mock). Explicit
+//! imports take precedence over the glob import, so the rest of the crate uses `crate::ffi` in
+//! place of `linuxcnc_hal_sys` without knowing which is in use.
+
+pub(crate) use linuxcnc_hal_sys::*;
+
+#[cfg(feature = "mock")]
+pub(crate) use crate::mock::sys::{
+    hal_add_funct_to_thread, hal_del_funct_from_thread, hal_exit, hal_get_param_value_by_name,
+    hal_get_pin_value_by_name, hal_get_signal_value_by_name, hal_init, hal_link, hal_malloc,
+    hal_param_alias, hal_param_bit_new, hal_param_bit_set, hal_param_float_new,
+    hal_param_float_set, hal_param_s32_new, hal_param_s32_set, hal_param_s64_new,
+    hal_param_s64_set, hal_param_u32_new, hal_param_u32_set, hal_param_u64_new, hal_param_u64_set,
+    hal_pin_alias, hal_pin_bit_new, hal_pin_float_new, hal_pin_port_new, hal_pin_s32_new,
+    hal_pin_s64_new, hal_pin_u32_new, hal_pin_u64_new, hal_port_buffer_size, hal_port_clear,
+    hal_port_peek, hal_port_peek_commit, hal_port_read, hal_port_readable, hal_port_writable,
+    hal_port_write, hal_ready, hal_shmem, hal_signal_delete, hal_signal_new, hal_start_threads,
+    hal_stop_threads, hal_stream_attach, hal_stream_create, hal_stream_depth, hal_stream_destroy,
+    hal_stream_detach, hal_stream_maxdepth, hal_stream_num_overruns, hal_stream_num_underruns,
+    hal_stream_read, hal_stream_readable, hal_stream_writable, hal_stream_write, hal_unlink,
+};
+
+#[cfg(all(feature = "mock", not(feature = "ulapi")))]
+pub(crate) use crate::mock::sys::{hal_create_thread, hal_export_funct, hal_thread_delete};
+
+/// Get the base of HAL shared memory and the `hal_data_t` at its start
+///
+/// Both pointers are null until a component has been created in this process.
+#[cfg(all(feature = "linuxcnc-2-9", not(feature = "mock")))]
+pub(crate) fn hal_shmem() -> (*mut std::os::raw::c_char, *mut hal_data_t) {
+    // SAFETY: Both globals are set by `hal_init` and never change afterwards
+    unsafe { (hal_shmem_base, hal_data) }
+}
@@ -0,0 +1,167 @@
+//! Realtime functions exported to HAL threads
+//!
+//! Functions can only be exported by realtime components, so this module is not available with the
+//! `ulapi` feature.
+
+use crate::error::{FunctionExportError, HalErrno, StorageError};
+use crate::ffi::{hal_export_funct, hal_malloc, HAL_NAME_LEN};
+use std::{
+    convert::TryInto,
+    ffi::{c_void, CString},
+    mem,
+    os::raw::c_long,
+    panic::{self, AssertUnwindSafe},
+    ptr,
+    time::Duration,
+};
+
+/// Options used when exporting a function
+#[derive(Copy, Clone, Debug)]
+pub struct FunctionOptions {
+    /// Whether the function uses floating point operations
+    ///
+    /// If in doubt, leave this as `true`. Defaults to `true`.
+    pub uses_fp: bool,
+}
+
+impl Default for FunctionOptions {
+    fn default() -> Self {
+        Self { uses_fp: true }
+    }
+}
+
+/// A function exported to the HAL
+///
+/// Returned by [`RegisterResources::export_function`](crate::RegisterResources::export_function)
+/// and [`RegisterResources::export_reentrant_function`](crate::RegisterResources::export_reentrant_function).
+/// Once exported, the function can be added to a realtime thread with e.g. `addf <name>
+/// servo-thread` in a HAL file.
+///
+/// Exported functions are removed by LinuxCNC when the component exits. Their state is leaked; see
+/// [`RegisterResources::export_function`](crate::RegisterResources::export_function).
+#[derive(Debug)]
+pub struct HalFunction {
+    pub(crate) name: String,
+}
+
+impl HalFunction {
+    /// Get the full name of the function, including the component name prefix
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// State and callback stored in HAL shared memory and passed to the trampoline on every call
+struct FunctionData<S, F> {
+    state: S,
+    function: F,
+}
+
+/// Called by HAL for exported non-reentrant functions
+pub(crate) unsafe extern "C" fn call_function<S, F>(arg: *mut c_void, period: c_long)
+where
+    F: FnMut(&mut S, Duration),
+{
+    // SAFETY: `arg` was created from a `FunctionData<S, F>` in `export`. Non-reentrant functions are
+    // never called concurrently, so this is the only reference to the data.
+    let data = &mut *(arg as *mut FunctionData<S, F>);
+
+    let period = Duration::from_nanos(period.max(0) as u64);
+
+    // Panics must not unwind across the FFI boundary into LinuxCNC
+    if panic::catch_unwind(AssertUnwindSafe(|| {
+        (data.function)(&mut data.state, period)
+    }))
+    .is_err()
+    {
+        error!("Exported function panicked");
+    }
+}
+
+/// Called by HAL for exported reentrant functions
+pub(crate) unsafe extern "C" fn call_reentrant_function<S, F>(arg: *mut c_void, period: c_long)
+where
+    F: Fn(&S, Duration),
+{
+    // SAFETY: `arg` was created from a `FunctionData<S, F>` in `export`. Reentrant functions may be
+    // called concurrently, so only shared references are created.
+    let data = &*(arg as *const FunctionData<S, F>);
+
+    let period = Duration::from_nanos(period.max(0) as u64);
+
+    if panic::catch_unwind(AssertUnwindSafe(|| (data.function)(&data.state, period))).is_err() {
+        error!("Exported function panicked");
+    }
+}
+
+/// Move `state` and `function` into HAL shared memory and export them under `full_name`
+///
+/// The data is never dropped. HAL shared memory is only freed when every component has exited, and
+/// `hal_exit` doesn't wait for a running function to return before removing it from its thread.
+pub(crate) fn export<S, F>(
+    full_name: &str,
+    options: FunctionOptions,
+    reentrant: bool,
+    state: S,
+    function: F,
+    trampoline: unsafe extern "C" fn(*mut c_void, c_long),
+    component_id: i32,
+) -> Result<HalFunction, FunctionExportError> {
+    if full_name.len() > HAL_NAME_LEN as usize {
+        return Err(FunctionExportError::NameLength(full_name.to_string()));
+    }
+
+    let full_name_ffi = CString::new(full_name).map_err(|e| {
+        error!("Failed to convert name to C string: {}", e);
+
+        FunctionExportError::NameConversion(full_name.to_string())
+    })?;
+
+    let data = unsafe {
+        let size = mem::size_of::<FunctionData<S, F>>().max(1);
+
+        debug!("Allocating {} bytes for function {}", size, full_name);
+
+        let ptr = hal_malloc(size.try_into().unwrap()) as *mut FunctionData<S, F>;
+
+        if ptr.is_null() {
+            return Err(FunctionExportError::Storage {
+                name: full_name.to_string(),
+                source: StorageError::Null,
+            });
+        }
+
+        if !ptr.is_aligned() {
+            return Err(FunctionExportError::Storage {
+                name: full_name.to_string(),
+                source: StorageError::Alignment,
+            });
+        }
+
+        ptr::write(ptr, FunctionData { state, function });
+
+        ptr
+    };
+
+    let ret = unsafe {
+        hal_export_funct(
+            full_name_ffi.as_ptr(),
+            Some(trampoline),
+            data.cast(),
+            options.uses_fp as i32,
+            reentrant as i32,
+            component_id,
+        )
+    };
+
+    HalErrno::check(ret).map_err(|source| FunctionExportError::Hal {
+        name: full_name.to_string(),
+        source,
+    })?;
+
+    debug!("Exported function {}", full_name);
+
+    Ok(HalFunction {
+        name: full_name.to_string(),
+    })
+}
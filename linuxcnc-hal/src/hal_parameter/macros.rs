@@ -1,11 +1,14 @@
 macro_rules! impl_param {
     ($type:ident, $storage:ty, $hal_fn:expr) => {
-        impl $type<$storage> {
+        // SAFETY: See `impl_pin!`
+        unsafe impl<'comp> Send for $type<'comp, $storage> {}
+
+        impl<'comp> $type<'comp, $storage> {
             /// Get the value of the parameter
             pub fn value(
                 &self,
             ) -> Result<
-                &<Self as $crate::hal_parameter::HalParameter>::Storage,
+                &<Self as $crate::hal_parameter::HalParameter<'comp>>::Storage,
                 $crate::error::StorageError,
             > {
                 $crate::hal_parameter::HalParameter::storage(self)
@@ -14,7 +17,7 @@ macro_rules! impl_param {
             /// Set the value of the parameter
             pub fn set_value(
                 &self,
-                value: <Self as $crate::hal_parameter::HalParameter>::Storage,
+                value: <Self as $crate::hal_parameter::HalParameter<'comp>>::Storage,
             ) -> Result<(), $crate::error::StorageError> {
                 let storage = $crate::hal_parameter::HalParameter::storage_mut(self)?;
 
@@ -24,7 +27,7 @@ macro_rules! impl_param {
             }
         }
 
-        impl $crate::hal_parameter::HalParameter for $type<$storage> {
+        impl<'comp> $crate::hal_parameter::HalParameter<'comp> for $type<'comp, $storage> {
             type Storage = $storage;
 
             fn name(&self) -> &str {
@@ -49,55 +52,57 @@ macro_rules! impl_param {
 
             fn register(
                 full_param_name: &str,
-                component_id: i32,
+                comp: &$crate::RegisterResources<'comp>,
                 direction: ParameterPermissions,
             ) -> Result<Self, $crate::error::ParameterRegisterError> {
                 if full_param_name.len() > linuxcnc_hal_sys::HAL_NAME_LEN as usize {
-                    return Err($crate::error::ParameterRegisterError::NameLength);
+                    return Err($crate::error::ParameterRegisterError::NameLength(
+                        full_param_name.to_string(),
+                    ));
                 }
 
                 let full_param_name_ffi = std::ffi::CString::new(full_param_name).map_err(|e| {
                     log::error!("Failed to convert name to C string: {}", e);
 
-                    $crate::error::ParameterRegisterError::NameConversion
+                    $crate::error::ParameterRegisterError::NameConversion(
+                        full_param_name.to_string(),
+                    )
                 })?;
 
                 let full_param_name_ffi = full_param_name_ffi.as_c_str();
 
                 log::debug!("Full pin name {:?}", full_param_name);
 
-                let storage = Self::allocate_storage()
-                    .map_err($crate::error::ParameterRegisterError::Storage)?;
+                let storage = Self::allocate_storage().map_err(|source| {
+                    $crate::error::ParameterRegisterError::Storage {
+                        name: full_param_name.to_string(),
+                        source,
+                    }
+                })?;
 
                 let ret = unsafe {
                     $hal_fn(
                         full_param_name_ffi.as_ptr() as *const std::os::raw::c_char,
                         direction as u32,
                         storage,
-                        component_id,
+                        comp.component().id(),
                     )
                 };
 
-                match ret {
-                    x if x == -(linuxcnc_hal_sys::EINVAL as i32) => {
-                        Err($crate::error::ParameterRegisterError::Invalid)
-                    }
-                    x if x == -(linuxcnc_hal_sys::EPERM as i32) => {
-                        Err($crate::error::ParameterRegisterError::LockedHal)
+                $crate::error::HalErrno::check(ret).map_err(|source| {
+                    $crate::error::ParameterRegisterError::Hal {
+                        name: full_param_name.to_string(),
+                        source,
                     }
-                    x if x == -(linuxcnc_hal_sys::ENOMEM as i32) => {
-                        Err($crate::error::ParameterRegisterError::Memory)
-                    }
-                    0 => {
-                        debug!("Make pin {} returned {}", full_param_name, ret);
+                })?;
 
-                        Ok(Self {
-                            name: full_param_name.to_string(),
-                            storage,
-                        })
-                    }
-                    code => unreachable!("Hit unreachable error code {}", code),
-                }
+                debug!("Make pin {} returned {}", full_param_name, ret);
+
+                Ok(Self {
+                    name: full_param_name.to_string(),
+                    storage,
+                    _component: std::marker::PhantomData,
+                })
             }
         }
     };
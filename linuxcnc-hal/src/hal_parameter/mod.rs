@@ -5,14 +5,18 @@ mod macros;
 
 mod parameter_trait;
 
-use linuxcnc_hal_sys::{
-    hal_param_bit_new, hal_param_dir_t_HAL_RO as HAL_RO, hal_param_dir_t_HAL_RW as HAL_RW,
-    hal_param_float_new, hal_param_s32_new, hal_param_u32_new,
+use crate::error::{HalErrno, ParameterRegisterError};
+use crate::ffi::{
+    hal_param_alias, hal_param_bit_new, hal_param_dir_t_HAL_RO as HAL_RO,
+    hal_param_dir_t_HAL_RW as HAL_RW, hal_param_float_new, hal_param_s32_new, hal_param_s64_new,
+    hal_param_u32_new, hal_param_u64_new, HAL_NAME_LEN,
 };
 pub use parameter_trait::HalParameter;
+use std::{ffi::CString, marker::PhantomData, ptr};
 
 /// Parameter write mode.
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ParameterPermissions {
     /// The parameter may only be read by LinuxCNC.
     ReadOnly = HAL_RO as isize,
@@ -30,6 +34,8 @@ pub enum ParameterPermissions {
 /// | `Parameter<f64>`  | `f64`   | [`hal_param_float_new`]                  |
 /// | `Parameter<u32>`  | `u32`   | [`hal_param_u32_new`]                    |
 /// | `Parameter<i32>`  | `i32`   | [`hal_param_s32_new`]                    |
+/// | `Parameter<u64>`  | `u64`   | [`hal_param_u64_new`]                    |
+/// | `Parameter<i64>`  | `i64`   | [`hal_param_s64_new`]                    |
 /// | `Parameter<bool>` | `bool`  | [`hal_param_bit_new`]                    |
 ///
 /// # Examples
@@ -51,14 +57,14 @@ pub enum ParameterPermissions {
 ///    time::{Duration, Instant},
 /// };
 ///
-/// struct MyApp {
-///    parameter: Parameter<f64>,
+/// struct MyApp<'comp> {
+///    parameter: Parameter<'comp, f64>,
 /// }
 ///
-/// impl Resources for MyApp {
+/// impl<'comp> Resources<'comp> for MyApp<'comp> {
 ///    type RegisterError = ParameterRegisterError;
 ///
-///    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+///    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
 ///        Ok(MyApp {
 ///            parameter: comp.register_parameter("named-parameter")?,
 ///        })
@@ -66,9 +72,9 @@ pub enum ParameterPermissions {
 /// }
 ///
 /// fn main() -> Result<(), Box<dyn Error>> {
-///    let comp: HalComponent<MyApp> = HalComponent::new("demo-component")?;
+///    let comp = HalComponent::new("demo-component")?;
 ///
-///    let MyApp { parameter } = comp.resources();
+///    let MyApp { parameter } = comp.register()?;
 ///
 ///    let start = Instant::now();
 ///
@@ -82,18 +88,148 @@ pub enum ParameterPermissions {
 /// }
 /// ```
 #[derive(Debug)]
-pub struct Parameter<S> {
+pub struct Parameter<'comp, S> {
     pub(crate) name: String,
     pub(crate) storage: *mut S,
+    pub(crate) _component: PhantomData<&'comp ()>,
 }
 
-impl<S> Drop for Parameter<S> {
+impl<S> Drop for Parameter<'_, S> {
     fn drop(&mut self) {
         debug!("Drop Parameter {}", self.name);
     }
 }
 
+impl<S> Parameter<'_, S> {
+    /// Give the parameter an alias, e.g. `x-axis.home-offset`
+    ///
+    /// The alias is used in place of the parameter's full name by `halcmd`. It is not prefixed
+    /// with the component name. Setting an alias replaces any previous alias.
+    ///
+    /// # Errors
+    ///
+    /// * [`ParameterRegisterError::NameLength`] - if the alias is longer than [`HAL_NAME_LEN`]
+    /// * [`ParameterRegisterError::NameConversion`] - if the alias cannot be converted to a C
+    ///   string
+    /// * [`ParameterRegisterError::Alias`] - if the alias is already used by another parameter
+    ///   ([`HalErrno::Invalid`]), the HAL is locked ([`HalErrno::LockedHal`]) or there is not
+    ///   enough shared memory to store the alias ([`HalErrno::Memory`])
+    pub fn set_alias(&self, alias: &str) -> Result<(), ParameterRegisterError> {
+        alias_parameter(&self.name, Some(alias))
+    }
+
+    /// Remove the parameter's alias, if it has one
+    pub fn clear_alias(&self) -> Result<(), ParameterRegisterError> {
+        alias_parameter(&self.name, None)
+    }
+}
+
+/// Set or clear the alias of the parameter called `full_name`
+pub(crate) fn alias_parameter(
+    full_name: &str,
+    alias: Option<&str>,
+) -> Result<(), ParameterRegisterError> {
+    let to_c = |name: &str| {
+        if name.len() > HAL_NAME_LEN as usize {
+            return Err(ParameterRegisterError::NameLength(name.to_string()));
+        }
+
+        CString::new(name).map_err(|e| {
+            error!("Failed to convert name to C string: {}", e);
+
+            ParameterRegisterError::NameConversion(name.to_string())
+        })
+    };
+
+    let name_ffi = to_c(full_name)?;
+    let alias_ffi = alias.map(to_c).transpose()?;
+
+    let ret = unsafe {
+        hal_param_alias(
+            name_ffi.as_ptr(),
+            alias_ffi
+                .as_ref()
+                .map_or(ptr::null(), |alias| alias.as_ptr()),
+        )
+    };
+
+    HalErrno::check(ret).map_err(|source| ParameterRegisterError::Alias {
+        name: full_name.to_string(),
+        source,
+    })?;
+
+    debug!("Set alias of parameter {} to {:?}", full_name, alias);
+
+    Ok(())
+}
+
 impl_param!(Parameter, f64, hal_param_float_new);
 impl_param!(Parameter, u32, hal_param_u32_new);
 impl_param!(Parameter, i32, hal_param_s32_new);
+impl_param!(Parameter, u64, hal_param_u64_new);
+impl_param!(Parameter, i64, hal_param_s64_new);
 impl_param!(Parameter, bool, hal_param_bit_new);
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        error::{QueryError, ResourcesError},
+        query::{self, HalValue},
+        HalComponent, RegisterResources, Resources,
+    };
+
+    struct Params<'comp> {
+        offset: Parameter<'comp, f64>,
+        scale: Parameter<'comp, f64>,
+    }
+
+    impl<'comp> Resources<'comp> for Params<'comp> {
+        type RegisterError = ResourcesError;
+
+        fn register_resources(
+            comp: &RegisterResources<'comp>,
+        ) -> Result<Self, Self::RegisterError> {
+            Ok(Params {
+                offset: comp.register_parameter_with_alias("offset", "x-axis.home-offset")?,
+                scale: comp.register_parameter("scale")?,
+            })
+        }
+    }
+
+    #[test]
+    fn set_and_clear_alias() {
+        let comp = HalComponent::new("palias").unwrap();
+        let params: Params = comp.register().unwrap();
+
+        query::set_parameter("x-axis.home-offset", HalValue::Float(1.5)).unwrap();
+
+        assert_eq!(params.offset.value(), Ok(&1.5));
+        assert_eq!(
+            query::parameter("palias.offset").map(|param| param.permissions),
+            Ok(ParameterPermissions::ReadWrite)
+        );
+
+        assert_eq!(
+            params.scale.set_alias("x-axis.home-offset"),
+            Err(ParameterRegisterError::Alias {
+                name: "palias.scale".to_string(),
+                source: HalErrno::Invalid,
+            })
+        );
+
+        params.offset.clear_alias().unwrap();
+
+        assert_eq!(
+            query::parameter("x-axis.home-offset"),
+            Err(QueryError::NotFound("x-axis.home-offset".to_string()))
+        );
+
+        params.scale.set_alias("x-axis.home-offset").unwrap();
+
+        assert_eq!(
+            query::parameter("x-axis.home-offset").map(|param| param.value),
+            Ok(HalValue::Float(0.0))
+        );
+    }
+}
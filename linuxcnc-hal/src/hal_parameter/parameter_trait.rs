@@ -1,6 +1,7 @@
 use crate::error::{ParameterRegisterError, StorageError};
+use crate::ffi::hal_malloc;
 use crate::hal_parameter::ParameterPermissions;
-use linuxcnc_hal_sys::hal_malloc;
+use crate::RegisterResources;
 use std::{convert::TryInto, mem};
 
 fn is_aligned_to<T: ?Sized>(ptr: *const T, align: usize) -> bool {
@@ -13,7 +14,7 @@ fn is_aligned_to<T: ?Sized>(ptr: *const T, align: usize) -> bool {
 /// HAL pin trait
 ///
 /// Implemented for any HAL pin. Handles allocation of backing storage in LinuxCNC's memory space.
-pub trait HalParameter: Sized {
+pub trait HalParameter<'comp>: Sized {
     /// The underlying storage type for the given pin
     ///
     /// This will usually be a scalar value such as `u32` or `bool`
@@ -69,7 +70,7 @@ pub trait HalParameter: Sized {
     /// Returns a raw pointer to the underling HAL shared memory for the pin
     fn register(
         full_pin_name: &str,
-        component_id: i32,
+        comp: &RegisterResources<'comp>,
         direction: ParameterPermissions,
     ) -> Result<Self, ParameterRegisterError>;
 }
@@ -1,5 +1,9 @@
+use crate::ffi::{
+    hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_pin_s64_new, hal_pin_u32_new,
+    hal_pin_u64_new,
+};
 use crate::hal_pin::{pin_direction::PinDirection, PinRead, PinWrite};
-use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_pin_u32_new};
+use std::marker::PhantomData;
 
 /// A pin that can be both read from and written to
 ///
@@ -10,6 +14,8 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// | `BidirectionalPin<f64>`  | `f64`   | [`hal_pin_float_new`]                  |
 /// | `BidirectionalPin<u32>`  | `u32`   | [`hal_pin_u32_new`]                    |
 /// | `BidirectionalPin<i32>`  | `i32`   | [`hal_pin_s32_new`]                    |
+/// | `BidirectionalPin<u64>`  | `u64`   | [`hal_pin_u64_new`]                    |
+/// | `BidirectionalPin<i64>`  | `i64`   | [`hal_pin_s64_new`]                    |
 /// | `BidirectionalPin<bool>` | `bool`  | [`hal_pin_bit_new`]                    |
 ///
 /// # Examples
@@ -31,14 +37,14 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 ///    time::{Duration, Instant},
 /// };
 ///
-/// struct Pins {
-///    pin: BidirectionalPin<f64>,
+/// struct Pins<'comp> {
+///    pin: BidirectionalPin<'comp, f64>,
 /// }
 ///
-/// impl Resources for Pins {
+/// impl<'comp> Resources<'comp> for Pins<'comp> {
 ///    type RegisterError = PinRegisterError;
 ///
-///    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+///    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
 ///        Ok(Pins {
 ///            pin: comp.register_pin::<BidirectionalPin<f64>>("named-pin")?,
 ///        })
@@ -46,9 +52,9 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// }
 ///
 /// fn main() -> Result<(), Box<dyn Error>> {
-///    let comp: HalComponent<Pins> = HalComponent::new("demo-component")?;
+///    let comp = HalComponent::new("demo-component")?;
 ///
-///    let Pins { pin } = comp.resources();
+///    let Pins { pin } = comp.register()?;
 ///
 ///    let start = Instant::now();
 ///
@@ -65,12 +71,13 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// }
 /// ```
 #[derive(Debug)]
-pub struct BidirectionalPin<S> {
+pub struct BidirectionalPin<'comp, S> {
     pub(crate) name: String,
     pub(crate) storage: *mut *mut S,
+    pub(crate) _component: PhantomData<&'comp ()>,
 }
 
-impl<S> Drop for BidirectionalPin<S> {
+impl<S> Drop for BidirectionalPin<'_, S> {
     fn drop(&mut self) {
         debug!("Drop BidirectionalPin {}", self.name);
     }
@@ -94,6 +101,18 @@ impl_pin!(
     hal_pin_s32_new,
     PinDirection::Bidirectional
 );
+impl_pin!(
+    BidirectionalPin,
+    u64,
+    hal_pin_u64_new,
+    PinDirection::Bidirectional
+);
+impl_pin!(
+    BidirectionalPin,
+    i64,
+    hal_pin_s64_new,
+    PinDirection::Bidirectional
+);
 impl_pin!(
     BidirectionalPin,
     bool,
@@ -101,12 +120,16 @@ impl_pin!(
     PinDirection::Bidirectional
 );
 
-impl PinWrite for BidirectionalPin<f64> {}
-impl PinWrite for BidirectionalPin<u32> {}
-impl PinWrite for BidirectionalPin<i32> {}
-impl PinWrite for BidirectionalPin<bool> {}
+impl<'comp> PinWrite<'comp> for BidirectionalPin<'comp, f64> {}
+impl<'comp> PinWrite<'comp> for BidirectionalPin<'comp, u32> {}
+impl<'comp> PinWrite<'comp> for BidirectionalPin<'comp, i32> {}
+impl<'comp> PinWrite<'comp> for BidirectionalPin<'comp, u64> {}
+impl<'comp> PinWrite<'comp> for BidirectionalPin<'comp, i64> {}
+impl<'comp> PinWrite<'comp> for BidirectionalPin<'comp, bool> {}
 
-impl PinRead for BidirectionalPin<f64> {}
-impl PinRead for BidirectionalPin<u32> {}
-impl PinRead for BidirectionalPin<i32> {}
-impl PinRead for BidirectionalPin<bool> {}
+impl<'comp> PinRead<'comp> for BidirectionalPin<'comp, f64> {}
+impl<'comp> PinRead<'comp> for BidirectionalPin<'comp, u32> {}
+impl<'comp> PinRead<'comp> for BidirectionalPin<'comp, i32> {}
+impl<'comp> PinRead<'comp> for BidirectionalPin<'comp, u64> {}
+impl<'comp> PinRead<'comp> for BidirectionalPin<'comp, i64> {}
+impl<'comp> PinRead<'comp> for BidirectionalPin<'comp, bool> {}
@@ -1,6 +1,7 @@
-use crate::error::{PinRegisterError, StorageError};
-use linuxcnc_hal_sys::hal_malloc;
-use std::{convert::TryInto, mem};
+use crate::error::{HalErrno, PinRegisterError, StorageError};
+use crate::ffi::{hal_malloc, hal_pin_alias, HAL_NAME_LEN};
+use crate::RegisterResources;
+use std::{convert::TryInto, ffi::CString, mem, ptr};
 
 fn is_aligned_to<T: ?Sized>(ptr: *const T, align: usize) -> bool {
     assert!(align.is_power_of_two());
@@ -12,7 +13,10 @@ fn is_aligned_to<T: ?Sized>(ptr: *const T, align: usize) -> bool {
 /// HAL pin trait
 ///
 /// Implemented for any HAL pin. Handles allocation of backing storage in LinuxCNC's memory space.
-pub trait HalPin: Sized {
+///
+/// Pins borrow the component they are registered with for `'comp`, as their storage is freed when
+/// the component exits.
+pub trait HalPin<'comp>: Sized {
     /// The underlying storage type for the given pin
     ///
     /// This will usually be a scalar value such as `u32` or `bool`
@@ -64,7 +68,154 @@ pub trait HalPin: Sized {
     fn storage(&self) -> Result<&Self::Storage, StorageError>;
 
     /// Register the pin with the LinuxCNC HAL
+    fn register(
+        full_pin_name: &str,
+        comp: &RegisterResources<'comp>,
+    ) -> Result<Self, PinRegisterError>;
+
+    /// Give the pin an alias, e.g. `x-axis.home-sw`
+    ///
+    /// The alias is used in place of the pin's full name by `halcmd` and can be used to link the
+    /// pin to a signal. It is not prefixed with the component name. Setting an alias replaces any
+    /// previous alias.
+    ///
+    /// # Errors
     ///
-    /// Returns a raw pointer to the underling HAL shared memory for the pin
-    fn register(full_pin_name: &str, component_id: i32) -> Result<Self, PinRegisterError>;
+    /// * [`PinRegisterError::NameLength`] - if the alias is longer than [`HAL_NAME_LEN`]
+    /// * [`PinRegisterError::NameConversion`] - if the alias cannot be converted to a C string
+    /// * [`PinRegisterError::Alias`] - if the alias is already used by another pin
+    ///   ([`HalErrno::Invalid`]), the HAL is locked ([`HalErrno::LockedHal`]) or there is not
+    ///   enough shared memory to store the alias ([`HalErrno::Memory`])
+    fn set_alias(&self, alias: &str) -> Result<(), PinRegisterError> {
+        alias_pin(self.name(), Some(alias))
+    }
+
+    /// Remove the pin's alias, if it has one
+    fn clear_alias(&self) -> Result<(), PinRegisterError> {
+        alias_pin(self.name(), None)
+    }
+}
+
+/// Set or clear the alias of the pin called `full_pin_name`
+fn alias_pin(full_pin_name: &str, alias: Option<&str>) -> Result<(), PinRegisterError> {
+    let to_c = |name: &str| {
+        if name.len() > HAL_NAME_LEN as usize {
+            return Err(PinRegisterError::NameLength(name.to_string()));
+        }
+
+        CString::new(name).map_err(|e| {
+            error!("Failed to convert name to C string: {}", e);
+
+            PinRegisterError::NameConversion(name.to_string())
+        })
+    };
+
+    let pin_name_ffi = to_c(full_pin_name)?;
+    let alias_ffi = alias.map(to_c).transpose()?;
+
+    let ret = unsafe {
+        hal_pin_alias(
+            pin_name_ffi.as_ptr(),
+            alias_ffi
+                .as_ref()
+                .map_or(ptr::null(), |alias| alias.as_ptr()),
+        )
+    };
+
+    HalErrno::check(ret).map_err(|source| PinRegisterError::Alias {
+        name: full_pin_name.to_string(),
+        source,
+    })?;
+
+    debug!("Set alias of pin {} to {:?}", full_pin_name, alias);
+
+    Ok(())
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        error::{QueryError, ResourcesError},
+        hal_pin::{InputPin, PinDirection},
+        prelude::*,
+        query::{self, HalValue},
+        HalComponent, Resources,
+    };
+
+    struct Pins<'comp> {
+        home: InputPin<'comp, bool>,
+        limit: InputPin<'comp, bool>,
+    }
+
+    impl<'comp> Resources<'comp> for Pins<'comp> {
+        type RegisterError = ResourcesError;
+
+        fn register_resources(
+            comp: &RegisterResources<'comp>,
+        ) -> Result<Self, Self::RegisterError> {
+            Ok(Pins {
+                home: comp.register_pin_with_alias("home", "x-axis.home-sw")?,
+                limit: comp.register_pin("limit")?,
+            })
+        }
+    }
+
+    #[test]
+    fn set_and_clear_alias() {
+        let comp = HalComponent::new("alias").unwrap();
+        let pins: Pins = comp.register().unwrap();
+
+        // Pins can be looked up by their full name or their alias
+        crate::mock::set_pin("x-axis.home-sw", HalValue::Bit(true)).unwrap();
+
+        assert_eq!(pins.home.value(), Ok(&true));
+        assert_eq!(
+            query::pin("alias.home").map(|pin| pin.value),
+            Ok(HalValue::Bit(true))
+        );
+        assert_eq!(
+            query::pin("x-axis.home-sw").map(|pin| pin.direction),
+            Ok(PinDirection::In)
+        );
+
+        assert_eq!(
+            pins.limit.set_alias("x-axis.home-sw"),
+            Err(PinRegisterError::Alias {
+                name: "alias.limit".to_string(),
+                source: HalErrno::Invalid,
+            })
+        );
+
+        pins.limit.set_alias("x-axis.limit").unwrap();
+
+        assert!(query::pin("x-axis.limit").is_ok());
+
+        // A new alias replaces the old one
+        pins.limit.set_alias("x-axis.neg-limit").unwrap();
+
+        assert_eq!(
+            query::pin("x-axis.limit"),
+            Err(QueryError::NotFound("x-axis.limit".to_string()))
+        );
+        assert!(query::pin("x-axis.neg-limit").is_ok());
+
+        pins.home.clear_alias().unwrap();
+
+        assert_eq!(
+            query::pin("x-axis.home-sw"),
+            Err(QueryError::NotFound("x-axis.home-sw".to_string()))
+        );
+        assert!(query::pin("alias.home").is_ok());
+
+        // The freed alias can be reused
+        pins.limit.set_alias("x-axis.home-sw").unwrap();
+
+        assert_eq!(
+            pins.home.set_alias(&"x".repeat(HAL_NAME_LEN as usize + 1)),
+            Err(PinRegisterError::NameLength(
+                "x".repeat(HAL_NAME_LEN as usize + 1)
+            ))
+        );
+    }
 }
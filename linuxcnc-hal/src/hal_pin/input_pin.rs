@@ -1,5 +1,9 @@
+use crate::ffi::{
+    hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_pin_s64_new, hal_pin_u32_new,
+    hal_pin_u64_new,
+};
 use crate::hal_pin::{pin_direction::PinDirection, PinRead};
-use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_pin_u32_new};
+use std::marker::PhantomData;
 
 /// An input pin readable by the component
 ///
@@ -10,6 +14,8 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// | `InputPin<f64>`  | `f64`   | [`hal_pin_float_new`]                  |
 /// | `InputPin<u32>`  | `u32`   | [`hal_pin_u32_new`]                    |
 /// | `InputPin<i32>`  | `i32`   | [`hal_pin_s32_new`]                    |
+/// | `InputPin<u64>`  | `u64`   | [`hal_pin_u64_new`]                    |
+/// | `InputPin<i64>`  | `i64`   | [`hal_pin_s64_new`]                    |
 /// | `InputPin<bool>` | `bool`  | [`hal_pin_bit_new`]                    |
 ///
 /// # Examples
@@ -31,14 +37,14 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 ///    time::{Duration, Instant},
 /// };
 ///
-/// struct Pins {
-///    pin: InputPin<f64>,
+/// struct Pins<'comp> {
+///    pin: InputPin<'comp, f64>,
 /// }
 ///
-/// impl Resources for Pins {
+/// impl<'comp> Resources<'comp> for Pins<'comp> {
 ///    type RegisterError = PinRegisterError;
 ///
-///    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+///    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
 ///        Ok(Pins {
 ///            pin: comp.register_pin::<InputPin<f64>>("named-pin")?,
 ///        })
@@ -46,9 +52,9 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// }
 ///
 /// fn main() -> Result<(), Box<dyn Error>> {
-///    let comp: HalComponent<Pins> = HalComponent::new("demo-component")?;
+///    let comp = HalComponent::new("demo-component")?;
 ///
-///    let Pins { pin } = comp.resources();
+///    let Pins { pin } = comp.register()?;
 ///
 ///    let start = Instant::now();
 ///
@@ -63,12 +69,13 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// }
 /// ```
 #[derive(Debug)]
-pub struct InputPin<S> {
+pub struct InputPin<'comp, S> {
     pub(crate) name: String,
     pub(crate) storage: *mut *mut S,
+    pub(crate) _component: PhantomData<&'comp ()>,
 }
 
-impl<S> Drop for InputPin<S> {
+impl<S> Drop for InputPin<'_, S> {
     fn drop(&mut self) {
         debug!("Drop InputPin {}", self.name);
     }
@@ -77,9 +84,13 @@ impl<S> Drop for InputPin<S> {
 impl_pin!(InputPin, f64, hal_pin_float_new, PinDirection::In);
 impl_pin!(InputPin, u32, hal_pin_u32_new, PinDirection::In);
 impl_pin!(InputPin, i32, hal_pin_s32_new, PinDirection::In);
+impl_pin!(InputPin, u64, hal_pin_u64_new, PinDirection::In);
+impl_pin!(InputPin, i64, hal_pin_s64_new, PinDirection::In);
 impl_pin!(InputPin, bool, hal_pin_bit_new, PinDirection::In);
 
-impl PinRead for InputPin<f64> {}
-impl PinRead for InputPin<u32> {}
-impl PinRead for InputPin<i32> {}
-impl PinRead for InputPin<bool> {}
+impl<'comp> PinRead<'comp> for InputPin<'comp, f64> {}
+impl<'comp> PinRead<'comp> for InputPin<'comp, u32> {}
+impl<'comp> PinRead<'comp> for InputPin<'comp, i32> {}
+impl<'comp> PinRead<'comp> for InputPin<'comp, u64> {}
+impl<'comp> PinRead<'comp> for InputPin<'comp, i64> {}
+impl<'comp> PinRead<'comp> for InputPin<'comp, bool> {}
@@ -0,0 +1,184 @@
+use crate::ffi::{
+    hal_pin_port_new, hal_port_buffer_size, hal_port_clear, hal_port_peek, hal_port_peek_commit,
+    hal_port_read, hal_port_readable, hal_port_t,
+};
+use crate::{error::StorageError, hal_pin::pin_direction::PinDirection, hal_pin::HalPin};
+use std::{convert::TryFrom, io, marker::PhantomData};
+
+/// A port pin that the component can read a stream of bytes from
+///
+/// Ports are a HAL pin type that pass variable length messages between components through a ring
+/// buffer in HAL shared memory. The buffer belongs to the signal the port is connected to. Only
+/// one component may read from a port, and only one may write to it.
+///
+/// Along with the byte stream methods [`read`](InputPort::read) and
+/// [`read_exact`](InputPort::read_exact), data can be inspected with [`peek`](InputPort::peek)
+/// without consuming it. Once a peeked message is handled, call [`commit`](InputPort::commit)
+/// with the same number of bytes to remove it from the buffer.
+///
+/// `&InputPort` also implements [`std::io::Read`]. If no bytes are available, reads return an
+/// error of kind [`std::io::ErrorKind::WouldBlock`].
+///
+/// | Type        | Storage      | Equivalent `linuxcnc_hal_sys` function |
+/// | ----------- | ------------ | -------------------------------------- |
+/// | `InputPort` | `hal_port_t` | [`hal_pin_port_new`]                   |
+///
+/// # Examples
+///
+/// ## Read fixed size packets
+///
+/// This example creates an `InputPort` under `demo-component.packets` and reads 4 byte packets
+/// from it.
+///
+/// ```rust,no_run
+/// use linuxcnc_hal::{
+///    error::PinRegisterError,
+///    hal_pin::InputPort,
+///    HalComponent, RegisterResources, Resources,
+/// };
+/// use std::{
+///    error::Error,
+///    thread,
+///    time::Duration,
+/// };
+///
+/// struct Pins<'comp> {
+///    packets: InputPort<'comp>,
+/// }
+///
+/// impl<'comp> Resources<'comp> for Pins<'comp> {
+///    type RegisterError = PinRegisterError;
+///
+///    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+///        Ok(Pins {
+///            packets: comp.register_pin::<InputPort>("packets")?,
+///        })
+///    }
+/// }
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///    let comp = HalComponent::new("demo-component")?;
+///
+///    let Pins { packets } = comp.register()?;
+///
+///    let mut packet = [0u8; 4];
+///
+///    // Main control loop
+///    while !comp.should_exit() {
+///        while packets.read_exact(&mut packet)? {
+///            println!("Packet: {:?}", packet);
+///        }
+///
+///        thread::sleep(Duration::from_millis(100));
+///    }
+///
+///    Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct InputPort<'comp> {
+    pub(crate) name: String,
+    pub(crate) storage: *mut *mut hal_port_t,
+    pub(crate) _component: PhantomData<&'comp ()>,
+}
+
+impl Drop for InputPort<'_> {
+    fn drop(&mut self) {
+        debug!("Drop InputPort {}", self.name);
+    }
+}
+
+impl_pin!(@port InputPort, hal_pin_port_new, PinDirection::In);
+
+impl InputPort<'_> {
+    /// Get the number of bytes available to read from the port
+    pub fn readable(&self) -> Result<usize, StorageError> {
+        let port = *self.storage()?;
+
+        Ok(unsafe { hal_port_readable(port) } as usize)
+    }
+
+    /// Get the total number of bytes the port can buffer
+    pub fn buffer_size(&self) -> Result<usize, StorageError> {
+        let port = *self.storage()?;
+
+        Ok(unsafe { hal_port_buffer_size(port) } as usize)
+    }
+
+    /// Read as many bytes as are available into `buf`, up to its length
+    ///
+    /// Returns the number of bytes read, which will be `0` if the port is empty.
+    pub fn read(&self, buf: &mut [u8]) -> Result<usize, StorageError> {
+        let count = self.readable()?.min(buf.len());
+
+        if count > 0 && self.read_exact(&mut buf[0..count])? {
+            Ok(count)
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Read exactly `buf.len()` bytes from the port
+    ///
+    /// Returns `false` and leaves the port untouched if fewer than `buf.len()` bytes are available.
+    pub fn read_exact(&self, buf: &mut [u8]) -> Result<bool, StorageError> {
+        let port = *self.storage()?;
+
+        let count = match u32::try_from(buf.len()) {
+            Ok(count) => count,
+            Err(_) => return Ok(false),
+        };
+
+        Ok(unsafe { hal_port_read(port, buf.as_mut_ptr().cast(), count) })
+    }
+
+    /// Copy exactly `buf.len()` bytes from the port without consuming them
+    ///
+    /// Repeated calls return the same data until [`commit`](InputPort::commit) or a read is
+    /// called. Returns `false` if fewer than `buf.len()` bytes are available.
+    pub fn peek(&self, buf: &mut [u8]) -> Result<bool, StorageError> {
+        let port = *self.storage()?;
+
+        let count = match u32::try_from(buf.len()) {
+            Ok(count) => count,
+            Err(_) => return Ok(false),
+        };
+
+        Ok(unsafe { hal_port_peek(port, buf.as_mut_ptr().cast(), count) })
+    }
+
+    /// Consume `count` previously peeked bytes
+    ///
+    /// A [`peek`](InputPort::peek) followed by a `commit` of the same length is equivalent to a
+    /// call to [`read_exact`](InputPort::read_exact). Returns `false` and consumes nothing if
+    /// fewer than `count` bytes are available.
+    pub fn commit(&self, count: usize) -> Result<bool, StorageError> {
+        let port = *self.storage()?;
+
+        let count = match u32::try_from(count) {
+            Ok(count) => count,
+            Err(_) => return Ok(false),
+        };
+
+        Ok(unsafe { hal_port_peek_commit(port, count) })
+    }
+
+    /// Discard all bytes currently in the port without reading them
+    pub fn clear(&self) -> Result<(), StorageError> {
+        let port = *self.storage()?;
+
+        unsafe { hal_port_clear(port) };
+
+        Ok(())
+    }
+}
+
+impl io::Read for &InputPort<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match InputPort::read(self, buf) {
+            Ok(0) if !buf.is_empty() => Err(io::ErrorKind::WouldBlock.into()),
+            Ok(count) => Ok(count),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+}
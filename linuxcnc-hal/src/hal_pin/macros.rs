@@ -1,6 +1,18 @@
 macro_rules! impl_pin {
     ($type:ident, $storage:ty, $hal_fn:expr, $direction:expr) => {
-        impl $crate::hal_pin::HalPin for $type<$storage> {
+        impl_pin!(@impl $type<'comp, $storage>, $storage, $hal_fn, $direction);
+    };
+    (@port $type:ident, $hal_fn:expr, $direction:expr) => {
+        impl_pin!(@impl $type<'comp>, linuxcnc_hal_sys::hal_port_t, $hal_fn, $direction);
+    };
+    (@impl $type:ty, $storage:ty, $hal_fn:expr, $direction:expr) => {
+        // SAFETY: The only non-`Send` field is the storage pointer, which points into HAL shared
+        // memory. HAL shared memory is designed to be accessed from any process or thread, including
+        // the realtime threads that run exported functions, so moving the pointer to another thread
+        // is sound. Parameters rely on the same reasoning in `impl_param!`.
+        unsafe impl<'comp> Send for $type {}
+
+        impl<'comp> $crate::hal_pin::HalPin<'comp> for $type {
             type Storage = $storage;
 
             fn name(&self) -> &str {
@@ -25,16 +37,18 @@ macro_rules! impl_pin {
 
             fn register(
                 full_pin_name: &str,
-                component_id: i32,
+                comp: &$crate::RegisterResources<'comp>,
             ) -> Result<Self, $crate::error::PinRegisterError> {
                 if full_pin_name.len() > linuxcnc_hal_sys::HAL_NAME_LEN as usize {
-                    return Err($crate::error::PinRegisterError::NameLength);
+                    return Err($crate::error::PinRegisterError::NameLength(
+                        full_pin_name.to_string(),
+                    ));
                 }
 
                 let full_pin_name_ffi = std::ffi::CString::new(full_pin_name).map_err(|e| {
                     log::error!("Failed to convert name to C string: {}", e);
 
-                    $crate::error::PinRegisterError::NameConversion
+                    $crate::error::PinRegisterError::NameConversion(full_pin_name.to_string())
                 })?;
 
                 let full_pin_name_ffi = full_pin_name_ffi.as_c_str();
@@ -42,37 +56,36 @@ macro_rules! impl_pin {
                 log::debug!("Full pin name {:?}", full_pin_name);
 
                 let storage =
-                    Self::allocate_storage().map_err($crate::error::PinRegisterError::Storage)?;
+                    Self::allocate_storage().map_err(|source| {
+                    $crate::error::PinRegisterError::Storage {
+                        name: full_pin_name.to_string(),
+                        source,
+                    }
+                })?;
 
                 let ret = unsafe {
                     $hal_fn(
                         full_pin_name_ffi.as_ptr() as *const std::os::raw::c_char,
                         $direction as i32,
                         storage,
-                        component_id,
+                        comp.component().id(),
                     )
                 };
 
-                match ret {
-                    x if x == -(linuxcnc_hal_sys::EINVAL as i32) => {
-                        Err($crate::error::PinRegisterError::Invalid)
-                    }
-                    x if x == -(linuxcnc_hal_sys::EPERM as i32) => {
-                        Err($crate::error::PinRegisterError::LockedHal)
+                $crate::error::HalErrno::check(ret).map_err(|source| {
+                    $crate::error::PinRegisterError::Hal {
+                        name: full_pin_name.to_string(),
+                        source,
                     }
-                    x if x == -(linuxcnc_hal_sys::ENOMEM as i32) => {
-                        Err($crate::error::PinRegisterError::Memory)
-                    }
-                    0 => {
-                        debug!("Make pin {} returned {}", full_pin_name, ret);
+                })?;
 
-                        Ok(Self {
-                            name: full_pin_name.to_string(),
-                            storage,
-                        })
-                    }
-                    code => unreachable!("Hit unreachable error code {}", code),
-                }
+                debug!("Make pin {} returned {}", full_pin_name, ret);
+
+                Ok(Self {
+                    name: full_pin_name.to_string(),
+                    storage,
+                    _component: std::marker::PhantomData,
+                })
             }
         }
     };
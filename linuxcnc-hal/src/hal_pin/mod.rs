@@ -6,21 +6,25 @@ mod macros;
 mod bidirectional_pin;
 mod hal_pin;
 mod input_pin;
+mod input_port;
 mod output_pin;
+mod output_port;
+mod pin_array;
 mod pin_direction;
 
 pub use self::{
     bidirectional_pin::BidirectionalPin, hal_pin::HalPin, input_pin::InputPin,
-    output_pin::OutputPin,
+    input_port::InputPort, output_pin::OutputPin, output_port::OutputPort, pin_array::PinArray,
+    pin_direction::PinDirection,
 };
 use crate::error::StorageError;
 
 /// Readable pin trait
 ///
 /// Implemented for any pin that can only be read by a component
-pub trait PinRead: HalPin {
+pub trait PinRead<'comp>: HalPin<'comp> {
     /// Get the value of the pin
-    fn value(&self) -> Result<&<Self as HalPin>::Storage, StorageError> {
+    fn value(&self) -> Result<&<Self as HalPin<'comp>>::Storage, StorageError> {
         self.storage()
     }
 }
@@ -28,9 +32,9 @@ pub trait PinRead: HalPin {
 /// Writable pin trait
 ///
 /// Implemented for any pin that can be only written to by a component
-pub trait PinWrite: HalPin {
+pub trait PinWrite<'comp>: HalPin<'comp> {
     /// Set the value of the pin
-    fn set_value(&self, value: <Self as HalPin>::Storage) -> Result<(), StorageError> {
+    fn set_value(&self, value: <Self as HalPin<'comp>>::Storage) -> Result<(), StorageError> {
         Ok(*self.storage_mut()? = value)
     }
 }
@@ -1,5 +1,9 @@
+use crate::ffi::{
+    hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_pin_s64_new, hal_pin_u32_new,
+    hal_pin_u64_new,
+};
 use crate::hal_pin::{pin_direction::PinDirection, PinWrite};
-use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_pin_u32_new};
+use std::marker::PhantomData;
 
 /// A pin that can be written to by the component
 ///
@@ -10,6 +14,8 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// | `OutputPin<f64>`  | `f64`   | [`hal_pin_float_new`]                  |
 /// | `OutputPin<u32>`  | `u32`   | [`hal_pin_u32_new`]                    |
 /// | `OutputPin<i32>`  | `i32`   | [`hal_pin_s32_new`]                    |
+/// | `OutputPin<u64>`  | `u64`   | [`hal_pin_u64_new`]                    |
+/// | `OutputPin<i64>`  | `i64`   | [`hal_pin_s64_new`]                    |
 /// | `OutputPin<bool>` | `bool`  | [`hal_pin_bit_new`]                    |
 ///
 /// # Examples
@@ -31,14 +37,14 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 ///    time::{Duration, Instant},
 /// };
 ///
-/// struct Pins {
-///    pin: OutputPin<f64>,
+/// struct Pins<'comp> {
+///    pin: OutputPin<'comp, f64>,
 /// }
 ///
-/// impl Resources for Pins {
+/// impl<'comp> Resources<'comp> for Pins<'comp> {
 ///    type RegisterError = PinRegisterError;
 ///
-///    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+///    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
 ///        Ok(Pins {
 ///            pin: comp.register_pin::<OutputPin<f64>>("named-pin")?,
 ///        })
@@ -46,9 +52,9 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// }
 ///
 /// fn main() -> Result<(), Box<dyn Error>> {
-///    let comp: HalComponent<Pins> = HalComponent::new("demo-component")?;
+///    let comp = HalComponent::new("demo-component")?;
 ///
-///    let Pins { pin } = comp.resources();
+///    let Pins { pin } = comp.register()?;
 ///
 ///    let start = Instant::now();
 ///
@@ -62,12 +68,13 @@ use linuxcnc_hal_sys::{hal_pin_bit_new, hal_pin_float_new, hal_pin_s32_new, hal_
 /// }
 /// ```
 #[derive(Debug)]
-pub struct OutputPin<S> {
+pub struct OutputPin<'comp, S> {
     pub(crate) name: String,
     pub(crate) storage: *mut *mut S,
+    pub(crate) _component: PhantomData<&'comp ()>,
 }
 
-impl<S> Drop for OutputPin<S> {
+impl<S> Drop for OutputPin<'_, S> {
     fn drop(&mut self) {
         debug!("Drop OutputPin {}", self.name);
     }
@@ -76,9 +83,13 @@ impl<S> Drop for OutputPin<S> {
 impl_pin!(OutputPin, f64, hal_pin_float_new, PinDirection::Out);
 impl_pin!(OutputPin, u32, hal_pin_u32_new, PinDirection::Out);
 impl_pin!(OutputPin, i32, hal_pin_s32_new, PinDirection::Out);
+impl_pin!(OutputPin, u64, hal_pin_u64_new, PinDirection::Out);
+impl_pin!(OutputPin, i64, hal_pin_s64_new, PinDirection::Out);
 impl_pin!(OutputPin, bool, hal_pin_bit_new, PinDirection::Out);
 
-impl PinWrite for OutputPin<f64> {}
-impl PinWrite for OutputPin<u32> {}
-impl PinWrite for OutputPin<i32> {}
-impl PinWrite for OutputPin<bool> {}
+impl<'comp> PinWrite<'comp> for OutputPin<'comp, f64> {}
+impl<'comp> PinWrite<'comp> for OutputPin<'comp, u32> {}
+impl<'comp> PinWrite<'comp> for OutputPin<'comp, i32> {}
+impl<'comp> PinWrite<'comp> for OutputPin<'comp, u64> {}
+impl<'comp> PinWrite<'comp> for OutputPin<'comp, i64> {}
+impl<'comp> PinWrite<'comp> for OutputPin<'comp, bool> {}
@@ -0,0 +1,139 @@
+use crate::ffi::{
+    hal_pin_port_new, hal_port_buffer_size, hal_port_t, hal_port_writable, hal_port_write,
+};
+use crate::{error::StorageError, hal_pin::pin_direction::PinDirection, hal_pin::HalPin};
+use std::{convert::TryFrom, io, marker::PhantomData};
+
+/// A port pin that the component can write a stream of bytes to
+///
+/// See [`InputPort`](crate::hal_pin::InputPort) for details on how ports work.
+///
+/// `&OutputPort` also implements [`std::io::Write`]. If the port is full, writes return an error
+/// of kind [`std::io::ErrorKind::WouldBlock`].
+///
+/// | Type         | Storage      | Equivalent `linuxcnc_hal_sys` function |
+/// | ------------ | ------------ | -------------------------------------- |
+/// | `OutputPort` | `hal_port_t` | [`hal_pin_port_new`]                   |
+///
+/// # Examples
+///
+/// ## Write a message
+///
+/// This example creates an `OutputPort` under `demo-component.messages` and writes a message to
+/// it every second if there is enough space in the buffer.
+///
+/// ```rust,no_run
+/// use linuxcnc_hal::{
+///    error::PinRegisterError,
+///    hal_pin::OutputPort,
+///    HalComponent, RegisterResources, Resources,
+/// };
+/// use std::{
+///    error::Error,
+///    thread,
+///    time::Duration,
+/// };
+///
+/// struct Pins<'comp> {
+///    messages: OutputPort<'comp>,
+/// }
+///
+/// impl<'comp> Resources<'comp> for Pins<'comp> {
+///    type RegisterError = PinRegisterError;
+///
+///    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+///        Ok(Pins {
+///            messages: comp.register_pin::<OutputPort>("messages")?,
+///        })
+///    }
+/// }
+///
+/// fn main() -> Result<(), Box<dyn Error>> {
+///    let comp = HalComponent::new("demo-component")?;
+///
+///    let Pins { messages } = comp.register()?;
+///
+///    // Main control loop
+///    while !comp.should_exit() {
+///        if !messages.write_all(b"G0 X10 Y20\n")? {
+///            println!("Port is full");
+///        }
+///
+///        thread::sleep(Duration::from_millis(1000));
+///    }
+///
+///    Ok(())
+/// }
+/// ```
+#[derive(Debug)]
+pub struct OutputPort<'comp> {
+    pub(crate) name: String,
+    pub(crate) storage: *mut *mut hal_port_t,
+    pub(crate) _component: PhantomData<&'comp ()>,
+}
+
+impl Drop for OutputPort<'_> {
+    fn drop(&mut self) {
+        debug!("Drop OutputPort {}", self.name);
+    }
+}
+
+impl_pin!(@port OutputPort, hal_pin_port_new, PinDirection::Out);
+
+impl OutputPort<'_> {
+    /// Get the number of bytes that can currently be written to the port
+    pub fn writable(&self) -> Result<usize, StorageError> {
+        let port = *self.storage()?;
+
+        Ok(unsafe { hal_port_writable(port) } as usize)
+    }
+
+    /// Get the total number of bytes the port can buffer
+    pub fn buffer_size(&self) -> Result<usize, StorageError> {
+        let port = *self.storage()?;
+
+        Ok(unsafe { hal_port_buffer_size(port) } as usize)
+    }
+
+    /// Write as many bytes from `buf` as there is space for in the port
+    ///
+    /// Returns the number of bytes written, which will be `0` if the port is full.
+    pub fn write(&self, buf: &[u8]) -> Result<usize, StorageError> {
+        let count = self.writable()?.min(buf.len());
+
+        if count > 0 && self.write_all(&buf[0..count])? {
+            Ok(count)
+        } else {
+            Ok(0)
+        }
+    }
+
+    /// Write all of `buf` to the port
+    ///
+    /// Returns `false` and writes nothing if there is not enough space for the whole buffer. This
+    /// guarantees that the reader never sees a partially written message.
+    pub fn write_all(&self, buf: &[u8]) -> Result<bool, StorageError> {
+        let port = *self.storage()?;
+
+        let count = match u32::try_from(buf.len()) {
+            Ok(count) => count,
+            Err(_) => return Ok(false),
+        };
+
+        Ok(unsafe { hal_port_write(port, buf.as_ptr().cast(), count) })
+    }
+}
+
+impl io::Write for &OutputPort<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match OutputPort::write(self, buf) {
+            Ok(0) if !buf.is_empty() => Err(io::ErrorKind::WouldBlock.into()),
+            Ok(count) => Ok(count),
+            Err(e) => Err(io::Error::other(e)),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
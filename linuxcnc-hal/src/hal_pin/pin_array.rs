@@ -0,0 +1,152 @@
+use crate::{error::PinRegisterError, hal_pin::HalPin, RegisterResources};
+use linuxcnc_hal_sys::HAL_NAME_LEN;
+use std::{ops::Deref, slice};
+
+/// A fixed size group of pins with indexed names
+///
+/// Registered with [`RegisterResources::register_pin_array`](crate::RegisterResources::register_pin_array).
+/// Each pin is named `<component>.<prefix>.<index>.<suffix>`, where `<index>` counts up from `0`,
+/// following the LinuxCNC convention for per-joint or per-channel pins, e.g. `joint.0.pos-cmd`. If
+/// the suffix is empty, pins are named `<component>.<prefix>.<index>` instead.
+///
+/// A `PinArray` dereferences to a slice of pins, so it can be indexed and iterated over.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use linuxcnc_hal::{
+///     error::PinRegisterError,
+///     hal_pin::{InputPin, OutputPin, PinArray},
+///     prelude::*,
+///     HalComponent, RegisterResources, Resources,
+/// };
+///
+/// struct Pins<'comp> {
+///     /// `axes.joint.0.pos-cmd` to `axes.joint.8.pos-cmd`
+///     pos_cmd: PinArray<InputPin<'comp, f64>, 9>,
+///
+///     /// `axes.joint.0.pos-fb` to `axes.joint.8.pos-fb`
+///     pos_fb: PinArray<OutputPin<'comp, f64>, 9>,
+/// }
+///
+/// impl<'comp> Resources<'comp> for Pins<'comp> {
+///     type RegisterError = PinRegisterError;
+///
+///     fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+///         Ok(Pins {
+///             pos_cmd: comp.register_pin_array("joint", "pos-cmd")?,
+///             pos_fb: comp.register_pin_array("joint", "pos-fb")?,
+///         })
+///     }
+/// }
+///
+/// let comp = HalComponent::new("axes").unwrap();
+///
+/// let pins: Pins = comp.register().unwrap();
+///
+/// for (cmd, fb) in pins.pos_cmd.iter().zip(pins.pos_fb.iter()) {
+///     fb.set_value(*cmd.value().unwrap()).unwrap();
+/// }
+///
+/// println!("Joint 2 position command: {:?}", pins.pos_cmd[2].value());
+/// ```
+#[derive(Debug)]
+pub struct PinArray<P, const N: usize> {
+    pins: [P; N],
+}
+
+impl<'comp, P, const N: usize> PinArray<P, N>
+where
+    P: HalPin<'comp>,
+{
+    /// Register `N` pins with names created from `full_prefix` and `suffix`
+    ///
+    /// Every name is checked against [`HAL_NAME_LEN`] before any pins are registered, so a name
+    /// that is too long won't leave a partially registered array in the HAL.
+    pub(crate) fn register(
+        full_prefix: &str,
+        suffix: &str,
+        comp: &RegisterResources<'comp>,
+    ) -> Result<Self, PinRegisterError> {
+        let names = (0..N)
+            .map(|index| array_pin_name(full_prefix, index, suffix))
+            .collect::<Vec<_>>();
+
+        if let Some(name) = names.iter().find(|name| name.len() > HAL_NAME_LEN as usize) {
+            error!(
+                "Pin name {} must be no longer than {} bytes",
+                name, HAL_NAME_LEN
+            );
+
+            return Err(PinRegisterError::NameLength(name.clone()));
+        }
+
+        let pins = names
+            .iter()
+            .map(|name| P::register(name, comp))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // NOTE: Unreachable as exactly `N` names are created above
+        let pins = pins
+            .try_into()
+            .unwrap_or_else(|_| unreachable!("Pin array must contain {} pins", N));
+
+        Ok(Self { pins })
+    }
+}
+
+impl<P, const N: usize> PinArray<P, N> {
+    /// Get an iterator over the pins in this array
+    pub fn iter(&self) -> slice::Iter<'_, P> {
+        self.pins.iter()
+    }
+}
+
+impl<P, const N: usize> Deref for PinArray<P, N> {
+    type Target = [P];
+
+    fn deref(&self) -> &Self::Target {
+        &self.pins
+    }
+}
+
+impl<'a, P, const N: usize> IntoIterator for &'a PinArray<P, N> {
+    type Item = &'a P;
+    type IntoIter = slice::Iter<'a, P>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pins.iter()
+    }
+}
+
+impl<P, const N: usize> IntoIterator for PinArray<P, N> {
+    type Item = P;
+    type IntoIter = std::array::IntoIter<P, N>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pins.into_iter()
+    }
+}
+
+/// Create the full name of the pin at `index` in an array
+fn array_pin_name(full_prefix: &str, index: usize, suffix: &str) -> String {
+    if suffix.is_empty() {
+        format!("{}.{}", full_prefix, index)
+    } else {
+        format!("{}.{}.{}", full_prefix, index, suffix)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pin_names() {
+        assert_eq!(
+            array_pin_name("comp.joint", 3, "pos-cmd"),
+            "comp.joint.3.pos-cmd"
+        );
+        assert_eq!(array_pin_name("comp.gpio", 12, ""), "comp.gpio.12");
+    }
+}
@@ -4,7 +4,8 @@ use linuxcnc_hal_sys::{
 };
 
 /// Pin direction
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum PinDirection {
     /// The pin is an input to the component
     In = HAL_IN as isize,
@@ -0,0 +1,267 @@
+//! Multi-instance components
+//!
+//! Many LinuxCNC components can be loaded with `count=N` or `names=a,b,c` to create several
+//! instances of the same set of pins, parameters and functions. Each instance registers its
+//! resources with its own name prefix:
+//!
+//! | Arguments          | Instance prefixes for a component named `foo` |
+//! | ------------------ | --------------------------------------------- |
+//! | None               | `foo.0`                                       |
+//! | `count=3`          | `foo.0`, `foo.1`, `foo.2`                     |
+//! | `names=x,y,z`      | `x`, `y`, `z`                                 |
+//!
+//! Userspace components can parse these from command line arguments with
+//! [`InstanceNames::from_args`] and pass them to
+//! [`HalComponent::register_instances`](crate::HalComponent::register_instances). Realtime
+//! components get them from the `count` and `names` module parameters generated by `rtapi_app!`.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::{
+//!     error::PinRegisterError,
+//!     hal_pin::{InputPin, OutputPin},
+//!     instance::{InstanceNames, Instances},
+//!     prelude::*,
+//!     HalComponent, RegisterResources, Resources,
+//! };
+//! use std::error::Error;
+//!
+//! struct Pins<'comp> {
+//!     input: InputPin<'comp, f64>,
+//!     output: OutputPin<'comp, f64>,
+//! }
+//!
+//! impl<'comp> Resources<'comp> for Pins<'comp> {
+//!     type RegisterError = PinRegisterError;
+//!
+//!     fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+//!         Ok(Pins {
+//!             input: comp.register_pin("in")?,
+//!             output: comp.register_pin("out")?,
+//!         })
+//!     }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!     // `loadusr -W scale names=x,y,z` creates pins `x.in`, `x.out`, `y.in`, etc
+//!     let instances = InstanceNames::from_args(std::env::args().skip(1))?;
+//!
+//!     let comp = HalComponent::new("scale")?;
+//!     let instances: Instances<Pins> = comp.register_instances(instances)?;
+//!
+//!     while !comp.should_exit() {
+//!         for instance in &instances {
+//!             let pins = instance.resources();
+//!
+//!             pins.output.set_value(pins.input.value()? * 2.0)?;
+//!         }
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::InstanceArgsError;
+use std::{ops::Deref, slice};
+
+/// The instances of a component to create
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum InstanceNames {
+    /// Create this many instances, named `<component>.0`, `<component>.1`, etc
+    Count(usize),
+
+    /// Create one instance for each name
+    ///
+    /// The names are used as-is, and are not prefixed with the component name.
+    Names(Vec<String>),
+}
+
+impl Default for InstanceNames {
+    /// A single instance named `<component>.0`
+    fn default() -> Self {
+        Self::Count(1)
+    }
+}
+
+impl InstanceNames {
+    /// Parse `count=N` or `names=a,b,c` from a list of arguments
+    ///
+    /// Returns [`InstanceNames::default`] if neither argument is given.
+    ///
+    /// # Errors
+    ///
+    /// * [`InstanceArgsError::Conflict`] - if both `count` and `names` are given
+    /// * [`InstanceArgsError::Count`] - if the value of `count` is not a positive integer
+    /// * [`InstanceArgsError::Names`] - if `names` is empty or contains an empty name
+    /// * [`InstanceArgsError::Unknown`] - if any other argument is given
+    pub fn from_args<I, S>(args: I) -> Result<Self, InstanceArgsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let mut instances = None;
+
+        for arg in args {
+            let arg = arg.as_ref();
+
+            let parsed = match arg.split_once('=') {
+                Some(("count", count)) => Self::parse_count(count)?,
+                Some(("names", names)) => Self::parse_names(names.split(','))?,
+                _ => return Err(InstanceArgsError::Unknown(arg.to_string())),
+            };
+
+            if instances.replace(parsed).is_some() {
+                return Err(InstanceArgsError::Conflict);
+            }
+        }
+
+        Ok(instances.unwrap_or_default())
+    }
+
+    /// Parse the value of a `count=N` argument
+    pub(crate) fn parse_count(count: &str) -> Result<Self, InstanceArgsError> {
+        match count.trim().parse::<usize>() {
+            Ok(count) if count > 0 => Ok(Self::Count(count)),
+            _ => Err(InstanceArgsError::Count(count.to_string())),
+        }
+    }
+
+    /// Parse the values of a `names=a,b,c` argument
+    pub(crate) fn parse_names<I, S>(names: I) -> Result<Self, InstanceArgsError>
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let names = names
+            .into_iter()
+            .map(|name| name.as_ref().trim().to_string())
+            .collect::<Vec<_>>();
+
+        if names.is_empty() || names.iter().any(|name| name.is_empty()) {
+            return Err(InstanceArgsError::Names);
+        }
+
+        Ok(Self::Names(names))
+    }
+
+    /// Get the resource name prefix of each instance of a component called `component_name`
+    pub fn prefixes(&self, component_name: &str) -> Vec<String> {
+        match self {
+            Self::Count(count) => (0..*count)
+                .map(|index| format!("{}.{}", component_name, index))
+                .collect(),
+            Self::Names(names) => names.clone(),
+        }
+    }
+}
+
+/// A single instance of a multi-instance component
+#[derive(Debug)]
+pub struct Instance<R> {
+    pub(crate) name: String,
+    pub(crate) resources: R,
+}
+
+impl<R> Instance<R> {
+    /// Get the name prefix of this instance, e.g. `foo.0`
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get a reference to this instance's resources
+    pub fn resources(&self) -> &R {
+        &self.resources
+    }
+}
+
+/// The resources of every instance of a multi-instance component
+///
+/// Created by [`HalComponent::register_instances`](crate::HalComponent::register_instances).
+/// Dereferences to a slice of [`Instance`]s in the order they were requested.
+#[derive(Debug)]
+pub struct Instances<R> {
+    pub(crate) instances: Vec<Instance<R>>,
+}
+
+impl<R> Instances<R> {
+    /// Get an iterator over every instance
+    pub fn iter(&self) -> slice::Iter<'_, Instance<R>> {
+        self.instances.iter()
+    }
+
+    /// Get an instance by name
+    pub fn get_by_name(&self, name: &str) -> Option<&Instance<R>> {
+        self.instances.iter().find(|instance| instance.name == name)
+    }
+}
+
+impl<R> Deref for Instances<R> {
+    type Target = [Instance<R>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.instances
+    }
+}
+
+impl<'a, R> IntoIterator for &'a Instances<R> {
+    type Item = &'a Instance<R>;
+    type IntoIter = slice::Iter<'a, Instance<R>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.instances.iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_args() {
+        assert_eq!(
+            InstanceNames::from_args(Vec::<String>::new()),
+            Ok(InstanceNames::Count(1))
+        );
+        assert_eq!(
+            InstanceNames::from_args(["count=3"]),
+            Ok(InstanceNames::Count(3))
+        );
+        assert_eq!(
+            InstanceNames::from_args(["names=x,y,z"]),
+            Ok(InstanceNames::Names(vec![
+                "x".to_string(),
+                "y".to_string(),
+                "z".to_string()
+            ]))
+        );
+        assert_eq!(
+            InstanceNames::from_args(["count=2", "names=x"]),
+            Err(InstanceArgsError::Conflict)
+        );
+        assert_eq!(
+            InstanceNames::from_args(["count=0"]),
+            Err(InstanceArgsError::Count("0".to_string()))
+        );
+        assert_eq!(
+            InstanceNames::from_args(["names=x,,z"]),
+            Err(InstanceArgsError::Names)
+        );
+        assert_eq!(
+            InstanceNames::from_args(["debug=1"]),
+            Err(InstanceArgsError::Unknown("debug=1".to_string()))
+        );
+    }
+
+    #[test]
+    fn prefixes() {
+        assert_eq!(
+            InstanceNames::Count(2).prefixes("foo"),
+            vec!["foo.0".to_string(), "foo.1".to_string()]
+        );
+        assert_eq!(
+            InstanceNames::Names(vec!["x".to_string()]).prefixes("foo"),
+            vec!["x".to_string()]
+        );
+    }
+}
@@ -14,20 +14,19 @@
 //!
 //! # Project setup
 //!
-//! This crate depends on the `linuxcnc-hal-sys` crate which requires the `LINUXCNC_SRC` environment
-//! variable to be set to correctly generate the C bindings. The value must be the absolute path to
-//! the root of the LinuxCNC source code.
+//! This crate depends on the `linuxcnc-hal-sys` crate, which ships pre-generated bindings for
+//! LinuxCNC 2.9 and finds `liblinuxcnchal` through `LINUXCNC_LIB_DIR`, `LINUXCNC_SRC`, pkg-config or
+//! common system library directories. See the `linuxcnc-hal-sys` documentation for the available
+//! LinuxCNC versions and how to generate bindings for other versions with its `bindgen` feature.
 //!
-//! **The version of the LinuxCNC sources must match the LinuxCNC version used in the machine
-//! control.**
+//! **The version of the bindings must match the LinuxCNC version used in the machine control.**
 //!
-//! ```bash
-//! # Clone LinuxCNC source code into linuxcnc/
-//! git clone https://github.com/LinuxCNC/linuxcnc.git
-//!
-//! # Check out a specific version tag. This may also be a commit, but must match the version in use by the machine control.
-//! cd linuxcnc && git checkout v2.8.0 && cd ..
+//! The `linuxcnc-2-9` feature is enabled by default. As well as selecting the LinuxCNC 2.9
+//! bindings, it enables the `snapshot` module and the `query` functions that read HAL shared
+//! memory, as its layout is only known for LinuxCNC 2.9. Disable the default features to build
+//! against bindings for another version generated with `linuxcnc-hal-sys`'s `bindgen` feature.
 //!
+//! ```bash
 //! # Create your component lib
 //! cargo new --lib my_comp
 //!
@@ -36,12 +35,46 @@
 //! # Add LinuxCNC HAL bindings as a Cargo dependency with cargo-edit
 //! cargo add linuxcnc-hal
 //!
+//! # Against a packaged LinuxCNC install
+//! cargo build
+//!
+//! # Against a LinuxCNC source tree built to run in place
 //! LINUXCNC_SRC=/path/to/linuxcnc/source/code cargo build
 //! ```
 //!
 //! If LinuxCNC is configured to run in place, `liblinuxcnchal.so.0` may not be found on startup. To
 //! fix, try setting the library path with e.g. `export LD_LIBRARY_PATH=~/Repositories/linuxcnc/lib`
 //!
+//! Alternatively, enable the `dlopen` feature to load `liblinuxcnchal` at runtime instead of linking
+//! against it. One binary then runs on both run-in-place and packaged LinuxCNC installs, and HAL
+//! functions missing from older LinuxCNC versions are reported as `Unsupported` errors. If the
+//! library can't be found, `HalComponent::new` returns `ComponentInitError::Library`.
+//!
+//! # Build modes
+//!
+//! Components loaded with `loadusr` run in their own process, while components loaded with `loadrt`
+//! run inside LinuxCNC's realtime process. Enable the `ulapi` or `rtapi` feature to select the
+//! matching bindings and turn misuse of the other mode's APIs into a compile error:
+//!
+//! | Feature | Component | Removed APIs                                              |
+//! | ------- | --------- | --------------------------------------------------------- |
+//! | `ulapi` | `loadusr` | `rtapi_app!`, exported functions and `thread::HalThread`  |
+//! | `rtapi` | `loadrt`  | Unix signal handling and `HalComponent::should_exit`      |
+//!
+//! If neither feature is enabled, every API is available, as in previous versions. The features are
+//! mutually exclusive.
+//!
+//! # Testing
+//!
+//! Enable the `mock` feature in `dev-dependencies` to replace the HAL with an in-process mock, so
+//! components can be tested with `cargo test` without LinuxCNC installed. See the `mock` module
+//! for details.
+//!
+//! ```toml
+//! [dev-dependencies]
+//! linuxcnc-hal = { version = "*", features = [ "mock" ] }
+//! ```
+//!
 //! # Examples
 //!
 //! ## Create a component with input and output
@@ -59,8 +92,10 @@
 //! ```
 //!
 //! Pins and other resources are registered using the [`Resources`] trait. This example creates a
-//! `Pins` struct which holds the two pins. [`HalComponent::new`] handles component creation,
-//! resources (pin, signal, etc) initialisation and UNIX signal handler registration.
+//! `Pins` struct which holds the two pins. [`HalComponent::new`] handles component creation and
+//! UNIX signal handler registration, then [`HalComponent::register`] initialises the resources (pin,
+//! signal, etc) and marks the component as ready. Resources borrow the component, so they can't be
+//! used after it has been dropped.
 //!
 //! ```rust,no_run
 //! use linuxcnc_hal::{
@@ -75,15 +110,15 @@
 //!     time::{Duration, Instant},
 //! };
 //!
-//! struct Pins {
-//!     input_1: InputPin<f64>,
-//!     output_1: OutputPin<f64>,
+//! struct Pins<'comp> {
+//!     input_1: InputPin<'comp, f64>,
+//!     output_1: OutputPin<'comp, f64>,
 //! }
 //!
-//! impl Resources for Pins {
+//! impl<'comp> Resources<'comp> for Pins<'comp> {
 //!     type RegisterError = PinRegisterError;
 //!
-//!     fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError> {
+//!     fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
 //!         Ok(Pins {
 //!             input_1: comp.register_pin::<InputPin<f64>>("input-1")?,
 //!             output_1: comp.register_pin::<OutputPin<f64>>("output-1")?,
@@ -95,10 +130,10 @@
 //!     rtapi_logger::init();
 //!
 //!     // Create a new HAL component called `rust-comp`
-//!     let comp: HalComponent<Pins> = HalComponent::new("rust-comp")?;
+//!     let comp = HalComponent::new("rust-comp")?;
 //!
-//!     // Get a reference to the `Pins` struct
-//!     let pins = comp.resources();
+//!     // Register the pins in the `Pins` struct and mark the component as ready
+//!     let pins: Pins = comp.register()?;
 //!
 //!     let start = Instant::now();
 //!
@@ -118,7 +153,8 @@
 //!     }
 //!
 //!     // The custom implementation of `Drop` for `HalComponent` ensures that `hal_exit()` is called
-//!     // at this point. Registered signal handlers are also deregistered.
+//!     // at this point, after the pins have been dropped. Registered signal handlers are also
+//!     // deregistered.
 //!
 //!     Ok(())
 //! }
@@ -132,55 +168,135 @@ extern crate log;
 
 mod component;
 pub mod error;
+mod ffi;
+#[cfg(not(feature = "ulapi"))]
+pub mod hal_function;
 mod hal_parameter;
 pub mod hal_pin;
+pub mod instance;
+#[cfg(feature = "mock")]
+pub mod mock;
 pub mod prelude;
+pub mod query;
+#[cfg(not(feature = "ulapi"))]
+pub mod rtapi;
+pub mod signal;
+#[cfg(feature = "linuxcnc-2-9")]
+pub mod snapshot;
+pub mod stream;
+pub mod thread;
 
-use hal_parameter::ParameterPermissions;
+#[cfg(not(feature = "ulapi"))]
+use std::time::Duration;
 
 pub use crate::component::HalComponent;
-pub use crate::hal_parameter::Parameter;
+pub use crate::hal_parameter::{Parameter, ParameterPermissions};
+#[cfg(not(feature = "ulapi"))]
+use crate::{
+    error::FunctionExportError,
+    hal_function::{FunctionOptions, HalFunction},
+};
 use crate::{
-    error::{ParameterRegisterError, PinRegisterError, ResourcesError},
+    error::{ParameterRegisterError, PinRegisterError, ResourcesError, StreamError},
     hal_parameter::HalParameter,
-    hal_pin::HalPin,
+    hal_pin::{HalPin, PinArray},
+    stream::HalStream,
 };
+#[cfg(feature = "derive")]
+pub use linuxcnc_hal_derive::Resources;
 
 /// Resources for a component
-pub trait Resources: Sized {
+///
+/// Resources borrow the component they are registered with for `'comp`, so they can't be used
+/// after the component has exited. Types that hold pins, parameters or other resources need a
+/// lifetime parameter for this, e.g. `InputPin<'comp, f64>`.
+pub trait Resources<'comp>: Sized {
     /// The type of error to return if a resource registration failed
     ///
-    /// This must be convertable into a [`ResourcesError`].
+    /// This must be convertable into a [`ResourcesError`]. Use [`ResourcesError`] itself to
+    /// register pins, ports, parameters, signals and other resources in the same struct, as every
+    /// registration error converts into it with `?`.
     type RegisterError: Into<ResourcesError>;
 
     /// Register resources against a component
-    fn register_resources(comp: &RegisterResources) -> Result<Self, Self::RegisterError>;
+    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError>;
 }
 
 /// Component metadata used when registering resources
-pub struct RegisterResources {
-    /// Component name
-    name: &'static str,
+pub struct RegisterResources<'comp> {
+    /// Resource name prefix, either the component name or an instance name
+    name: String,
 
-    /// Component ID
-    id: i32,
+    /// The component every resource is registered with
+    comp: &'comp HalComponent,
 }
 
-impl RegisterResources {
+impl<'comp> RegisterResources<'comp> {
+    pub(crate) fn new(comp: &'comp HalComponent, name: String) -> Self {
+        Self { name, comp }
+    }
+
+    /// Get the component resources are registered with
+    ///
+    /// Signals and threads can be created with it alongside pins and parameters.
+    pub fn component(&self) -> &'comp HalComponent {
+        self.comp
+    }
+
     /// Register a pin with this component.
     ///
     /// The pin name will be prefixed with the component name
-    pub fn register_pin<P>(&self, pin_name: &'static str) -> Result<P, PinRegisterError>
+    pub fn register_pin<P>(&self, pin_name: impl AsRef<str>) -> Result<P, PinRegisterError>
+    where
+        P: HalPin<'comp>,
+    {
+        let full_name = format!("{}.{}", self.name, pin_name.as_ref());
+
+        let pin = P::register(&full_name, self)?;
+
+        Ok(pin)
+    }
+
+    /// Register a pin with this component and give it an alias.
+    ///
+    /// The pin name will be prefixed with the component name, but the alias is used as-is. For
+    /// example, a component named `hm2` could register `gpio.012.in` with the alias
+    /// `x-axis.home-sw`. See [`HalPin::set_alias`].
+    pub fn register_pin_with_alias<P>(
+        &self,
+        pin_name: impl AsRef<str>,
+        alias: impl AsRef<str>,
+    ) -> Result<P, PinRegisterError>
     where
-        P: HalPin,
+        P: HalPin<'comp>,
     {
-        let full_name = format!("{}.{}", self.name, pin_name);
+        let pin = self.register_pin::<P>(pin_name)?;
 
-        let pin = P::register(&full_name, self.id)?;
+        pin.set_alias(alias.as_ref())?;
 
         Ok(pin)
     }
 
+    /// Register an array of `N` pins with this component.
+    ///
+    /// Pins are named `<component>.<prefix>.<index>.<suffix>`, e.g. `comp.joint.0.pos-cmd`. If
+    /// `suffix` is empty, pins are named `<component>.<prefix>.<index>`. Every name is checked
+    /// against [`HAL_NAME_LEN`](linuxcnc_hal_sys::HAL_NAME_LEN) before any pin is registered.
+    ///
+    /// See [`PinArray`] for an example.
+    pub fn register_pin_array<P, const N: usize>(
+        &self,
+        prefix: impl AsRef<str>,
+        suffix: impl AsRef<str>,
+    ) -> Result<PinArray<P, N>, PinRegisterError>
+    where
+        P: HalPin<'comp>,
+    {
+        let full_prefix = format!("{}.{}", self.name, prefix.as_ref());
+
+        PinArray::register(&full_prefix, suffix.as_ref(), self)
+    }
+
     /// Register a read/write parameter with this component.
     ///
     /// The parameter name will be prefixed with the component name.
@@ -188,14 +304,14 @@ impl RegisterResources {
     /// To register a pin that LinuxCNC cannot write to, call [`RegisterResources::register_readonly_parameter`].
     pub fn register_parameter<P>(
         &self,
-        parameter_name: &'static str,
+        parameter_name: impl AsRef<str>,
     ) -> Result<P, ParameterRegisterError>
     where
-        P: HalParameter,
+        P: HalParameter<'comp>,
     {
-        let full_name = format!("{}.{}", self.name, parameter_name);
+        let full_name = format!("{}.{}", self.name, parameter_name.as_ref());
 
-        let parameter = P::register(&full_name, self.id, ParameterPermissions::ReadWrite)?;
+        let parameter = P::register(&full_name, self, ParameterPermissions::ReadWrite)?;
 
         Ok(parameter)
     }
@@ -205,15 +321,209 @@ impl RegisterResources {
     /// The parameter name will be prefixed with the component name
     pub fn register_readonly_parameter<P>(
         &self,
-        parameter_name: &'static str,
+        parameter_name: impl AsRef<str>,
+    ) -> Result<P, ParameterRegisterError>
+    where
+        P: HalParameter<'comp>,
+    {
+        let full_name = format!("{}.{}", self.name, parameter_name.as_ref());
+
+        let parameter = P::register(&full_name, self, ParameterPermissions::ReadOnly)?;
+
+        Ok(parameter)
+    }
+
+    /// Register a read/write parameter with this component and give it an alias.
+    ///
+    /// The parameter name will be prefixed with the component name, but the alias is used as-is.
+    /// See [`Parameter::set_alias`].
+    pub fn register_parameter_with_alias<P>(
+        &self,
+        parameter_name: impl AsRef<str>,
+        alias: impl AsRef<str>,
     ) -> Result<P, ParameterRegisterError>
     where
-        P: HalParameter,
+        P: HalParameter<'comp>,
     {
-        let full_name = format!("{}.{}", self.name, parameter_name,);
+        let parameter = self.register_parameter::<P>(parameter_name)?;
 
-        let parameter = P::register(&full_name, self.id, ParameterPermissions::ReadOnly)?;
+        hal_parameter::alias_parameter(parameter.name(), Some(alias.as_ref()))?;
 
         Ok(parameter)
     }
+
+    /// Register a read only parameter with this component and give it an alias.
+    ///
+    /// The parameter name will be prefixed with the component name, but the alias is used as-is.
+    /// See [`Parameter::set_alias`].
+    pub fn register_readonly_parameter_with_alias<P>(
+        &self,
+        parameter_name: impl AsRef<str>,
+        alias: impl AsRef<str>,
+    ) -> Result<P, ParameterRegisterError>
+    where
+        P: HalParameter<'comp>,
+    {
+        let parameter = self.register_readonly_parameter::<P>(parameter_name)?;
+
+        hal_parameter::alias_parameter(parameter.name(), Some(alias.as_ref()))?;
+
+        Ok(parameter)
+    }
+
+    /// Create a new HAL stream owned by this component.
+    ///
+    /// `key` is the shared memory key other components use to attach to the stream, and `depth` is
+    /// the maximum number of samples the stream can hold. The stream is destroyed when the returned
+    /// [`StreamWriter`](stream::StreamWriter) or [`StreamReader`](stream::StreamReader) is dropped.
+    pub fn create_stream<S>(&self, key: i32, depth: usize) -> Result<S, StreamError>
+    where
+        S: HalStream<'comp>,
+    {
+        S::create(key, depth, self.comp)
+    }
+
+    /// Attach to a HAL stream created by another component.
+    ///
+    /// Returns [`StreamError::Hal`] with [`HalErrno::Invalid`](error::HalErrno::Invalid) if the
+    /// stream's type string does not match the sample type.
+    pub fn attach_stream<S>(&self, key: i32) -> Result<S, StreamError>
+    where
+        S: HalStream<'comp>,
+    {
+        S::attach(key, self.comp)
+    }
+}
+
+/// Realtime functions
+///
+/// Exported functions are called by LinuxCNC until the component exits, so they can only be
+/// exported by a component that lives for the rest of the program, such as one created by
+/// [`rtapi_app!`]. This makes `'comp` `'static`, so registered pins and parameters can be moved
+/// into the function's state.
+#[cfg(not(feature = "ulapi"))]
+impl RegisterResources<'static> {
+    /// Export a function that can be added to a realtime HAL thread.
+    ///
+    /// The function name will be prefixed with the component name. `state` is moved into HAL shared
+    /// memory and passed to `function` on every call, along with the period of the thread the
+    /// function is called from. Pins and parameters can be registered first and moved into
+    /// `state` for use in the function.
+    ///
+    /// The exported function is not reentrant; HAL will never call it again before the previous
+    /// call has returned. To export a reentrant function, use
+    /// [`RegisterResources::export_reentrant_function`].
+    ///
+    /// Only realtime components loaded with `loadrt` can export functions, so this method is not
+    /// available with the `ulapi` feature. Panics in `function` are caught and logged.
+    ///
+    /// The state is never dropped, even after the component exits, so its `Drop` implementation
+    /// never runs and any heap memory it owns is leaked. HAL shared memory is only freed once every
+    /// component has exited, and a realtime thread may still be running the function while
+    /// [`hal_exit`](linuxcnc_hal_sys::hal_exit) removes it, so there is no point at which dropping
+    /// the state would be sound. Keep the state to pins, parameters and plain values.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use linuxcnc_hal::{
+    ///     error::ResourcesError,
+    ///     hal_function::{FunctionOptions, HalFunction},
+    ///     hal_pin::{InputPin, OutputPin},
+    ///     prelude::*,
+    ///     RegisterResources, Resources,
+    /// };
+    ///
+    /// struct Integrator {
+    ///     input: InputPin<'static, f64>,
+    ///     output: OutputPin<'static, f64>,
+    ///     total: f64,
+    /// }
+    ///
+    /// struct Comp {
+    ///     update: HalFunction,
+    /// }
+    ///
+    /// impl Resources<'static> for Comp {
+    ///     type RegisterError = ResourcesError;
+    ///
+    ///     fn register_resources(
+    ///         comp: &RegisterResources<'static>,
+    ///     ) -> Result<Self, Self::RegisterError> {
+    ///         let state = Integrator {
+    ///             input: comp.register_pin("in")?,
+    ///             output: comp.register_pin("out")?,
+    ///             total: 0.0,
+    ///         };
+    ///
+    ///         // Call with `addf <comp name>.update servo-thread`
+    ///         let update = comp.export_function(
+    ///             "update",
+    ///             FunctionOptions::default(),
+    ///             state,
+    ///             |state, period| {
+    ///                 let input = state.input.value().copied().unwrap_or_default();
+    ///
+    ///                 state.total += input * period.as_secs_f64();
+    ///
+    ///                 state.output.set_value(state.total).ok();
+    ///             },
+    ///         )?;
+    ///
+    ///         Ok(Comp { update })
+    ///     }
+    /// }
+    /// ```
+    pub fn export_function<S, F>(
+        &self,
+        function_name: impl AsRef<str>,
+        options: FunctionOptions,
+        state: S,
+        function: F,
+    ) -> Result<HalFunction, FunctionExportError>
+    where
+        S: Send + 'static,
+        F: FnMut(&mut S, Duration) + Send + 'static,
+    {
+        let full_name = format!("{}.{}", self.name, function_name.as_ref());
+
+        hal_function::export(
+            &full_name,
+            options,
+            false,
+            state,
+            function,
+            hal_function::call_function::<S, F>,
+            self.comp.id(),
+        )
+    }
+
+    /// Export a reentrant function that can be added to a realtime HAL thread.
+    ///
+    /// This is the same as [`RegisterResources::export_function`], however HAL may call the
+    /// function again before a previous call has returned, so `function` only gets a shared
+    /// reference to `state`.
+    pub fn export_reentrant_function<S, F>(
+        &self,
+        function_name: impl AsRef<str>,
+        options: FunctionOptions,
+        state: S,
+        function: F,
+    ) -> Result<HalFunction, FunctionExportError>
+    where
+        S: Send + Sync + 'static,
+        F: Fn(&S, Duration) + Send + Sync + 'static,
+    {
+        let full_name = format!("{}.{}", self.name, function_name.as_ref());
+
+        hal_function::export(
+            &full_name,
+            options,
+            true,
+            state,
+            function,
+            hal_function::call_reentrant_function::<S, F>,
+            self.comp.id(),
+        )
+    }
 }
@@ -0,0 +1,1678 @@
+//! In-process mock HAL for unit tests
+//!
+//! With the `mock` feature enabled, every HAL function used by this crate is replaced with an
+//! in-process Rust implementation, so components can be tested with `cargo test` on a machine
+//! without LinuxCNC installed. `liblinuxcnchal` is not linked.
+//!
+//! The mock HAL is local to each thread. Tests run on their own threads by default, so each test
+//! gets its own empty HAL and tests can run in parallel.
+//!
+//! Components, pins, parameters, signals, aliases, exported functions and threads behave like
+//! they do in LinuxCNC, including most error cases. Pin and parameter values can be read with the
+//! [`query`](crate::query) module. The functions in this module drive the component:
+//!
+//! | Function          | Description                                              |
+//! | ----------------- | -------------------------------------------------------- |
+//! | [`set_pin`]       | Set the value of a pin, e.g. to drive an input pin       |
+//! | [`call_function`] | Call an exported function once                           |
+//! | [`run_thread`]    | Call every function in a thread once, in order           |
+//! | [`is_ready`]      | Check whether a component has been marked as ready       |
+//! | [`reset`]         | Remove everything from this thread's HAL                 |
+//!
+//! Some parts of the HAL are not supported:
+//!
+//! * Ports can be registered, but are always empty and never writable
+//! * Streams cannot be created or attached to
+//! * HAL shared memory, used by [`HalSnapshot::capture`](crate::snapshot::HalSnapshot::capture),
+//!   is a copy of the mock HAL that is rebuilt every time it is read. Components are always
+//!   reported as userspace components of the current process.
+//! * Only the RTAPI message functions used by `rtapi-logger` are available. Messages are printed
+//!   to stderr, and the RTAPI message handler cannot be replaced.
+//!
+//! # Examples
+//!
+//! ```rust
+//! use linuxcnc_hal::{
+//!     error::ResourcesError,
+//!     hal_function::{FunctionOptions, HalFunction},
+//!     hal_pin::{InputPin, OutputPin},
+//!     mock,
+//!     prelude::*,
+//!     query::{self, HalValue},
+//!     HalComponent, RegisterResources, Resources,
+//! };
+//! use std::time::Duration;
+//!
+//! struct Doubler {
+//!     double: HalFunction,
+//! }
+//!
+//! impl Resources<'static> for Doubler {
+//!     type RegisterError = ResourcesError;
+//!
+//!     fn register_resources(comp: &RegisterResources<'static>) -> Result<Self, Self::RegisterError> {
+//!         let input: InputPin<f64> = comp.register_pin("in")?;
+//!         let output: OutputPin<f64> = comp.register_pin("out")?;
+//!
+//!         let double = comp.export_function(
+//!             "double",
+//!             FunctionOptions::default(),
+//!             (input, output),
+//!             |(input, output), _period| {
+//!                 output.set_value(input.value().unwrap() * 2.0).unwrap();
+//!             },
+//!         )?;
+//!
+//!         Ok(Self { double })
+//!     }
+//! }
+//!
+//! // Exporting functions requires a component that lives for `'static`
+//! let comp: &'static HalComponent = Box::leak(Box::new(HalComponent::new("doubler").unwrap()));
+//! let _doubler: Doubler = comp.register().unwrap();
+//!
+//! mock::set_pin("doubler.in", HalValue::Float(21.0)).unwrap();
+//! mock::call_function("doubler.double", Duration::from_millis(1)).unwrap();
+//!
+//! assert_eq!(query::pin("doubler.out").unwrap().value, HalValue::Float(42.0));
+//! ```
+
+use crate::{error::QueryError, query::HalValue};
+use linuxcnc_hal_sys::{
+    hal_comp_t, hal_data_t, hal_data_u, hal_funct_entry_t, hal_funct_t, hal_list_t, hal_oldname_t,
+    hal_param_t, hal_pin_t, hal_sig_t, hal_thread_t, hal_type_t, HAL_NAME_LEN,
+};
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    ffi::{c_void, CStr},
+    mem,
+    os::raw::{c_char, c_int, c_long},
+    process, ptr,
+    time::Duration,
+};
+
+/// Set the value of a pin by its full name
+///
+/// If the pin is linked to a signal, the value of the signal is set.
+///
+/// # Errors
+///
+/// * [`QueryError::NotFound`] - if no pin with this name exists
+/// * [`QueryError::TypeMismatch`] - if the value type does not match the pin type
+/// * [`QueryError::Port`] - if the pin is a port
+pub fn set_pin(name: &str, value: HalValue) -> Result<(), QueryError> {
+    with_hal(|hal| {
+        let pin = hal
+            .pin(name)
+            .ok_or_else(|| QueryError::NotFound(name.to_string()))?;
+
+        if value.hal_type().as_raw() != pin.ty {
+            return Err(QueryError::TypeMismatch(name.to_string()));
+        }
+
+        // SAFETY: The pin's data pointer always points to either its own dummy value or the value
+        // of the signal it is linked to, both of which have the pin's type.
+        unsafe { value.write(*pin.data_ptr_addr) }
+    })
+}
+
+/// Call an exported function once by its full name
+///
+/// # Errors
+///
+/// * [`QueryError::NotFound`] - if no function with this name exists
+pub fn call_function(name: &str, period: Duration) -> Result<(), QueryError> {
+    let function = with_hal(|hal| {
+        hal.functions
+            .iter()
+            .find(|f| f.name == name)
+            .map(|f| (f.funct, f.arg))
+            .ok_or_else(|| QueryError::NotFound(name.to_string()))
+    })?;
+
+    // The HAL must not be borrowed while the function runs, as it may use the HAL itself
+    call(function, period);
+
+    Ok(())
+}
+
+/// Call every function in a thread once, in the order they were added
+///
+/// Each function is passed the period of the thread.
+///
+/// # Errors
+///
+/// * [`QueryError::NotFound`] - if no thread with this name exists
+pub fn run_thread(name: &str) -> Result<(), QueryError> {
+    let (functions, period) = with_hal(|hal| {
+        let thread = hal
+            .threads
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| QueryError::NotFound(name.to_string()))?;
+
+        let functions = thread
+            .functions
+            .iter()
+            .filter_map(|name| hal.functions.iter().find(|f| &f.name == name))
+            .map(|f| (f.funct, f.arg))
+            .collect::<Vec<_>>();
+
+        Ok::<_, QueryError>((functions, thread.period))
+    })?;
+
+    for function in functions {
+        call(function, period);
+    }
+
+    Ok(())
+}
+
+/// Whether the component with the given name has been marked as ready
+pub fn is_ready(component: &str) -> bool {
+    with_hal(|hal| {
+        hal.components
+            .iter()
+            .any(|c| c.name == component && c.ready)
+    })
+}
+
+/// Remove every component, pin, parameter, signal, function and thread from this thread's HAL
+///
+/// Shared memory is not freed until the thread exits, so resources that are still alive remain
+/// valid but are no longer known to the HAL.
+pub fn reset() {
+    with_hal(|hal| {
+        let memory = mem::take(&mut hal.memory);
+        let values = mem::take(&mut hal.values);
+
+        *hal = Hal {
+            memory,
+            values,
+            ..Hal::default()
+        };
+    })
+}
+
+type Funct = unsafe extern "C" fn(*mut c_void, c_long);
+
+fn call((funct, arg): (Funct, *mut c_void), period: Duration) {
+    unsafe { funct(arg, period.as_nanos() as c_long) }
+}
+
+thread_local! {
+    static HAL: RefCell<Hal> = RefCell::new(Hal::default());
+}
+
+fn with_hal<T>(f: impl FnOnce(&mut Hal) -> T) -> T {
+    HAL.with(|hal| f(&mut hal.borrow_mut()))
+}
+
+/// The state of one thread's mock HAL
+#[derive(Default)]
+struct Hal {
+    /// Whether a component has been created, so HAL shared memory exists
+    initialised: bool,
+    next_id: i32,
+    components: Vec<Component>,
+    pins: Vec<Pin>,
+    parameters: Vec<Parameter>,
+    signals: Vec<Signal>,
+    functions: Vec<Function>,
+    threads: Vec<Thread>,
+
+    /// Blocks allocated with `hal_malloc`
+    memory: Vec<Box<[u128]>>,
+
+    /// Pin dummy values and signal values
+    ///
+    /// Each value is boxed so pointers to it stay valid when the `Vec` grows.
+    #[allow(clippy::vec_box)]
+    values: Vec<Box<hal_data_u>>,
+}
+
+impl Hal {
+    /// Allocate a zeroed value that lives until the thread exits
+    fn alloc_value(&mut self) -> *mut hal_data_u {
+        // SAFETY: Every field of the union is valid when zeroed
+        let mut value = Box::new(unsafe { mem::zeroed::<hal_data_u>() });
+
+        let ptr: *mut hal_data_u = &mut *value;
+
+        self.values.push(value);
+
+        ptr
+    }
+
+    fn next_id(&mut self) -> i32 {
+        self.next_id += 1;
+
+        self.next_id
+    }
+
+    fn component(&self, id: i32) -> Option<&Component> {
+        self.components.iter().find(|c| c.id == id)
+    }
+
+    fn pin(&self, name: &str) -> Option<&Pin> {
+        self.pins
+            .iter()
+            .find(|p| p.name == name || p.alias.as_deref() == Some(name))
+    }
+
+    fn parameter(&self, name: &str) -> Option<&Parameter> {
+        self.parameters
+            .iter()
+            .find(|p| p.name == name || p.alias.as_deref() == Some(name))
+    }
+
+    fn signal(&self, name: &str) -> Option<&Signal> {
+        self.signals.iter().find(|s| s.name == name)
+    }
+
+    /// Whether a pin or parameter can be created with this name, or given this alias
+    fn name_available(&self, name: &str) -> bool {
+        self.pin(name).is_none() && self.parameter(name).is_none()
+    }
+
+    /// Build an image of HAL shared memory with `hal_data_t` at offset 0, like LinuxCNC
+    ///
+    /// Signal and parameter values are copied into the image, as offsets can't point outside it.
+    fn shmem_image(&self) -> Box<[u128]> {
+        let entries = self
+            .threads
+            .iter()
+            .map(|t| t.functions.len())
+            .sum::<usize>();
+        let aliases = self.pins.iter().filter(|p| p.alias.is_some()).count()
+            + self.parameters.iter().filter(|p| p.alias.is_some()).count();
+
+        let mut image = ShmemImage::new(
+            block_size::<hal_data_t>()
+                + self.components.len() * block_size::<hal_comp_t>()
+                + self.pins.len() * block_size::<hal_pin_t>()
+                + self.signals.len() * (block_size::<hal_sig_t>() + block_size::<hal_data_u>())
+                + self.parameters.len()
+                    * (block_size::<hal_param_t>() + block_size::<hal_data_u>())
+                + self.functions.len() * block_size::<hal_funct_t>()
+                + self.threads.len() * block_size::<hal_thread_t>()
+                + entries * block_size::<hal_funct_entry_t>()
+                + aliases * block_size::<hal_oldname_t>(),
+        );
+
+        let data = image.alloc::<hal_data_t>();
+
+        // SAFETY: Every block returned by `alloc` is zeroed, aligned and inside the image. Each list
+        // is built back to front so every entry can point to the next one.
+        unsafe {
+            let data = image.get::<hal_data_t>(data);
+            let mut components = HashMap::new();
+            let mut next = 0;
+
+            for comp in self.components.iter().rev() {
+                let offset = image.alloc::<hal_comp_t>();
+                let entry = image.get::<hal_comp_t>(offset);
+
+                (*entry).next_ptr = next;
+                (*entry).comp_id = comp.id;
+                (*entry).ready = comp.ready.into();
+                (*entry).pid = process::id() as c_int;
+                copy_name(&mut (*entry).name, &comp.name);
+
+                components.insert(comp.id, offset);
+                next = offset;
+            }
+
+            (*data).comp_list_ptr = next;
+
+            let mut signals = HashMap::new();
+            next = 0;
+
+            for signal in self.signals.iter().rev() {
+                let offset = image.alloc::<hal_sig_t>();
+                let value = image.alloc::<hal_data_u>();
+                let entry = image.get::<hal_sig_t>(offset);
+
+                *image.get::<hal_data_u>(value) = *signal.value;
+
+                (*entry).next_ptr = next;
+                (*entry).data_ptr = value;
+                (*entry).type_ = signal.ty;
+                (*entry).readers = signal.readers as c_int;
+                (*entry).writers = signal.writers as c_int;
+                (*entry).bidirs = signal.bidirs as c_int;
+                copy_name(&mut (*entry).name, &signal.name);
+
+                signals.insert(signal.name.as_str(), offset);
+                next = offset;
+            }
+
+            (*data).sig_list_ptr = next;
+            next = 0;
+
+            for pin in self.pins.iter().rev() {
+                let offset = image.alloc::<hal_pin_t>();
+                let entry = image.get::<hal_pin_t>(offset);
+
+                (*entry).next_ptr = next;
+                (*entry).owner_ptr = components[&pin.owner];
+                (*entry).signal = pin.signal.as_deref().map_or(0, |name| signals[name]);
+                (*entry).dummysig = *pin.dummy;
+                (*entry).type_ = pin.ty;
+                (*entry).dir = pin.dir;
+                (*entry).oldname = image.old_name(&pin.name, pin.alias.as_deref());
+                copy_name(&mut (*entry).name, pin.alias.as_ref().unwrap_or(&pin.name));
+
+                next = offset;
+            }
+
+            (*data).pin_list_ptr = next;
+            next = 0;
+
+            for param in self.parameters.iter().rev() {
+                let offset = image.alloc::<hal_param_t>();
+                let value = image.alloc::<hal_data_u>();
+                let entry = image.get::<hal_param_t>(offset);
+
+                *image.get::<hal_data_u>(value) = *param.data_addr;
+
+                (*entry).next_ptr = next;
+                (*entry).data_ptr = value;
+                (*entry).owner_ptr = components[&param.owner];
+                (*entry).type_ = param.ty;
+                (*entry).dir = param.dir;
+                (*entry).oldname = image.old_name(&param.name, param.alias.as_deref());
+                copy_name(
+                    &mut (*entry).name,
+                    param.alias.as_ref().unwrap_or(&param.name),
+                );
+
+                next = offset;
+            }
+
+            (*data).param_list_ptr = next;
+
+            let mut functions = HashMap::new();
+            next = 0;
+
+            for function in self.functions.iter().rev() {
+                let offset = image.alloc::<hal_funct_t>();
+                let entry = image.get::<hal_funct_t>(offset);
+
+                (*entry).next_ptr = next;
+                (*entry).uses_fp = function.uses_fp.into();
+                (*entry).owner_ptr = components[&function.owner];
+                (*entry).reentrant = function.reentrant.into();
+                (*entry).users = function.users as c_int;
+                (*entry).arg = function.arg;
+                (*entry).funct = Some(function.funct);
+                copy_name(&mut (*entry).name, &function.name);
+
+                functions.insert(function.name.as_str(), offset);
+                next = offset;
+            }
+
+            (*data).funct_list_ptr = next;
+            next = 0;
+
+            for thread in self.threads.iter().rev() {
+                let offset = image.alloc::<hal_thread_t>();
+                let entry = image.get::<hal_thread_t>(offset);
+
+                (*entry).next_ptr = next;
+                (*entry).uses_fp = thread.uses_fp.into();
+                (*entry).period = thread.period.as_nanos() as c_long;
+                copy_name(&mut (*entry).name, &thread.name);
+
+                // The function list is circular, starting and ending at the list in the thread
+                let head = image.offset(ptr::addr_of!((*entry).funct_list));
+                let mut prev = head;
+
+                for name in &thread.functions {
+                    let link = image.alloc::<hal_funct_entry_t>();
+                    let funct = image.get::<hal_funct_entry_t>(link);
+                    let function = &*image.get::<hal_funct_t>(functions[name.as_str()]);
+
+                    (*funct).links = hal_list_t { next: head, prev };
+                    (*funct).arg = function.arg;
+                    (*funct).funct = function.funct;
+                    (*funct).funct_ptr = functions[name.as_str()];
+
+                    (*image.get::<hal_list_t>(prev)).next = link;
+                    prev = link;
+                }
+
+                (*entry).funct_list.prev = prev;
+
+                if prev == head {
+                    (*entry).funct_list.next = head;
+                }
+
+                next = offset;
+            }
+
+            (*data).thread_list_ptr = next;
+        }
+
+        image.memory
+    }
+
+    /// Unlink the pin at `index` from its signal, if it is linked to one
+    fn unlink(&mut self, index: usize) {
+        let pin = &mut self.pins[index];
+
+        let Some(signal) = pin.signal.take() else {
+            return;
+        };
+
+        let signal = self
+            .signals
+            .iter_mut()
+            .find(|s| s.name == signal)
+            .expect("linked signal exists");
+
+        // Like LinuxCNC, the pin keeps the last value of the signal
+        unsafe {
+            *pin.dummy = *signal.value;
+            *pin.data_ptr_addr = pin.dummy;
+        }
+
+        match pin.dir {
+            x if x == PIN_OUT => signal.writers -= 1,
+            x if x == PIN_IO => signal.bidirs -= 1,
+            _ => signal.readers -= 1,
+        }
+    }
+}
+
+/// An image of HAL shared memory, allocated in aligned blocks
+struct ShmemImage {
+    memory: Box<[u128]>,
+    len: usize,
+}
+
+impl ShmemImage {
+    fn new(size: usize) -> Self {
+        Self {
+            memory: vec![0u128; size.div_ceil(mem::size_of::<u128>())].into_boxed_slice(),
+            len: 0,
+        }
+    }
+
+    /// Reserve a zeroed block for a `T`, returning its offset
+    fn alloc<T>(&mut self) -> i32 {
+        let offset = self.len;
+
+        self.len += block_size::<T>();
+
+        assert!(
+            self.len <= mem::size_of_val(&*self.memory),
+            "shmem image is too small"
+        );
+
+        offset as i32
+    }
+
+    /// Get a pointer to the `T` at `offset`
+    unsafe fn get<T>(&mut self, offset: i32) -> *mut T {
+        self.memory
+            .as_mut_ptr()
+            .cast::<u8>()
+            .add(offset as usize)
+            .cast()
+    }
+
+    /// Add an old name block if a pin or parameter has an alias, returning its offset
+    ///
+    /// Like LinuxCNC, an alias replaces the name of the pin or parameter, and the original name
+    /// is kept in a separate block.
+    fn old_name(&mut self, name: &str, alias: Option<&str>) -> i32 {
+        if alias.is_none() {
+            return 0;
+        }
+
+        let offset = self.alloc::<hal_oldname_t>();
+
+        // SAFETY: The block was just allocated
+        unsafe { copy_name(&mut (*self.get::<hal_oldname_t>(offset)).name, name) };
+
+        offset
+    }
+
+    /// Get the offset of a pointer into the image
+    fn offset<T>(&self, ptr: *const T) -> i32 {
+        (ptr as usize - self.memory.as_ptr() as usize) as i32
+    }
+}
+
+/// Size of a block holding a `T`, rounded up so every block in a [`ShmemImage`] is aligned
+fn block_size<T>() -> usize {
+    mem::size_of::<T>().next_multiple_of(mem::size_of::<u128>())
+}
+
+/// Copy a name into a fixed size, nul terminated HAL name
+fn copy_name(dest: &mut [c_char], name: &str) {
+    for (dest, byte) in dest.iter_mut().zip(name.bytes()) {
+        *dest = byte as c_char;
+    }
+}
+
+struct Component {
+    id: i32,
+    name: String,
+    ready: bool,
+}
+
+struct Pin {
+    name: String,
+    alias: Option<String>,
+    owner: i32,
+    ty: hal_type_t,
+    dir: i32,
+    data_ptr_addr: *mut *mut hal_data_u,
+    dummy: *mut hal_data_u,
+    signal: Option<String>,
+}
+
+struct Parameter {
+    name: String,
+    alias: Option<String>,
+    owner: i32,
+    ty: hal_type_t,
+    dir: u32,
+    data_addr: *mut hal_data_u,
+}
+
+struct Signal {
+    name: String,
+    ty: hal_type_t,
+    value: *mut hal_data_u,
+    readers: u32,
+    writers: u32,
+    bidirs: u32,
+}
+
+struct Function {
+    name: String,
+    owner: i32,
+    funct: Funct,
+    arg: *mut c_void,
+    uses_fp: bool,
+    reentrant: bool,
+    users: u32,
+}
+
+struct Thread {
+    name: String,
+    period: Duration,
+    uses_fp: bool,
+    functions: Vec<String>,
+}
+
+const PIN_OUT: i32 = linuxcnc_hal_sys::hal_pin_dir_t_HAL_OUT;
+const PIN_IO: i32 = linuxcnc_hal_sys::hal_pin_dir_t_HAL_IO;
+
+/// Convert a name passed to a HAL function, returning `None` if it is null, too long or not UTF-8
+unsafe fn name<'a>(name: *const c_char) -> Option<&'a str> {
+    if name.is_null() {
+        return None;
+    }
+
+    CStr::from_ptr(name)
+        .to_str()
+        .ok()
+        .filter(|name| !name.is_empty() && name.len() <= HAL_NAME_LEN as usize)
+}
+
+/// Replacements for the HAL functions in [`linuxcnc_hal_sys`]
+///
+/// Every function has the same signature as the function it replaces, and is re-exported in place
+/// of it by `crate::ffi`.
+#[allow(non_upper_case_globals, clippy::missing_safety_doc)]
+pub(crate) mod sys {
+    use super::*;
+    use linuxcnc_hal_sys::{
+        hal_bit_t, hal_data_t, hal_param_dir_t, hal_param_dir_t_HAL_RO, hal_param_dir_t_HAL_RW,
+        hal_pin_dir_t, hal_pin_dir_t_HAL_IN, hal_port_t, hal_s32_t, hal_s64_t, hal_stream_data,
+        hal_stream_t, hal_type_t_HAL_BIT, hal_type_t_HAL_FLOAT, hal_type_t_HAL_PORT,
+        hal_type_t_HAL_S32, hal_type_t_HAL_S64, hal_type_t_HAL_U32, hal_type_t_HAL_U64, hal_u32_t,
+        hal_u64_t, real_t, EINVAL, ENOSYS,
+    };
+    use std::os::raw::{c_int, c_uint, c_ulong};
+
+    const ERR_INVALID: c_int = -(EINVAL as c_int);
+
+    /// Replaces the `hal_shmem_base` and `hal_data` globals, see [`crate::ffi::hal_shmem`]
+    ///
+    /// A new image of shared memory is built from the mock HAL on every call. Earlier images may
+    /// still be in use, so they are kept until the thread exits.
+    pub(crate) fn hal_shmem() -> (*mut c_char, *mut hal_data_t) {
+        with_hal(|hal| {
+            if !hal.initialised {
+                return (ptr::null_mut(), ptr::null_mut());
+            }
+
+            let mut image = hal.shmem_image();
+            let base = image.as_mut_ptr().cast::<c_char>();
+
+            hal.memory.push(image);
+
+            (base, base.cast())
+        })
+    }
+
+    pub(crate) unsafe fn hal_init(name: *const c_char) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            if hal.components.iter().any(|c| c.name == name) {
+                return ERR_INVALID;
+            }
+
+            let id = hal.next_id();
+
+            hal.initialised = true;
+            hal.components.push(Component {
+                id,
+                name: name.to_string(),
+                ready: false,
+            });
+
+            id
+        })
+    }
+
+    pub(crate) unsafe fn hal_ready(comp_id: c_int) -> c_int {
+        with_hal(|hal| {
+            match hal.components.iter_mut().find(|c| c.id == comp_id) {
+                Some(comp) if !comp.ready => comp.ready = true,
+                _ => return ERR_INVALID,
+            }
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_exit(comp_id: c_int) -> c_int {
+        with_hal(|hal| {
+            if hal.component(comp_id).is_none() {
+                return ERR_INVALID;
+            }
+
+            for index in 0..hal.pins.len() {
+                if hal.pins[index].owner == comp_id {
+                    hal.unlink(index);
+                }
+            }
+
+            let functions = hal
+                .functions
+                .iter()
+                .filter(|f| f.owner == comp_id)
+                .map(|f| f.name.clone())
+                .collect::<Vec<_>>();
+
+            for thread in hal.threads.iter_mut() {
+                thread.functions.retain(|name| !functions.contains(name));
+            }
+
+            hal.components.retain(|c| c.id != comp_id);
+            hal.pins.retain(|p| p.owner != comp_id);
+            hal.parameters.retain(|p| p.owner != comp_id);
+            hal.functions.retain(|f| f.owner != comp_id);
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_malloc(size: c_long) -> *mut c_void {
+        let blocks = (size.max(1) as usize).div_ceil(mem::size_of::<u128>());
+
+        with_hal(|hal| {
+            let mut block = vec![0u128; blocks].into_boxed_slice();
+
+            let ptr = block.as_mut_ptr().cast();
+
+            hal.memory.push(block);
+
+            ptr
+        })
+    }
+
+    unsafe fn pin_new(
+        name: *const c_char,
+        ty: hal_type_t,
+        dir: hal_pin_dir_t,
+        data_ptr_addr: *mut *mut hal_data_u,
+        comp_id: c_int,
+    ) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        if data_ptr_addr.is_null() || ![hal_pin_dir_t_HAL_IN, PIN_OUT, PIN_IO].contains(&dir) {
+            return ERR_INVALID;
+        }
+
+        with_hal(|hal| {
+            match hal.component(comp_id) {
+                Some(comp) if !comp.ready => (),
+                _ => return ERR_INVALID,
+            }
+
+            if !hal.name_available(name) {
+                return ERR_INVALID;
+            }
+
+            let dummy = hal.alloc_value();
+
+            *data_ptr_addr = dummy;
+
+            hal.pins.push(Pin {
+                name: name.to_string(),
+                alias: None,
+                owner: comp_id,
+                ty,
+                dir,
+                data_ptr_addr,
+                dummy,
+                signal: None,
+            });
+
+            0
+        })
+    }
+
+    unsafe fn param_new(
+        name: *const c_char,
+        ty: hal_type_t,
+        dir: hal_param_dir_t,
+        data_addr: *mut hal_data_u,
+        comp_id: c_int,
+    ) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        if data_addr.is_null() || ![hal_param_dir_t_HAL_RO, hal_param_dir_t_HAL_RW].contains(&dir) {
+            return ERR_INVALID;
+        }
+
+        with_hal(|hal| {
+            match hal.component(comp_id) {
+                Some(comp) if !comp.ready => (),
+                _ => return ERR_INVALID,
+            }
+
+            if !hal.name_available(name) {
+                return ERR_INVALID;
+            }
+
+            hal.parameters.push(Parameter {
+                name: name.to_string(),
+                alias: None,
+                owner: comp_id,
+                ty,
+                dir,
+                data_addr,
+            });
+
+            0
+        })
+    }
+
+    unsafe fn param_set(name: *const c_char, value: HalValue) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| match hal.parameter(name) {
+            Some(param)
+                if param.ty == value.hal_type().as_raw() && param.dir == hal_param_dir_t_HAL_RW =>
+            {
+                match value.write(param.data_addr) {
+                    Ok(()) => 0,
+                    Err(_) => ERR_INVALID,
+                }
+            }
+            _ => ERR_INVALID,
+        })
+    }
+
+    macro_rules! mock_pin_new {
+        ($fn:ident, $storage:ty, $ty:expr) => {
+            pub(crate) unsafe fn $fn(
+                name: *const c_char,
+                dir: hal_pin_dir_t,
+                data_ptr_addr: *mut *mut $storage,
+                comp_id: c_int,
+            ) -> c_int {
+                pin_new(name, $ty, dir, data_ptr_addr.cast(), comp_id)
+            }
+        };
+    }
+
+    mock_pin_new!(hal_pin_bit_new, hal_bit_t, hal_type_t_HAL_BIT);
+    mock_pin_new!(hal_pin_float_new, real_t, hal_type_t_HAL_FLOAT);
+    mock_pin_new!(hal_pin_s32_new, hal_s32_t, hal_type_t_HAL_S32);
+    mock_pin_new!(hal_pin_u32_new, hal_u32_t, hal_type_t_HAL_U32);
+    mock_pin_new!(hal_pin_s64_new, hal_s64_t, hal_type_t_HAL_S64);
+    mock_pin_new!(hal_pin_u64_new, hal_u64_t, hal_type_t_HAL_U64);
+    mock_pin_new!(hal_pin_port_new, hal_port_t, hal_type_t_HAL_PORT);
+
+    macro_rules! mock_param_new {
+        ($fn:ident, $storage:ty, $ty:expr) => {
+            pub(crate) unsafe fn $fn(
+                name: *const c_char,
+                dir: hal_param_dir_t,
+                data_addr: *mut $storage,
+                comp_id: c_int,
+            ) -> c_int {
+                param_new(name, $ty, dir, data_addr.cast(), comp_id)
+            }
+        };
+    }
+
+    mock_param_new!(hal_param_bit_new, hal_bit_t, hal_type_t_HAL_BIT);
+    mock_param_new!(hal_param_float_new, real_t, hal_type_t_HAL_FLOAT);
+    mock_param_new!(hal_param_s32_new, hal_s32_t, hal_type_t_HAL_S32);
+    mock_param_new!(hal_param_u32_new, hal_u32_t, hal_type_t_HAL_U32);
+    mock_param_new!(hal_param_s64_new, hal_s64_t, hal_type_t_HAL_S64);
+    mock_param_new!(hal_param_u64_new, hal_u64_t, hal_type_t_HAL_U64);
+
+    pub(crate) unsafe fn hal_param_bit_set(name: *const c_char, value: c_int) -> c_int {
+        param_set(name, HalValue::Bit(value != 0))
+    }
+
+    pub(crate) unsafe fn hal_param_float_set(name: *const c_char, value: f64) -> c_int {
+        param_set(name, HalValue::Float(value))
+    }
+
+    pub(crate) unsafe fn hal_param_s32_set(name: *const c_char, value: c_long) -> c_int {
+        param_set(name, HalValue::S32(value as hal_s32_t))
+    }
+
+    pub(crate) unsafe fn hal_param_u32_set(name: *const c_char, value: c_ulong) -> c_int {
+        param_set(name, HalValue::U32(value as hal_u32_t))
+    }
+
+    pub(crate) unsafe fn hal_param_s64_set(name: *const c_char, value: c_long) -> c_int {
+        param_set(name, HalValue::S64(value as hal_s64_t))
+    }
+
+    pub(crate) unsafe fn hal_param_u64_set(name: *const c_char, value: c_ulong) -> c_int {
+        param_set(name, HalValue::U64(value as hal_u64_t))
+    }
+
+    pub(crate) unsafe fn hal_pin_alias(pin_name: *const c_char, alias: *const c_char) -> c_int {
+        let Some(pin_name) = super::name(pin_name) else {
+            return ERR_INVALID;
+        };
+
+        let alias = match alias.is_null() {
+            true => None,
+            false => match super::name(alias) {
+                Some(alias) => Some(alias.to_string()),
+                None => return ERR_INVALID,
+            },
+        };
+
+        with_hal(|hal| {
+            if alias
+                .as_deref()
+                .is_some_and(|alias| !hal.name_available(alias))
+            {
+                return ERR_INVALID;
+            }
+
+            let pin = hal
+                .pins
+                .iter_mut()
+                .find(|p| p.name == pin_name || p.alias.as_deref() == Some(pin_name));
+
+            match pin {
+                Some(pin) => {
+                    pin.alias = alias;
+
+                    0
+                }
+                None => ERR_INVALID,
+            }
+        })
+    }
+
+    pub(crate) unsafe fn hal_param_alias(pin_name: *const c_char, alias: *const c_char) -> c_int {
+        let Some(param_name) = super::name(pin_name) else {
+            return ERR_INVALID;
+        };
+
+        let alias = match alias.is_null() {
+            true => None,
+            false => match super::name(alias) {
+                Some(alias) => Some(alias.to_string()),
+                None => return ERR_INVALID,
+            },
+        };
+
+        with_hal(|hal| {
+            if alias
+                .as_deref()
+                .is_some_and(|alias| !hal.name_available(alias))
+            {
+                return ERR_INVALID;
+            }
+
+            let param = hal
+                .parameters
+                .iter_mut()
+                .find(|p| p.name == param_name || p.alias.as_deref() == Some(param_name));
+
+            match param {
+                Some(param) => {
+                    param.alias = alias;
+
+                    0
+                }
+                None => ERR_INVALID,
+            }
+        })
+    }
+
+    pub(crate) unsafe fn hal_get_pin_value_by_name(
+        name: *const c_char,
+        type_: *mut hal_type_t,
+        data: *mut *mut hal_data_u,
+        connected: *mut bool,
+    ) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| match hal.pin(name) {
+            Some(pin) => {
+                *type_ = pin.ty;
+                *data = *pin.data_ptr_addr;
+                *connected = pin.signal.is_some();
+
+                0
+            }
+            None => ERR_INVALID,
+        })
+    }
+
+    pub(crate) unsafe fn hal_get_param_value_by_name(
+        name: *const c_char,
+        type_: *mut hal_type_t,
+        data: *mut *mut hal_data_u,
+    ) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| match hal.parameter(name) {
+            Some(param) => {
+                *type_ = param.ty;
+                *data = param.data_addr;
+
+                0
+            }
+            None => ERR_INVALID,
+        })
+    }
+
+    pub(crate) unsafe fn hal_get_signal_value_by_name(
+        name: *const c_char,
+        type_: *mut hal_type_t,
+        data: *mut *mut hal_data_u,
+        has_writers: *mut bool,
+    ) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| match hal.signal(name) {
+            Some(signal) => {
+                *type_ = signal.ty;
+                *data = signal.value;
+                *has_writers = signal.writers > 0;
+
+                0
+            }
+            None => ERR_INVALID,
+        })
+    }
+
+    pub(crate) unsafe fn hal_signal_new(name: *const c_char, type_: hal_type_t) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            if hal.signal(name).is_some() {
+                return ERR_INVALID;
+            }
+
+            let value = hal.alloc_value();
+
+            hal.signals.push(Signal {
+                name: name.to_string(),
+                ty: type_,
+                value,
+                readers: 0,
+                writers: 0,
+                bidirs: 0,
+            });
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_signal_delete(name: *const c_char) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            if hal.signal(name).is_none() {
+                return ERR_INVALID;
+            }
+
+            for index in 0..hal.pins.len() {
+                if hal.pins[index].signal.as_deref() == Some(name) {
+                    hal.unlink(index);
+                }
+            }
+
+            hal.signals.retain(|s| s.name != name);
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_link(pin_name: *const c_char, sig_name: *const c_char) -> c_int {
+        let (Some(pin_name), Some(sig_name)) = (super::name(pin_name), super::name(sig_name))
+        else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            let Some(index) = hal
+                .pins
+                .iter()
+                .position(|p| p.name == pin_name || p.alias.as_deref() == Some(pin_name))
+            else {
+                return ERR_INVALID;
+            };
+
+            let pin = &mut hal.pins[index];
+
+            let Some(signal) = hal.signals.iter_mut().find(|s| s.name == sig_name) else {
+                return ERR_INVALID;
+            };
+
+            match &pin.signal {
+                Some(linked) if linked == sig_name => return 0,
+                Some(_) => return ERR_INVALID,
+                None => (),
+            }
+
+            let writer_conflict = match pin.dir {
+                x if x == PIN_OUT => signal.writers > 0 || signal.bidirs > 0,
+                x if x == PIN_IO => signal.writers > 0,
+                _ => false,
+            };
+
+            if pin.ty != signal.ty || writer_conflict {
+                return ERR_INVALID;
+            }
+
+            // Like LinuxCNC, the first pin linked to a signal sets its value
+            if signal.readers == 0 && signal.writers == 0 && signal.bidirs == 0 {
+                *signal.value = *pin.dummy;
+            }
+
+            *pin.data_ptr_addr = signal.value;
+            pin.signal = Some(signal.name.clone());
+
+            match pin.dir {
+                x if x == PIN_OUT => signal.writers += 1,
+                x if x == PIN_IO => signal.bidirs += 1,
+                _ => signal.readers += 1,
+            }
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_unlink(pin_name: *const c_char) -> c_int {
+        let Some(pin_name) = super::name(pin_name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            match hal
+                .pins
+                .iter()
+                .position(|p| p.name == pin_name || p.alias.as_deref() == Some(pin_name))
+            {
+                Some(index) => hal.unlink(index),
+                None => return ERR_INVALID,
+            }
+
+            0
+        })
+    }
+
+    // Realtime-only functions are still mocked with the `ulapi` feature, but nothing calls them
+    #[cfg_attr(feature = "ulapi", allow(dead_code))]
+    pub(crate) unsafe fn hal_export_funct(
+        name: *const c_char,
+        funct: Option<Funct>,
+        arg: *mut c_void,
+        uses_fp: c_int,
+        reentrant: c_int,
+        comp_id: c_int,
+    ) -> c_int {
+        let (Some(name), Some(funct)) = (super::name(name), funct) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            match hal.component(comp_id) {
+                Some(comp) if !comp.ready => (),
+                _ => return ERR_INVALID,
+            }
+
+            if hal.functions.iter().any(|f| f.name == name) {
+                return ERR_INVALID;
+            }
+
+            hal.functions.push(Function {
+                name: name.to_string(),
+                owner: comp_id,
+                funct,
+                arg,
+                uses_fp: uses_fp != 0,
+                reentrant: reentrant != 0,
+                users: 0,
+            });
+
+            0
+        })
+    }
+
+    // `c_ulong` is only 32 bits on some targets
+    #[allow(clippy::unnecessary_cast)]
+    #[cfg_attr(feature = "ulapi", allow(dead_code))]
+    pub(crate) unsafe fn hal_create_thread(
+        name: *const c_char,
+        period_nsec: c_ulong,
+        uses_fp: c_int,
+    ) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            if period_nsec == 0 || hal.threads.iter().any(|t| t.name == name) {
+                return ERR_INVALID;
+            }
+
+            hal.threads.push(Thread {
+                name: name.to_string(),
+                period: Duration::from_nanos(period_nsec as u64),
+                uses_fp: uses_fp != 0,
+                functions: Vec::new(),
+            });
+
+            hal.next_id()
+        })
+    }
+
+    #[cfg_attr(feature = "ulapi", allow(dead_code))]
+    pub(crate) unsafe fn hal_thread_delete(name: *const c_char) -> c_int {
+        let Some(name) = super::name(name) else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            let Some(index) = hal.threads.iter().position(|t| t.name == name) else {
+                return ERR_INVALID;
+            };
+
+            let thread = hal.threads.remove(index);
+
+            for function in hal.functions.iter_mut() {
+                if thread.functions.contains(&function.name) {
+                    function.users -= 1;
+                }
+            }
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_add_funct_to_thread(
+        funct_name: *const c_char,
+        thread_name: *const c_char,
+        position: c_int,
+    ) -> c_int {
+        let (Some(funct_name), Some(thread_name)) =
+            (super::name(funct_name), super::name(thread_name))
+        else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            let (Some(function), Some(thread)) = (
+                hal.functions.iter_mut().find(|f| f.name == funct_name),
+                hal.threads.iter_mut().find(|t| t.name == thread_name),
+            ) else {
+                return ERR_INVALID;
+            };
+
+            if (!function.reentrant && function.users > 0) || (function.uses_fp && !thread.uses_fp)
+            {
+                return ERR_INVALID;
+            }
+
+            let len = thread.functions.len() as c_int;
+
+            let index = match position {
+                p if p > 0 && p <= len + 1 => p - 1,
+                p if p < 0 && -p <= len + 1 => len + 1 + p,
+                _ => return ERR_INVALID,
+            };
+
+            thread
+                .functions
+                .insert(index as usize, function.name.clone());
+            function.users += 1;
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_del_funct_from_thread(
+        funct_name: *const c_char,
+        thread_name: *const c_char,
+    ) -> c_int {
+        let (Some(funct_name), Some(thread_name)) =
+            (super::name(funct_name), super::name(thread_name))
+        else {
+            return ERR_INVALID;
+        };
+
+        with_hal(|hal| {
+            let (Some(function), Some(thread)) = (
+                hal.functions.iter_mut().find(|f| f.name == funct_name),
+                hal.threads.iter_mut().find(|t| t.name == thread_name),
+            ) else {
+                return ERR_INVALID;
+            };
+
+            let Some(index) = thread.functions.iter().position(|f| f == funct_name) else {
+                return ERR_INVALID;
+            };
+
+            thread.functions.remove(index);
+            function.users -= 1;
+
+            0
+        })
+    }
+
+    pub(crate) unsafe fn hal_start_threads() -> c_int {
+        0
+    }
+
+    pub(crate) unsafe fn hal_stop_threads() -> c_int {
+        0
+    }
+
+    pub(crate) unsafe fn hal_port_read(
+        _port: hal_port_t,
+        _dest: *mut c_char,
+        count: c_uint,
+    ) -> bool {
+        count == 0
+    }
+
+    pub(crate) unsafe fn hal_port_peek(
+        _port: hal_port_t,
+        _dest: *mut c_char,
+        count: c_uint,
+    ) -> bool {
+        count == 0
+    }
+
+    pub(crate) unsafe fn hal_port_peek_commit(_port: hal_port_t, count: c_uint) -> bool {
+        count == 0
+    }
+
+    pub(crate) unsafe fn hal_port_write(
+        _port: hal_port_t,
+        _src: *const c_char,
+        count: c_uint,
+    ) -> bool {
+        count == 0
+    }
+
+    pub(crate) unsafe fn hal_port_readable(_port: hal_port_t) -> c_uint {
+        0
+    }
+
+    pub(crate) unsafe fn hal_port_writable(_port: hal_port_t) -> c_uint {
+        0
+    }
+
+    pub(crate) unsafe fn hal_port_buffer_size(_port: hal_port_t) -> c_uint {
+        0
+    }
+
+    pub(crate) unsafe fn hal_port_clear(_port: hal_port_t) {}
+
+    pub(crate) unsafe fn hal_stream_create(
+        _stream: *mut hal_stream_t,
+        _comp: c_int,
+        _key: c_int,
+        _depth: c_int,
+        _typestring: *const c_char,
+    ) -> c_int {
+        -(ENOSYS as c_int)
+    }
+
+    pub(crate) unsafe fn hal_stream_attach(
+        _stream: *mut hal_stream_t,
+        _comp: c_int,
+        _key: c_int,
+        _typestring: *const c_char,
+    ) -> c_int {
+        -(ENOSYS as c_int)
+    }
+
+    pub(crate) unsafe fn hal_stream_destroy(_stream: *mut hal_stream_t) {}
+
+    pub(crate) unsafe fn hal_stream_detach(_stream: *mut hal_stream_t) -> c_int {
+        0
+    }
+
+    pub(crate) unsafe fn hal_stream_read(
+        _stream: *mut hal_stream_t,
+        _buf: *mut hal_stream_data,
+        _sampleno: *mut c_uint,
+    ) -> c_int {
+        ERR_INVALID
+    }
+
+    pub(crate) unsafe fn hal_stream_write(
+        _stream: *mut hal_stream_t,
+        _buf: *mut hal_stream_data,
+    ) -> c_int {
+        ERR_INVALID
+    }
+
+    pub(crate) unsafe fn hal_stream_readable(_stream: *mut hal_stream_t) -> bool {
+        false
+    }
+
+    pub(crate) unsafe fn hal_stream_writable(_stream: *mut hal_stream_t) -> bool {
+        false
+    }
+
+    pub(crate) unsafe fn hal_stream_depth(_stream: *mut hal_stream_t) -> c_int {
+        0
+    }
+
+    pub(crate) unsafe fn hal_stream_maxdepth(_stream: *mut hal_stream_t) -> c_int {
+        0
+    }
+
+    pub(crate) unsafe fn hal_stream_num_underruns(_stream: *mut hal_stream_t) -> c_int {
+        0
+    }
+
+    pub(crate) unsafe fn hal_stream_num_overruns(_stream: *mut hal_stream_t) -> c_int {
+        0
+    }
+}
+
+/// Replacements for the RTAPI message functions used by `rtapi-logger`
+///
+/// `liblinuxcnchal` is not linked with the `mock` feature, so these are exported under the same
+/// symbol names to let components and examples that log with `rtapi-logger` link. Messages at or
+/// below the current message level are printed to stderr.
+///
+/// `rtapi_print_msg` is variadic in C, which can't be defined in stable Rust. This version takes
+/// exactly one string argument, and only prints it when the format string is `"%s"` as passed by
+/// `rtapi-logger`. Any other format string is printed as is, without reading its arguments.
+#[allow(clippy::missing_safety_doc)]
+mod rtapi {
+    use linuxcnc_hal_sys::{
+        msg_level_t, msg_level_t_RTAPI_MSG_ALL, msg_level_t_RTAPI_MSG_ERR,
+        msg_level_t_RTAPI_MSG_NONE, EINVAL,
+    };
+    use std::{
+        ffi::CStr,
+        os::raw::{c_char, c_int},
+        sync::atomic::{AtomicI32, Ordering},
+    };
+
+    /// The current message level, which defaults to errors only like LinuxCNC
+    static MSG_LEVEL: AtomicI32 = AtomicI32::new(msg_level_t_RTAPI_MSG_ERR as i32);
+
+    #[no_mangle]
+    pub unsafe extern "C" fn rtapi_print_msg(
+        level: msg_level_t,
+        fmt: *const c_char,
+        arg: *const c_char,
+    ) {
+        if fmt.is_null() || level as c_int > MSG_LEVEL.load(Ordering::Relaxed) {
+            return;
+        }
+
+        let fmt = CStr::from_ptr(fmt);
+
+        let message = if fmt.to_bytes() == b"%s" && !arg.is_null() {
+            CStr::from_ptr(arg)
+        } else {
+            fmt
+        };
+
+        eprint!("{}", message.to_string_lossy());
+    }
+
+    #[no_mangle]
+    pub extern "C" fn rtapi_set_msg_level(level: c_int) -> c_int {
+        if !(msg_level_t_RTAPI_MSG_NONE as c_int..=msg_level_t_RTAPI_MSG_ALL as c_int)
+            .contains(&level)
+        {
+            return -(EINVAL as c_int);
+        }
+
+        MSG_LEVEL.store(level, Ordering::Relaxed);
+
+        0
+    }
+
+    #[no_mangle]
+    pub extern "C" fn rtapi_get_msg_level() -> c_int {
+        MSG_LEVEL.load(Ordering::Relaxed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::{ComponentInitError, HalErrno, PinRegisterError, ResourcesError, SignalError},
+        hal_pin::{InputPin, OutputPin},
+        prelude::*,
+        query,
+        signal::Signal,
+        HalComponent, Parameter, RegisterResources, Resources,
+    };
+    use linuxcnc_hal_sys::EINVAL;
+    use std::error::Error;
+
+    struct Pins<'comp> {
+        input: InputPin<'comp, u32>,
+        output: OutputPin<'comp, u32>,
+        scale: Parameter<'comp, u32>,
+    }
+
+    impl<'comp> Resources<'comp> for Pins<'comp> {
+        type RegisterError = ResourcesError;
+
+        fn register_resources(
+            comp: &RegisterResources<'comp>,
+        ) -> Result<Self, Self::RegisterError> {
+            Ok(Pins {
+                input: comp.register_pin("in")?,
+                output: comp.register_pin("out")?,
+                scale: comp.register_parameter("scale")?,
+            })
+        }
+    }
+
+    #[test]
+    fn component_lifecycle() {
+        let comp = HalComponent::new("mock").unwrap();
+
+        assert!(!is_ready("mock"));
+
+        let pins: Pins = comp.register().unwrap();
+
+        assert!(is_ready("mock"));
+
+        set_pin("mock.in", HalValue::U32(3)).unwrap();
+        query::set_parameter("mock.scale", HalValue::U32(5)).unwrap();
+
+        pins.output
+            .set_value(pins.input.value().unwrap() * pins.scale.value().unwrap())
+            .unwrap();
+
+        assert_eq!(query::pin("mock.out").unwrap().value, HalValue::U32(15));
+        assert_eq!(
+            set_pin("mock.in", HalValue::Bit(true)),
+            Err(QueryError::TypeMismatch("mock.in".to_string()))
+        );
+
+        let signal = Signal::<u32>::new(&comp, "mock-out").unwrap();
+
+        signal.link(&pins.output).unwrap();
+        signal.link(&pins.input).unwrap();
+
+        assert_eq!(query::signal("mock-out").unwrap().value, HalValue::U32(15));
+        assert_eq!(
+            Signal::<u32>::open(&comp, "mock-out")
+                .unwrap()
+                .link_by_name("mock.out"),
+            Ok(())
+        );
+
+        let other = Signal::<u32>::new(&comp, "other").unwrap();
+
+        assert_eq!(
+            other.link(&pins.input),
+            Err(SignalError::AlreadyLinked("mock.in".to_string()))
+        );
+
+        drop((pins, signal, other));
+        drop(comp);
+
+        assert!(!is_ready("mock"));
+        assert_eq!(
+            query::pin("mock.in"),
+            Err(QueryError::NotFound("mock.in".to_string()))
+        );
+    }
+
+    #[cfg(not(feature = "ulapi"))]
+    #[test]
+    fn rtapi_init_errors() {
+        use crate::rtapi::ComponentSlot;
+        use linuxcnc_hal_sys::{EBUSY, ENOENT};
+
+        let slot = ComponentSlot::<Pins>::new();
+
+        assert_eq!(
+            slot.init("rtapi-comp", |_, _| Err(HalErrno::Busy.into())),
+            -(EBUSY as i32)
+        );
+        let not_found = QueryError::NotFound("rtapi-comp.in".to_string());
+
+        assert_eq!(
+            slot.init("rtapi-comp", |_, _| Err(not_found.into())),
+            -(ENOENT as i32)
+        );
+        assert_eq!(
+            query::pin("rtapi-comp.in"),
+            Err(QueryError::NotFound("rtapi-comp.in".to_string()))
+        );
+
+        assert_eq!(slot.init("rtapi-comp", |_, _| Ok(())), 0);
+        assert!(is_ready("rtapi-comp"));
+        assert!(query::pin("rtapi-comp.in").is_ok());
+
+        unsafe { slot.exit() };
+
+        assert!(!is_ready("rtapi-comp"));
+    }
+
+    struct Duplicate<'comp> {
+        _first: InputPin<'comp, u32>,
+        _second: InputPin<'comp, u32>,
+        _signal: Signal<'comp, u32>,
+    }
+
+    impl<'comp> Resources<'comp> for Duplicate<'comp> {
+        type RegisterError = ResourcesError;
+
+        fn register_resources(
+            comp: &RegisterResources<'comp>,
+        ) -> Result<Self, Self::RegisterError> {
+            Ok(Duplicate {
+                _signal: Signal::new(comp.component(), "duplicate")?,
+                _first: comp.register_pin("in")?,
+                _second: comp.register_pin("in")?,
+            })
+        }
+    }
+
+    #[test]
+    fn registration_error_chain() {
+        let comp = HalComponent::new("dup").unwrap();
+
+        let Err(err) = comp.register::<Duplicate>() else {
+            panic!("registering a pin twice should fail");
+        };
+
+        assert_eq!(err.errno(), -(EINVAL as i32));
+        assert!(matches!(
+            &err,
+            ComponentInitError::ResourceRegistration(ResourcesError::Pin(
+                PinRegisterError::Hal {
+                    name,
+                    source: HalErrno::Invalid,
+                }
+            )) if name == "dup.in"
+        ));
+
+        let chain = std::iter::successors(Some(&err as &dyn Error), |e| (*e).source())
+            .map(ToString::to_string)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            chain,
+            [
+                "failed to register resources with component",
+                "failed to register pin `dup.in`",
+                "invalid argument (EINVAL)",
+            ]
+        );
+    }
+}
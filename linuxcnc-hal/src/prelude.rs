@@ -1,3 +1,3 @@
 //! Prelude
 
-pub use crate::hal_pin::{PinRead, PinWrite};
+pub use crate::hal_pin::{HalPin, PinRead, PinWrite};
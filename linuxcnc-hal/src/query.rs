@@ -0,0 +1,545 @@
+//! Read and write pins, parameters and signals owned by any component
+//!
+//! These functions look resources up by their full name, e.g. `motion.spindle-speed-out`, so
+//! supervisory tools can inspect and tune a running machine without calling `halcmd getp` or
+//! `halcmd setp`. A [`HalComponent`](crate::HalComponent) must be created before any of the
+//! functions in this module are used.
+//!
+//! Pins and parameters are read from HAL shared memory while holding the HAL mutex, so their
+//! direction and permissions are returned along with their value. Pins and parameters can be
+//! looked up by their original name or by their alias. The layout of HAL shared memory is only
+//! known for LinuxCNC 2.9, so [`pin`], [`parameter`] and [`set_signal`] are only available with
+//! the `linuxcnc-2-9` feature.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::{
+//!     query::{self, HalValue},
+//!     HalComponent, RegisterResources, Resources,
+//! };
+//! use std::error::Error;
+//!
+//! struct NoResources;
+//!
+//! impl<'comp> Resources<'comp> for NoResources {
+//!     type RegisterError = linuxcnc_hal::error::ResourcesError;
+//!
+//!     fn register_resources(_comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+//!         Ok(NoResources)
+//!     }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!     let comp = HalComponent::new("supervisor")?;
+//!     let _resources: NoResources = comp.register()?;
+//!
+//!     let pin = query::pin("spindle.0.speed-out")?;
+//!
+//!     if let HalValue::Float(speed) = pin.value {
+//!         println!(
+//!             "Spindle speed {} ({:?}, connected: {})",
+//!             speed, pin.direction, pin.connected
+//!         );
+//!     }
+//!
+//!     query::set_parameter("pid.0.Pgain", HalValue::Float(1.5))?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::error::{HalErrno, QueryError};
+use crate::ffi::{
+    hal_data_u, hal_get_param_value_by_name, hal_get_pin_value_by_name,
+    hal_get_signal_value_by_name, hal_param_bit_set, hal_param_float_set, hal_param_s32_set,
+    hal_param_s64_set, hal_param_u32_set, hal_param_u64_set, hal_type_t, hal_type_t_HAL_BIT,
+    hal_type_t_HAL_FLOAT, hal_type_t_HAL_PORT, hal_type_t_HAL_S32, hal_type_t_HAL_S64,
+    hal_type_t_HAL_U32, hal_type_t_HAL_U64, HAL_NAME_LEN,
+};
+#[cfg(feature = "linuxcnc-2-9")]
+use crate::snapshot;
+use crate::{hal_parameter::ParameterPermissions, hal_pin::PinDirection};
+use std::{
+    ffi::{CStr, CString},
+    os::raw::{c_int, c_long, c_ulong},
+    ptr,
+};
+
+/// The type of a pin, parameter or signal
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HalType {
+    /// `bit`, stored as a `bool`
+    Bit,
+
+    /// `float`, stored as an `f64`
+    Float,
+
+    /// `s32`, stored as an `i32`
+    S32,
+
+    /// `u32`, stored as a `u32`
+    U32,
+
+    /// `s64`, stored as an `i64`
+    S64,
+
+    /// `u64`, stored as a `u64`
+    U64,
+
+    /// `port`
+    Port,
+}
+
+impl HalType {
+    /// Convert a raw [`hal_type_t`] into a `HalType`
+    ///
+    /// Returns `None` for unspecified or unknown types.
+    pub fn from_raw(ty: hal_type_t) -> Option<Self> {
+        match ty {
+            x if x == hal_type_t_HAL_BIT => Some(Self::Bit),
+            x if x == hal_type_t_HAL_FLOAT => Some(Self::Float),
+            x if x == hal_type_t_HAL_S32 => Some(Self::S32),
+            x if x == hal_type_t_HAL_U32 => Some(Self::U32),
+            x if x == hal_type_t_HAL_S64 => Some(Self::S64),
+            x if x == hal_type_t_HAL_U64 => Some(Self::U64),
+            x if x == hal_type_t_HAL_PORT => Some(Self::Port),
+            _ => None,
+        }
+    }
+
+    /// Get the raw [`hal_type_t`] of this type
+    pub fn as_raw(self) -> hal_type_t {
+        match self {
+            Self::Bit => hal_type_t_HAL_BIT,
+            Self::Float => hal_type_t_HAL_FLOAT,
+            Self::S32 => hal_type_t_HAL_S32,
+            Self::U32 => hal_type_t_HAL_U32,
+            Self::S64 => hal_type_t_HAL_S64,
+            Self::U64 => hal_type_t_HAL_U64,
+            Self::Port => hal_type_t_HAL_PORT,
+        }
+    }
+}
+
+/// The value of a pin, parameter or signal
+#[derive(Copy, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HalValue {
+    /// `bit` value
+    Bit(bool),
+
+    /// `float` value
+    Float(f64),
+
+    /// `s32` value
+    S32(i32),
+
+    /// `u32` value
+    U32(u32),
+
+    /// `s64` value
+    S64(i64),
+
+    /// `u64` value
+    U64(u64),
+
+    /// `port` resource
+    ///
+    /// Ports carry a stream of bytes rather than a single value, so they cannot be read or written
+    /// with this module.
+    Port,
+}
+
+impl HalValue {
+    /// Get the type of this value
+    pub fn hal_type(&self) -> HalType {
+        match self {
+            Self::Bit(_) => HalType::Bit,
+            Self::Float(_) => HalType::Float,
+            Self::S32(_) => HalType::S32,
+            Self::U32(_) => HalType::U32,
+            Self::S64(_) => HalType::S64,
+            Self::U64(_) => HalType::U64,
+            Self::Port => HalType::Port,
+        }
+    }
+
+    /// Read a value of the given type from HAL shared memory
+    ///
+    /// # Safety
+    ///
+    /// `data` must be a valid pointer to HAL shared memory holding a value of type `ty`.
+    pub(crate) unsafe fn read(ty: HalType, data: *const hal_data_u) -> Self {
+        match ty {
+            HalType::Bit => Self::Bit(ptr::read_volatile(ptr::addr_of!((*data).b))),
+            HalType::Float => Self::Float(ptr::read_volatile(ptr::addr_of!((*data).f))),
+            HalType::S32 => Self::S32(ptr::read_volatile(ptr::addr_of!((*data).s))),
+            HalType::U32 => Self::U32(ptr::read_volatile(ptr::addr_of!((*data).u))),
+            HalType::S64 => Self::S64(ptr::read_volatile(ptr::addr_of!((*data).ls))),
+            HalType::U64 => Self::U64(ptr::read_volatile(ptr::addr_of!((*data).lu))),
+            HalType::Port => Self::Port,
+        }
+    }
+
+    /// Write this value to HAL shared memory
+    ///
+    /// # Safety
+    ///
+    /// `data` must be a valid pointer to HAL shared memory holding a value of the same type as
+    /// this value.
+    #[cfg(feature = "linuxcnc-2-9")]
+    pub(crate) unsafe fn write(self, data: *mut hal_data_u) -> Result<(), QueryError> {
+        match self {
+            Self::Bit(value) => ptr::write_volatile(ptr::addr_of_mut!((*data).b), value),
+            Self::Float(value) => ptr::write_volatile(ptr::addr_of_mut!((*data).f), value),
+            Self::S32(value) => ptr::write_volatile(ptr::addr_of_mut!((*data).s), value),
+            Self::U32(value) => ptr::write_volatile(ptr::addr_of_mut!((*data).u), value),
+            Self::S64(value) => ptr::write_volatile(ptr::addr_of_mut!((*data).ls), value),
+            Self::U64(value) => ptr::write_volatile(ptr::addr_of_mut!((*data).lu), value),
+            Self::Port => return Err(QueryError::Port),
+        }
+
+        Ok(())
+    }
+}
+
+/// The value of a pin
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PinValue {
+    /// The current value of the pin
+    ///
+    /// If the pin is linked to a signal, this is the value of the signal.
+    pub value: HalValue,
+
+    /// The direction of the pin
+    pub direction: PinDirection,
+
+    /// Whether the pin is linked to a signal
+    pub connected: bool,
+}
+
+/// The value of a parameter
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ParameterValue {
+    /// The current value of the parameter
+    pub value: HalValue,
+
+    /// Whether the parameter can be set with [`set_parameter`]
+    pub permissions: ParameterPermissions,
+}
+
+/// The value of a signal
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SignalValue {
+    /// The current value of the signal
+    pub value: HalValue,
+
+    /// Whether a pin that writes to the signal is linked to it
+    pub has_writers: bool,
+}
+
+/// Get the value of any pin by its full name or alias
+///
+/// # Errors
+///
+/// * [`QueryError::NotFound`] - if no pin with this name exists
+/// * [`QueryError::NotInitialised`] - if no component has been created in this process
+#[cfg(feature = "linuxcnc-2-9")]
+pub fn pin(name: &str) -> Result<PinValue, QueryError> {
+    name_to_c(name)?;
+
+    let pin = snapshot::find_pin(name)?.ok_or_else(|| QueryError::NotFound(name.to_string()))?;
+
+    Ok(PinValue {
+        value: pin.value,
+        direction: pin.direction,
+        connected: pin.signal.is_some(),
+    })
+}
+
+/// Get the value of any parameter by its full name or alias
+///
+/// # Errors
+///
+/// * [`QueryError::NotFound`] - if no parameter with this name exists
+/// * [`QueryError::NotInitialised`] - if no component has been created in this process
+#[cfg(feature = "linuxcnc-2-9")]
+pub fn parameter(name: &str) -> Result<ParameterValue, QueryError> {
+    name_to_c(name)?;
+
+    let param =
+        snapshot::find_parameter(name)?.ok_or_else(|| QueryError::NotFound(name.to_string()))?;
+
+    Ok(ParameterValue {
+        value: param.value,
+        permissions: param.permissions,
+    })
+}
+
+/// Get the value of any signal by its name
+pub fn signal(name: &str) -> Result<SignalValue, QueryError> {
+    let name_ffi = name_to_c(name)?;
+
+    let (ty, data, has_writers) = raw_signal(&name_ffi)?;
+
+    Ok(SignalValue {
+        value: unsafe { HalValue::read(ty, data) },
+        has_writers,
+    })
+}
+
+/// Set the value of any parameter by its full name, like `halcmd setp`
+///
+/// # Errors
+///
+/// * [`QueryError::NotFound`] - if no parameter with this name exists
+/// * [`QueryError::TypeMismatch`] - if the value type does not match the parameter type
+/// * [`QueryError::ReadOnly`] - if the parameter is read only
+/// * [`QueryError::LockedHal`] - if parameters are locked
+pub fn set_parameter(name: &str, value: HalValue) -> Result<(), QueryError> {
+    let name_ffi = name_to_c(name)?;
+
+    let (ty, _) = raw_parameter(&name_ffi)?;
+
+    if ty != value.hal_type() {
+        return Err(QueryError::TypeMismatch(name.to_string()));
+    }
+
+    let name_ptr = name_ffi.as_ptr();
+
+    let ret = unsafe {
+        match value {
+            HalValue::Bit(value) => hal_param_bit_set(name_ptr, value as i32),
+            HalValue::Float(value) => hal_param_float_set(name_ptr, value),
+            HalValue::S32(value) => hal_param_s32_set(name_ptr, value as c_long),
+            HalValue::U32(value) => hal_param_u32_set(name_ptr, value as c_ulong),
+            HalValue::S64(value) => hal_param_s64_set(name_ptr, value as c_long),
+            HalValue::U64(value) => hal_param_u64_set(name_ptr, value as c_ulong),
+            HalValue::Port => return Err(QueryError::Port),
+        }
+    };
+
+    match HalErrno::check(ret) {
+        Ok(_) => {
+            debug!("Set parameter {} to {:?}", name, value);
+
+            Ok(())
+        }
+        // The parameter exists and has the right type, so `EINVAL` means it is read only
+        Err(HalErrno::Invalid) => Err(QueryError::ReadOnly(name.to_string())),
+        Err(e) => Err(hal_error(&name_ffi, e)),
+    }
+}
+
+/// Set the value of a signal by its name, like `halcmd sets`
+///
+/// # Errors
+///
+/// * [`QueryError::NotFound`] - if no signal with this name exists
+/// * [`QueryError::TypeMismatch`] - if the value type does not match the signal type
+/// * [`QueryError::HasWriters`] - if a pin writes to the signal, as the value would be overwritten
+/// * [`QueryError::NotInitialised`] - if no component has been created in this process
+#[cfg(feature = "linuxcnc-2-9")]
+pub fn set_signal(name: &str, value: HalValue) -> Result<(), QueryError> {
+    let name_ffi = name_to_c(name)?;
+
+    // Hold the HAL mutex so a writer can't be linked between the check and the write
+    let _lock = snapshot::lock()?;
+
+    let (ty, data, has_writers) = raw_signal(&name_ffi)?;
+
+    if ty != value.hal_type() {
+        return Err(QueryError::TypeMismatch(name.to_string()));
+    }
+
+    if has_writers {
+        return Err(QueryError::HasWriters(name.to_string()));
+    }
+
+    unsafe { value.write(data)? };
+
+    debug!("Set signal {} to {:?}", name, value);
+
+    Ok(())
+}
+
+fn name_to_c(name: &str) -> Result<CString, QueryError> {
+    if name.len() > HAL_NAME_LEN as usize {
+        return Err(QueryError::NameLength);
+    }
+
+    CString::new(name).map_err(|e| {
+        error!("Failed to convert name to C string: {}", e);
+
+        QueryError::NameConversion
+    })
+}
+
+/// Convert an error returned by a HAL function for the resource `name`
+fn hal_error(name: &CStr, e: HalErrno) -> QueryError {
+    match e {
+        HalErrno::LockedHal => QueryError::LockedHal,
+        HalErrno::Unsupported => QueryError::Unsupported,
+        source => QueryError::Hal {
+            name: name.to_string_lossy().into_owned(),
+            source,
+        },
+    }
+}
+
+fn not_found(name: &CStr) -> QueryError {
+    QueryError::NotFound(name.to_string_lossy().into_owned())
+}
+
+/// Check the return value of one of the `hal_get_*_value_by_name` functions
+fn check_lookup(name: &CStr, ret: c_int) -> Result<(), QueryError> {
+    match HalErrno::check(ret) {
+        Ok(_) => Ok(()),
+        // LinuxCNC returns `-1` rather than an errno value if the resource doesn't exist, which
+        // reads as `EPERM`. The mock HAL returns `EINVAL`, like the rest of the HAL API.
+        Err(HalErrno::LockedHal | HalErrno::Invalid | HalErrno::NotFound) => Err(not_found(name)),
+        Err(e) => Err(hal_error(name, e)),
+    }
+}
+
+/// Get the type, data pointer and connection state of a pin
+pub(crate) fn raw_pin(name: &CString) -> Result<(HalType, *mut hal_data_u, bool), QueryError> {
+    let mut ty = 0;
+    let mut data: *mut hal_data_u = ptr::null_mut();
+    let mut connected = false;
+
+    let ret =
+        unsafe { hal_get_pin_value_by_name(name.as_ptr(), &mut ty, &mut data, &mut connected) };
+
+    check_lookup(name, ret)?;
+
+    if data.is_null() {
+        return Err(not_found(name));
+    }
+
+    HalType::from_raw(ty)
+        .map(|ty| (ty, data, connected))
+        .ok_or_else(|| not_found(name))
+}
+
+/// Get the type and data pointer of a parameter
+pub(crate) fn raw_parameter(name: &CString) -> Result<(HalType, *mut hal_data_u), QueryError> {
+    let mut ty = 0;
+    let mut data: *mut hal_data_u = ptr::null_mut();
+
+    let ret = unsafe { hal_get_param_value_by_name(name.as_ptr(), &mut ty, &mut data) };
+
+    check_lookup(name, ret)?;
+
+    if data.is_null() {
+        return Err(not_found(name));
+    }
+
+    HalType::from_raw(ty)
+        .map(|ty| (ty, data))
+        .ok_or_else(|| not_found(name))
+}
+
+/// Get the type, data pointer and writer state of a signal
+pub(crate) fn raw_signal(name: &CString) -> Result<(HalType, *mut hal_data_u, bool), QueryError> {
+    let mut ty = 0;
+    let mut data: *mut hal_data_u = ptr::null_mut();
+    let mut has_writers = false;
+
+    let ret = unsafe {
+        hal_get_signal_value_by_name(name.as_ptr(), &mut ty, &mut data, &mut has_writers)
+    };
+
+    check_lookup(name, ret)?;
+
+    if data.is_null() {
+        return Err(not_found(name));
+    }
+
+    HalType::from_raw(ty)
+        .map(|ty| (ty, data, has_writers))
+        .ok_or_else(|| not_found(name))
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        error::ResourcesError, hal_pin::OutputPin, signal::Signal, HalComponent, RegisterResources,
+        Resources,
+    };
+
+    struct Pins<'comp> {
+        output: OutputPin<'comp, f64>,
+    }
+
+    impl<'comp> Resources<'comp> for Pins<'comp> {
+        type RegisterError = ResourcesError;
+
+        fn register_resources(
+            comp: &RegisterResources<'comp>,
+        ) -> Result<Self, Self::RegisterError> {
+            Ok(Pins {
+                output: comp.register_pin("out")?,
+            })
+        }
+    }
+
+    #[test]
+    fn pins_and_signals() {
+        assert_eq!(pin("query.out"), Err(QueryError::NotInitialised));
+
+        let comp = HalComponent::new("query").unwrap();
+        let pins: Pins = comp.register().unwrap();
+
+        assert_eq!(
+            pin("query.out"),
+            Ok(PinValue {
+                value: HalValue::Float(0.0),
+                direction: PinDirection::Out,
+                connected: false,
+            })
+        );
+        assert_eq!(
+            pin("query.missing"),
+            Err(QueryError::NotFound("query.missing".to_string()))
+        );
+
+        let sig = Signal::<f64>::new(&comp, "query-signal").unwrap();
+
+        assert_eq!(set_signal("query-signal", HalValue::Float(1.5)), Ok(()));
+        assert_eq!(signal("query-signal").unwrap().value, HalValue::Float(1.5));
+        assert_eq!(
+            set_signal("query-signal", HalValue::Bit(true)),
+            Err(QueryError::TypeMismatch("query-signal".to_string()))
+        );
+
+        sig.link(&pins.output).unwrap();
+
+        assert!(pin("query.out").unwrap().connected);
+        assert_eq!(
+            set_signal("query-signal", HalValue::Float(2.0)),
+            Err(QueryError::HasWriters("query-signal".to_string()))
+        );
+        assert_eq!(
+            set_signal("query-missing", HalValue::Float(2.0)),
+            Err(QueryError::NotFound("query-missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn hal_errors() {
+        let name = CString::new("query.out").unwrap();
+
+        assert_eq!(
+            hal_error(&name, HalErrno::Memory),
+            QueryError::Hal {
+                name: "query.out".to_string(),
+                source: HalErrno::Memory
+            }
+        );
+        assert_eq!(hal_error(&name, HalErrno::LockedHal), QueryError::LockedHal);
+    }
+}
@@ -0,0 +1,466 @@
+//! Realtime component entry points
+//!
+//! Realtime components are built as a `cdylib` and loaded into LinuxCNC with `loadrt`. LinuxCNC
+//! calls `rtapi_app_main` when the component is loaded and `rtapi_app_exit` when it is unloaded.
+//! The [`rtapi_app`](crate::rtapi_app) macro generates both of these functions for a given
+//! [`Resources`] type.
+//!
+//! Multi-instance components also get `count` and `names` module parameters, so they can be
+//! loaded with e.g. `loadrt foo count=3` or `loadrt foo names=x,y,z`. See the
+//! [`instance`](crate::instance) module for how instances are named.
+//!
+//! This module is not available with the `ulapi` feature. Enable the `rtapi` feature instead, so
+//! APIs that only work in userspace components are not available in realtime code.
+
+use crate::{
+    error::{
+        ComponentInitError, Errno, FunctionExportError, HalErrno, InstanceArgsError,
+        ParameterRegisterError, PinRegisterError, QueryError, ResourcesError, SignalError,
+        SnapshotError, StorageError, StreamError, ThreadError,
+    },
+    instance::{InstanceNames, Instances},
+    HalComponent, Resources,
+};
+use linuxcnc_hal_sys::EINVAL;
+use std::{
+    cell::UnsafeCell,
+    error::Error,
+    ffi::{c_void, CStr},
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    ptr::{self, NonNull},
+    sync::Mutex,
+};
+
+/// The maximum number of instance names that can be passed with `names=`
+///
+/// This matches the limit used by components built with LinuxCNC's `halcompile`.
+pub const MAX_INSTANCES: usize = 16;
+
+/// Generate `rtapi_app_main` and `rtapi_app_exit` entry points for a realtime component
+///
+/// The generated `rtapi_app_main` creates a [`HalComponent`] with the given name, registers the
+/// [`Resources`] type with it, then calls the optional `init` function with references to both.
+/// The component and resources are kept alive in a static until LinuxCNC calls
+/// `rtapi_app_exit`, which drops them and calls [`hal_exit`](linuxcnc_hal_sys::hal_exit).
+///
+/// If component creation fails, the error is logged and `rtapi_app_main` returns the negative
+/// errno value given by [`ComponentInitError::errno`](crate::error::ComponentInitError::errno). If
+/// `init` returns one of this crate's error types, its errno value is returned in the same way.
+/// Any other `init` error or a panic returns `-EINVAL`. Panics never unwind into LinuxCNC.
+///
+/// Unix signal handlers are not registered, as the component runs inside LinuxCNC's own process.
+///
+/// The resources type must be `Send` and implement `Resources<'static>`, and the component name
+/// must match the name of the compiled library for LinuxCNC to load it. The resources must not be
+/// moved out of the component, e.g. into another static, as they would outlive it.
+///
+/// Use `instances:` instead of `resources:` to create a multi-instance component. The resources
+/// are registered once per instance requested with the `count` or `names` module parameters, and
+/// `init` is passed an `&Instances<R>`. For example, the component below could be
+/// loaded with `loadrt counter names=x,y` to get pins `x.out` and `y.out` and functions
+/// `x.count` and `y.count`:
+///
+/// ```rust,no_run
+/// # use linuxcnc_hal::{error::ResourcesError, hal_function::HalFunction, hal_function::FunctionOptions, hal_pin::OutputPin, prelude::*, rtapi_app, RegisterResources, Resources};
+/// # struct Comp { count: HalFunction }
+/// # impl Resources<'static> for Comp {
+/// #     type RegisterError = ResourcesError;
+/// #     fn register_resources(comp: &RegisterResources<'static>) -> Result<Self, Self::RegisterError> {
+/// #         let out: OutputPin<u32> = comp.register_pin("out")?;
+/// #         let count = comp.export_function("count", FunctionOptions::default(), out, |_, _| {})?;
+/// #         Ok(Comp { count })
+/// #     }
+/// # }
+/// rtapi_app! {
+///     name: "counter",
+///     instances: Comp,
+/// }
+/// ```
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use linuxcnc_hal::{
+///     error::ResourcesError, hal_function::HalFunction, hal_function::FunctionOptions,
+///     hal_pin::OutputPin, prelude::*, rtapi_app, HalComponent, RegisterResources, Resources,
+/// };
+/// use std::error::Error;
+///
+/// struct Comp {
+///     count: HalFunction,
+/// }
+///
+/// impl Resources<'static> for Comp {
+///     type RegisterError = ResourcesError;
+///
+///     fn register_resources(comp: &RegisterResources<'static>) -> Result<Self, Self::RegisterError> {
+///         let out: OutputPin<u32> = comp.register_pin("out")?;
+///
+///         let count = comp.export_function(
+///             "count",
+///             FunctionOptions::default(),
+///             (out, 0u32),
+///             |(out, count), _period| {
+///                 *count = count.wrapping_add(1);
+///
+///                 out.set_value(*count).ok();
+///             },
+///         )?;
+///
+///         Ok(Comp { count })
+///     }
+/// }
+///
+/// fn init(comp: &HalComponent, _resources: &Comp) -> Result<(), Box<dyn Error>> {
+///     rtapi_logger::init().ok();
+///
+///     log::info!("Loaded {}", comp.name());
+///
+///     Ok(())
+/// }
+///
+/// rtapi_app! {
+///     name: "counter",
+///     resources: Comp,
+///     init: init,
+/// }
+/// ```
+#[macro_export]
+macro_rules! rtapi_app {
+    (name: $name:expr, resources: $resources:ty $(,)?) => {
+        $crate::rtapi_app! {
+            name: $name,
+            resources: $resources,
+            init: |_, _| Ok(()),
+        }
+    };
+    (name: $name:expr, resources: $resources:ty, init: $init:expr $(,)?) => {
+        static __RTAPI_COMPONENT: $crate::rtapi::ComponentSlot<$resources> =
+            $crate::rtapi::ComponentSlot::new();
+
+        /// Component entry point, called by LinuxCNC on `loadrt`
+        #[no_mangle]
+        pub extern "C" fn rtapi_app_main() -> ::std::os::raw::c_int {
+            __RTAPI_COMPONENT.init($name, $init)
+        }
+
+        $crate::rtapi_app!(@exit);
+    };
+    (name: $name:expr, instances: $resources:ty $(,)?) => {
+        $crate::rtapi_app! {
+            name: $name,
+            instances: $resources,
+            init: |_, _| Ok(()),
+        }
+    };
+    (name: $name:expr, instances: $resources:ty, init: $init:expr $(,)?) => {
+        static __RTAPI_INSTANCE_PARAMS: $crate::rtapi::InstanceParams =
+            $crate::rtapi::InstanceParams::new();
+
+        // Module parameters are found and written to by LinuxCNC's `rtapi_app` before
+        // `rtapi_app_main` is called
+        #[no_mangle]
+        #[allow(non_upper_case_globals)]
+        pub static rtapi_info_address_count: $crate::rtapi::ModuleParam =
+            __RTAPI_INSTANCE_PARAMS.count_param();
+
+        #[no_mangle]
+        #[allow(non_upper_case_globals)]
+        pub static rtapi_info_type_count: $crate::rtapi::ModuleParamType =
+            $crate::rtapi::ModuleParamType::INT;
+
+        #[no_mangle]
+        #[allow(non_upper_case_globals)]
+        pub static rtapi_info_address_names: $crate::rtapi::ModuleParam =
+            __RTAPI_INSTANCE_PARAMS.names_param();
+
+        #[no_mangle]
+        #[allow(non_upper_case_globals)]
+        pub static rtapi_info_type_names: $crate::rtapi::ModuleParamType =
+            $crate::rtapi::ModuleParamType::STRING_ARRAY;
+
+        static __RTAPI_COMPONENT: $crate::rtapi::ComponentSlot<
+            $crate::instance::Instances<$resources>,
+        > = $crate::rtapi::ComponentSlot::new();
+
+        /// Component entry point, called by LinuxCNC on `loadrt`
+        #[no_mangle]
+        pub extern "C" fn rtapi_app_main() -> ::std::os::raw::c_int {
+            __RTAPI_COMPONENT.init_instances($name, &__RTAPI_INSTANCE_PARAMS, $init)
+        }
+
+        $crate::rtapi_app!(@exit);
+    };
+    (@exit) => {
+        /// Component exit point, called by LinuxCNC when the component is unloaded
+        #[no_mangle]
+        pub extern "C" fn rtapi_app_exit() {
+            // SAFETY: The resources are only ever stored in `__RTAPI_COMPONENT`
+            unsafe { __RTAPI_COMPONENT.exit() }
+        }
+    };
+}
+
+/// Storage for a realtime component between `rtapi_app_main` and `rtapi_app_exit`
+///
+/// The component is leaked for the lifetime of the loaded module, so its resources can borrow it
+/// for `'static` and export functions. It is reclaimed in [`exit`](ComponentSlot::exit) once the
+/// resources have been dropped.
+///
+/// Used by the [`rtapi_app`](crate::rtapi_app) macro.
+#[derive(Debug)]
+pub struct ComponentSlot<R> {
+    loaded: Mutex<Option<Loaded<R>>>,
+}
+
+/// A leaked component and the resources registered with it
+#[derive(Debug)]
+struct Loaded<R> {
+    resources: Option<R>,
+    component: NonNull<HalComponent>,
+}
+
+// SAFETY: The component is only accessed from `rtapi_app_main` and `rtapi_app_exit`, which
+// LinuxCNC never calls concurrently.
+unsafe impl<R: Send> Send for Loaded<R> {}
+
+impl<R> Drop for Loaded<R> {
+    fn drop(&mut self) {
+        // Resources borrow the component, so must be dropped first
+        drop(self.resources.take());
+
+        // SAFETY: The component was leaked in `ComponentSlot::init_with` and nothing borrows it
+        // any more.
+        drop(unsafe { Box::from_raw(self.component.as_ptr()) });
+    }
+}
+
+impl<R> ComponentSlot<R> {
+    /// Create an empty slot
+    pub const fn new() -> Self {
+        Self {
+            loaded: Mutex::new(None),
+        }
+    }
+
+    /// Create the component, register its resources, call `init` and store the component in
+    /// this slot
+    ///
+    /// Returns `0` on success or a negative errno value on failure.
+    pub fn init<F>(&self, name: &str, init: F) -> c_int
+    where
+        R: Resources<'static>,
+        F: FnOnce(&HalComponent, &R) -> Result<(), Box<dyn Error>>,
+    {
+        self.init_with(name, |comp| comp.register(), init)
+    }
+
+    fn init_with<C, F>(&self, name: &str, register: C, init: F) -> c_int
+    where
+        C: FnOnce(&'static HalComponent) -> Result<R, ComponentInitError>,
+        F: FnOnce(&HalComponent, &R) -> Result<(), Box<dyn Error>>,
+    {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let comp = match HalComponent::new_realtime(name) {
+                Ok(comp) => comp,
+                Err(e) => {
+                    error!("Failed to create component {}: {}", name, e);
+
+                    return e.errno();
+                }
+            };
+
+            let mut loaded = Loaded {
+                resources: None,
+                component: NonNull::from(Box::leak(Box::new(comp))),
+            };
+
+            // SAFETY: The component is only freed when `loaded` is dropped, after the resources
+            // borrowing it.
+            let comp: &'static HalComponent = unsafe { loaded.component.as_ref() };
+
+            let resources = match register(comp) {
+                Ok(resources) => loaded.resources.insert(resources),
+                Err(e) => {
+                    error!("Failed to create component {}: {}", name, e);
+
+                    return e.errno();
+                }
+            };
+
+            if let Err(e) = init(comp, resources) {
+                error!("Failed to initialise component {}: {}", name, e);
+
+                return init_errno(e.as_ref());
+            }
+
+            *self.loaded.lock().unwrap_or_else(|e| e.into_inner()) = Some(loaded);
+
+            0
+        }));
+
+        result.unwrap_or_else(|_| {
+            error!("Component {} panicked during initialisation", name);
+
+            -(EINVAL as i32)
+        })
+    }
+
+    /// Drop the stored resources and component, calling [`hal_exit`](linuxcnc_hal_sys::hal_exit)
+    ///
+    /// # Safety
+    ///
+    /// Resources registered with the component borrow it for `'static`. Neither the resources nor
+    /// references to the component may have been moved out of this slot, for example into
+    /// another static, as they would outlive the component.
+    pub unsafe fn exit(&self) {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let loaded = self.loaded.lock().unwrap_or_else(|e| e.into_inner()).take();
+
+            drop(loaded);
+        }));
+
+        if result.is_err() {
+            error!("Component panicked during exit");
+        }
+    }
+}
+
+impl<R> ComponentSlot<Instances<R>>
+where
+    R: Resources<'static>,
+{
+    /// Register instances from the given module parameters on a new component, call `init` and
+    /// store the component in this slot
+    ///
+    /// Returns `0` on success or a negative errno value on failure.
+    pub fn init_instances<F>(&self, name: &str, params: &InstanceParams, init: F) -> c_int
+    where
+        F: FnOnce(&HalComponent, &Instances<R>) -> Result<(), Box<dyn Error>>,
+    {
+        let instances = match params.instance_names() {
+            Ok(instances) => instances,
+            Err(e) => {
+                error!("Invalid instances for component {}: {}", name, e);
+
+                return e.errno();
+            }
+        };
+
+        self.init_with(name, |comp| comp.register_instances(instances), init)
+    }
+}
+
+/// Get the negative errno value for an error returned from a component's `init` function
+fn init_errno(e: &(dyn Error + 'static)) -> c_int {
+    ComponentInitError::downcast_errno(e)
+        .or_else(|| ResourcesError::downcast_errno(e))
+        .or_else(|| PinRegisterError::downcast_errno(e))
+        .or_else(|| ParameterRegisterError::downcast_errno(e))
+        .or_else(|| FunctionExportError::downcast_errno(e))
+        .or_else(|| StreamError::downcast_errno(e))
+        .or_else(|| SignalError::downcast_errno(e))
+        .or_else(|| ThreadError::downcast_errno(e))
+        .or_else(|| QueryError::downcast_errno(e))
+        .or_else(|| SnapshotError::downcast_errno(e))
+        .or_else(|| InstanceArgsError::downcast_errno(e))
+        .or_else(|| StorageError::downcast_errno(e))
+        .or_else(|| HalErrno::downcast_errno(e))
+        .unwrap_or(-(EINVAL as i32))
+}
+
+impl<R> Default for ComponentSlot<R> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Storage for the `count` and `names` module parameters of a multi-instance realtime component
+///
+/// Used by the [`rtapi_app`](crate::rtapi_app) macro.
+#[derive(Debug)]
+pub struct InstanceParams {
+    count: UnsafeCell<c_int>,
+    names: UnsafeCell<[*mut c_char; MAX_INSTANCES]>,
+}
+
+// SAFETY: The parameters are only written by LinuxCNC before `rtapi_app_main` is called, and only
+// read from `rtapi_app_main`.
+unsafe impl Sync for InstanceParams {}
+
+impl InstanceParams {
+    /// Create empty parameters
+    pub const fn new() -> Self {
+        Self {
+            count: UnsafeCell::new(0),
+            names: UnsafeCell::new([ptr::null_mut(); MAX_INSTANCES]),
+        }
+    }
+
+    /// Get the address of the `count` parameter
+    pub const fn count_param(&self) -> ModuleParam {
+        ModuleParam(self.count.get().cast())
+    }
+
+    /// Get the address of the `names` parameter
+    pub const fn names_param(&self) -> ModuleParam {
+        ModuleParam(self.names.get().cast())
+    }
+
+    /// Get the instances requested with the `count` or `names` parameters
+    fn instance_names(&self) -> Result<InstanceNames, InstanceArgsError> {
+        // SAFETY: See `Sync` impl. Non-null names are C strings allocated by LinuxCNC.
+        let (count, names) = unsafe {
+            let names = (*self.names.get())
+                .iter()
+                .take_while(|name| !name.is_null())
+                .map(|name| CStr::from_ptr(*name).to_string_lossy().into_owned())
+                .collect::<Vec<_>>();
+
+            (*self.count.get(), names)
+        };
+
+        match (count, names.is_empty()) {
+            (0, true) => Ok(InstanceNames::default()),
+            (count, true) => InstanceNames::parse_count(&count.to_string()),
+            (0, false) => InstanceNames::parse_names(names),
+            _ => Err(InstanceArgsError::Conflict),
+        }
+    }
+}
+
+impl Default for InstanceParams {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The address of a module parameter, exported as `rtapi_info_address_<name>`
+///
+/// Used by the [`rtapi_app`](crate::rtapi_app) macro.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct ModuleParam(*mut c_void);
+
+// SAFETY: The address is never dereferenced from Rust
+unsafe impl Sync for ModuleParam {}
+
+/// The type of a module parameter, exported as `rtapi_info_type_<name>`
+///
+/// Used by the [`rtapi_app`](crate::rtapi_app) macro.
+#[derive(Debug)]
+#[repr(transparent)]
+pub struct ModuleParamType(*const c_char);
+
+// SAFETY: Points to a static C string
+unsafe impl Sync for ModuleParamType {}
+
+impl ModuleParamType {
+    /// A single `int`
+    pub const INT: Self = Self(c"i".as_ptr());
+
+    /// An array of up to [`MAX_INSTANCES`] strings
+    // NOTE: The upper bound must match `MAX_INSTANCES`
+    pub const STRING_ARRAY: Self = Self(c"0-16s".as_ptr());
+}
@@ -0,0 +1,504 @@
+//! HAL signals
+//!
+//! Signals connect pins together, the same as `net` or `newsig` and `linksp` in a `.hal` file. A
+//! [`Signal`] can be created or opened by name, then linked to pins either by handle or by their
+//! full name.
+//!
+//! Signals are not owned by any component and are left in the HAL when a [`Signal`] is dropped,
+//! so a setup tool can wire up a machine and exit. Call [`Signal::delete`] to remove a signal.
+//!
+//! A [`Signal`] is created or opened with a [`HalComponent`], and borrows it so it can't be used
+//! after the component has exited. A component must also be created before [`unlink`] is used.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::{
+//!     error::PinRegisterError, hal_pin::OutputPin, signal::Signal, HalComponent,
+//!     RegisterResources, Resources,
+//! };
+//! use std::error::Error;
+//!
+//! struct Pins<'comp> {
+//!     speed: OutputPin<'comp, f64>,
+//! }
+//!
+//! impl<'comp> Resources<'comp> for Pins<'comp> {
+//!     type RegisterError = PinRegisterError;
+//!
+//!     fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+//!         Ok(Pins {
+//!             speed: comp.register_pin("speed")?,
+//!         })
+//!     }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!     let comp = HalComponent::new("setup")?;
+//!     let pins: Pins = comp.register()?;
+//!
+//!     // Equivalent to `net spindle-speed setup.speed spindle.0.speed-in`
+//!     let signal = Signal::<f64>::new(&comp, "spindle-speed")?;
+//!
+//!     signal.link(&pins.speed)?;
+//!     signal.link_by_name("spindle.0.speed-in")?;
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::ffi::{
+    hal_link, hal_signal_delete, hal_signal_new, hal_type_t, hal_type_t_HAL_BIT,
+    hal_type_t_HAL_FLOAT, hal_type_t_HAL_S32, hal_type_t_HAL_S64, hal_type_t_HAL_U32,
+    hal_type_t_HAL_U64, hal_unlink, HAL_NAME_LEN,
+};
+use crate::{
+    error::{HalErrno, QueryError, SignalError},
+    hal_pin::HalPin,
+    query, HalComponent,
+};
+#[cfg(feature = "linuxcnc-2-9")]
+use crate::{hal_pin::PinDirection, snapshot};
+use std::{
+    ffi::{CStr, CString},
+    marker::PhantomData,
+};
+
+/// A type that can be carried by a HAL signal
+///
+/// | Type   | HAL type |
+/// | ------ | -------- |
+/// | `bool` | `bit`    |
+/// | `f64`  | `float`  |
+/// | `i32`  | `s32`    |
+/// | `u32`  | `u32`    |
+/// | `i64`  | `s64`    |
+/// | `u64`  | `u64`    |
+pub trait SignalType {
+    /// The HAL type of signals and pins carrying this type
+    const HAL_TYPE: hal_type_t;
+}
+
+macro_rules! impl_signal_type {
+    ($type:ty, $hal_type:expr) => {
+        impl SignalType for $type {
+            const HAL_TYPE: hal_type_t = $hal_type;
+        }
+    };
+}
+
+impl_signal_type!(bool, hal_type_t_HAL_BIT);
+impl_signal_type!(f64, hal_type_t_HAL_FLOAT);
+impl_signal_type!(i32, hal_type_t_HAL_S32);
+impl_signal_type!(u32, hal_type_t_HAL_U32);
+impl_signal_type!(i64, hal_type_t_HAL_S64);
+impl_signal_type!(u64, hal_type_t_HAL_U64);
+
+/// A typed HAL signal
+///
+/// The signal borrows the component it was created or opened with for `'comp`.
+#[derive(Debug)]
+pub struct Signal<'comp, T> {
+    name: String,
+    _type: PhantomData<fn() -> T>,
+    _component: PhantomData<&'comp ()>,
+}
+
+impl<'comp, T> Signal<'comp, T>
+where
+    T: SignalType,
+{
+    /// Create a new signal
+    ///
+    /// # Errors
+    ///
+    /// * [`SignalError::AlreadyExists`] - if a signal with this name already exists
+    /// * [`SignalError::Hal`] - with [`HalErrno::LockedHal`] if the HAL is locked, or
+    ///   [`HalErrno::Memory`] if there is not enough shared memory to create the signal
+    pub fn new(_comp: &'comp HalComponent, name: impl Into<String>) -> Result<Self, SignalError> {
+        let name = name.into();
+        let name_ffi = name_to_c(&name)?;
+
+        let ret = unsafe { hal_signal_new(name_ffi.as_ptr(), T::HAL_TYPE) };
+
+        match HalErrno::check(ret) {
+            Ok(_) => {
+                debug!("Created signal {}", name);
+
+                Ok(Self {
+                    name,
+                    _type: PhantomData,
+                    _component: PhantomData,
+                })
+            }
+            Err(HalErrno::Invalid) if signal_info(&name_ffi).is_ok() => {
+                Err(SignalError::AlreadyExists(name))
+            }
+            Err(source) => Err(SignalError::Hal { name, source }),
+        }
+    }
+
+    /// Open an existing signal
+    ///
+    /// # Errors
+    ///
+    /// * [`SignalError::SignalNotFound`] - if no signal with this name exists
+    /// * [`SignalError::TypeMismatch`] - if the signal does not carry values of type `T`
+    pub fn open(_comp: &'comp HalComponent, name: impl Into<String>) -> Result<Self, SignalError> {
+        let name = name.into();
+        let name_ffi = name_to_c(&name)?;
+
+        match signal_info(&name_ffi) {
+            Ok((ty, _)) if ty == T::HAL_TYPE => Ok(Self {
+                name,
+                _type: PhantomData,
+                _component: PhantomData,
+            }),
+            Ok(_) => Err(SignalError::TypeMismatch(name)),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the signal name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Whether a pin that writes to this signal is linked to it
+    pub fn has_writers(&self) -> Result<bool, SignalError> {
+        let name_ffi = name_to_c(&self.name)?;
+
+        signal_info(&name_ffi).map(|(_, has_writers)| has_writers)
+    }
+
+    /// Link a pin owned by this process to this signal
+    pub fn link<'p, P>(&self, pin: &P) -> Result<(), SignalError>
+    where
+        P: HalPin<'p, Storage = T>,
+    {
+        self.link_by_name(pin.name())
+    }
+
+    /// Link a pin to this signal by its full name, e.g. `spindle.0.speed-in`
+    ///
+    /// # Errors
+    ///
+    /// * [`SignalError::PinNotFound`] - if no pin with this name exists
+    /// * [`SignalError::SignalNotFound`] - if this signal has been deleted
+    /// * [`SignalError::TypeMismatch`] - if the pin type does not match the signal type
+    /// * [`SignalError::AlreadyLinked`] - if the pin is linked to another signal
+    /// * [`SignalError::MultipleWriters`] - if the pin is an output or bidirectional pin, and the
+    ///   signal already has a pin writing to it
+    /// * [`SignalError::Hal`] - with [`HalErrno::LockedHal`] if the HAL is locked
+    pub fn link_by_name(&self, pin_name: &str) -> Result<(), SignalError> {
+        let pin_ffi = name_to_c(pin_name)?;
+        let signal_ffi = name_to_c(&self.name)?;
+
+        let ret = unsafe { hal_link(pin_ffi.as_ptr(), signal_ffi.as_ptr()) };
+
+        match HalErrno::check(ret) {
+            Ok(_) => {
+                debug!("Linked pin {} to signal {}", pin_name, self.name);
+
+                Ok(())
+            }
+            Err(HalErrno::Invalid) => Err(link_error(&pin_ffi, &signal_ffi)),
+            Err(source) => Err(SignalError::Hal {
+                name: self.name.clone(),
+                source,
+            }),
+        }
+    }
+
+    /// Unlink a pin owned by this process from this signal
+    pub fn unlink<'p, P>(&self, pin: &P) -> Result<(), SignalError>
+    where
+        P: HalPin<'p, Storage = T>,
+    {
+        self.unlink_by_name(pin.name())
+    }
+
+    /// Unlink a pin from this signal by its full name, e.g. `spindle.0.speed-in`
+    ///
+    /// Use [`unlink`] to unlink a pin from whatever signal it is linked to.
+    ///
+    /// # Errors
+    ///
+    /// * [`SignalError::PinNotFound`] - if no pin with this name exists
+    /// * [`SignalError::SignalNotFound`] - if this signal has been deleted
+    /// * [`SignalError::NotLinked`] - if the pin is not linked to this signal
+    /// * [`SignalError::Hal`] - with [`HalErrno::LockedHal`] if the HAL is locked
+    pub fn unlink_by_name(&self, pin_name: &str) -> Result<(), SignalError> {
+        let pin_ffi = name_to_c(pin_name)?;
+        let signal_ffi = name_to_c(&self.name)?;
+
+        // A linked pin's data pointer points to the value of its signal
+        let (_, pin_data, connected) =
+            query::raw_pin(&pin_ffi).map_err(|e| pin_error(&pin_ffi, e))?;
+        let (_, signal_data, _) =
+            query::raw_signal(&signal_ffi).map_err(|e| signal_error(&signal_ffi, e))?;
+
+        if !connected || pin_data != signal_data {
+            return Err(SignalError::NotLinked {
+                pin: pin_name.to_string(),
+                signal: self.name.clone(),
+            });
+        }
+
+        unlink(pin_name)
+    }
+
+    /// Delete the signal, unlinking any pins connected to it
+    ///
+    /// # Errors
+    ///
+    /// * [`SignalError::SignalNotFound`] - if the signal has already been deleted
+    /// * [`SignalError::Hal`] - with [`HalErrno::LockedHal`] if the HAL is locked
+    pub fn delete(self) -> Result<(), SignalError> {
+        let name_ffi = name_to_c(&self.name)?;
+
+        let ret = unsafe { hal_signal_delete(name_ffi.as_ptr()) };
+
+        match HalErrno::check(ret) {
+            Ok(_) => {
+                debug!("Deleted signal {}", self.name);
+
+                Ok(())
+            }
+            Err(HalErrno::Invalid) => Err(SignalError::SignalNotFound(self.name)),
+            Err(source) => Err(SignalError::Hal {
+                name: self.name,
+                source,
+            }),
+        }
+    }
+}
+
+/// Unlink a pin from whatever signal it is linked to by its full name, e.g. `spindle.0.speed-in`
+///
+/// Unlinking a pin that isn't linked to a signal does nothing.
+///
+/// # Errors
+///
+/// * [`SignalError::PinNotFound`] - if no pin with this name exists
+/// * [`SignalError::Hal`] - with [`HalErrno::LockedHal`] if the HAL is locked
+pub fn unlink(pin_name: &str) -> Result<(), SignalError> {
+    let pin_ffi = name_to_c(pin_name)?;
+
+    let ret = unsafe { hal_unlink(pin_ffi.as_ptr()) };
+
+    match HalErrno::check(ret) {
+        Ok(_) => {
+            debug!("Unlinked pin {}", pin_name);
+
+            Ok(())
+        }
+        Err(HalErrno::Invalid) => Err(SignalError::PinNotFound(pin_name.to_string())),
+        Err(source) => Err(SignalError::Hal {
+            name: pin_name.to_string(),
+            source,
+        }),
+    }
+}
+
+fn name_to_c(name: &str) -> Result<CString, SignalError> {
+    if name.len() > HAL_NAME_LEN as usize {
+        return Err(SignalError::NameLength(name.to_string()));
+    }
+
+    CString::new(name).map_err(|e| {
+        error!("Failed to convert name to C string: {}", e);
+
+        SignalError::NameConversion(name.to_string())
+    })
+}
+
+/// Get the type of a signal and whether it has a writer
+fn signal_info(name: &CString) -> Result<(hal_type_t, bool), SignalError> {
+    query::raw_signal(name)
+        .map(|(ty, _, has_writers)| (ty.as_raw(), has_writers))
+        .map_err(|e| signal_error(name, e))
+}
+
+/// Get the type of a pin and whether it is linked to a signal
+fn pin_info(name: &CString) -> Result<(hal_type_t, bool), SignalError> {
+    query::raw_pin(name)
+        .map(|(ty, _, connected)| (ty.as_raw(), connected))
+        .map_err(|e| pin_error(name, e))
+}
+
+fn signal_error(name: &CStr, e: QueryError) -> SignalError {
+    let name = name.to_string_lossy().into_owned();
+
+    match e {
+        QueryError::Unsupported => SignalError::Hal {
+            name,
+            source: HalErrno::Unsupported,
+        },
+        _ => SignalError::SignalNotFound(name),
+    }
+}
+
+fn pin_error(name: &CStr, e: QueryError) -> SignalError {
+    let name = name.to_string_lossy().into_owned();
+
+    match e {
+        QueryError::Unsupported => SignalError::Hal {
+            name,
+            source: HalErrno::Unsupported,
+        },
+        _ => SignalError::PinNotFound(name),
+    }
+}
+
+/// Work out why [`hal_link`] returned `EINVAL`, as it is used for every kind of link failure
+fn link_error(pin_name: &CString, signal_name: &CString) -> SignalError {
+    match (pin_info(pin_name), signal_info(signal_name)) {
+        (Err(e), _) | (_, Err(e)) => e,
+        (Ok((pin_type, _)), Ok((signal_type, _))) if pin_type != signal_type => {
+            SignalError::TypeMismatch(signal_name.to_string_lossy().into_owned())
+        }
+        (Ok((_, true)), _) => SignalError::AlreadyLinked(pin_name.to_string_lossy().into_owned()),
+        _ if writer_conflict(pin_name, signal_name) => {
+            SignalError::MultipleWriters(signal_name.to_string_lossy().into_owned())
+        }
+        _ => SignalError::Hal {
+            name: signal_name.to_string_lossy().into_owned(),
+            source: HalErrno::Invalid,
+        },
+    }
+}
+
+/// Whether linking the pin would give the signal more than one writer
+///
+/// An output pin can't be linked to a signal with any output or bidirectional pins, and a
+/// bidirectional pin can't be linked to a signal with an output pin. Pin directions are only
+/// available in HAL shared memory, so this returns `false` if the pin or signal can't be found
+/// there.
+#[cfg(feature = "linuxcnc-2-9")]
+fn writer_conflict(pin_name: &CString, signal_name: &CString) -> bool {
+    let (Ok(pin_name), Ok(signal_name)) = (pin_name.to_str(), signal_name.to_str()) else {
+        return false;
+    };
+
+    match (
+        snapshot::find_pin(pin_name),
+        snapshot::find_signal(signal_name),
+    ) {
+        (Ok(Some(pin)), Ok(Some(signal))) => match pin.direction {
+            PinDirection::Out => signal.writers > 0 || signal.bidirs > 0,
+            PinDirection::Bidirectional => signal.writers > 0,
+            PinDirection::In => false,
+        },
+        _ => false,
+    }
+}
+
+/// Whether linking the pin would give the signal more than one writer
+///
+/// The layout of HAL shared memory is only known for LinuxCNC 2.9, so this is always `false`
+/// without the `linuxcnc-2-9` feature.
+#[cfg(not(feature = "linuxcnc-2-9"))]
+fn writer_conflict(_pin_name: &CString, _signal_name: &CString) -> bool {
+    false
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        error::ResourcesError,
+        hal_pin::{BidirectionalPin, InputPin, OutputPin},
+        RegisterResources, Resources,
+    };
+
+    struct Pins<'comp> {
+        input: InputPin<'comp, u32>,
+        output: OutputPin<'comp, u32>,
+        other_output: OutputPin<'comp, u32>,
+        bidir: BidirectionalPin<'comp, u32>,
+        other_bidir: BidirectionalPin<'comp, u32>,
+    }
+
+    impl<'comp> Resources<'comp> for Pins<'comp> {
+        type RegisterError = ResourcesError;
+
+        fn register_resources(
+            comp: &RegisterResources<'comp>,
+        ) -> Result<Self, Self::RegisterError> {
+            Ok(Pins {
+                input: comp.register_pin("in")?,
+                output: comp.register_pin("out")?,
+                other_output: comp.register_pin("other-out")?,
+                bidir: comp.register_pin("io")?,
+                other_bidir: comp.register_pin("other-io")?,
+            })
+        }
+    }
+
+    #[test]
+    fn unlink_other_signal() {
+        let comp = HalComponent::new("unlink").unwrap();
+        let pins: Pins = comp.register().unwrap();
+
+        let signal = Signal::<u32>::new(&comp, "unlink-signal").unwrap();
+        let other = Signal::<u32>::new(&comp, "unlink-other").unwrap();
+
+        assert_eq!(
+            signal.unlink(&pins.input),
+            Err(SignalError::NotLinked {
+                pin: "unlink.in".to_string(),
+                signal: "unlink-signal".to_string()
+            })
+        );
+
+        signal.link(&pins.input).unwrap();
+
+        assert_eq!(
+            other.unlink(&pins.input),
+            Err(SignalError::NotLinked {
+                pin: "unlink.in".to_string(),
+                signal: "unlink-other".to_string()
+            })
+        );
+        assert!(query::pin("unlink.in").unwrap().connected);
+
+        assert_eq!(signal.unlink(&pins.input), Ok(()));
+        assert!(!query::pin("unlink.in").unwrap().connected);
+
+        assert_eq!(
+            signal.unlink_by_name("unlink.missing"),
+            Err(SignalError::PinNotFound("unlink.missing".to_string()))
+        );
+    }
+
+    #[test]
+    fn multiple_writers() {
+        let comp = HalComponent::new("writers").unwrap();
+        let pins: Pins = comp.register().unwrap();
+
+        let output = Signal::<u32>::new(&comp, "writers-out").unwrap();
+
+        output.link(&pins.output).unwrap();
+
+        let conflict = Err(SignalError::MultipleWriters("writers-out".to_string()));
+
+        assert_eq!(output.link(&pins.other_output), conflict);
+        assert_eq!(output.link(&pins.bidir), conflict);
+        assert_eq!(output.link(&pins.input), Ok(()));
+
+        let bidir = Signal::<u32>::new(&comp, "writers-io").unwrap();
+
+        bidir.link(&pins.bidir).unwrap();
+
+        assert_eq!(
+            bidir.link(&pins.other_output),
+            Err(SignalError::MultipleWriters("writers-io".to_string()))
+        );
+        assert_eq!(
+            bidir.link(&pins.other_output).unwrap_err().to_string(),
+            "signal `writers-io` already has a writer"
+        );
+        assert_eq!(bidir.link(&pins.other_bidir), Ok(()));
+    }
+}
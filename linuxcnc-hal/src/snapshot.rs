@@ -0,0 +1,780 @@
+//! HAL shared memory introspection
+//!
+//! [`HalSnapshot::capture`] walks every list in HAL shared memory while holding the HAL mutex,
+//! copying out the components, pins, parameters, signals, functions and threads currently loaded.
+//! This is the same information shown by `halcmd show all`.
+//!
+//! A snapshot is a plain value. It can be compared with a later snapshot to find changes,
+//! displayed in a UI or, with the `serde` feature enabled, serialized.
+//!
+//! A [`HalComponent`](crate::HalComponent) must be created before a snapshot can be captured.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::{snapshot::HalSnapshot, HalComponent, RegisterResources, Resources};
+//! use std::error::Error;
+//!
+//! struct NoResources;
+//!
+//! impl<'comp> Resources<'comp> for NoResources {
+//!     type RegisterError = linuxcnc_hal::error::ResourcesError;
+//!
+//!     fn register_resources(_comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+//!         Ok(NoResources)
+//!     }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!     let comp = HalComponent::new("inspect")?;
+//!     let _resources: NoResources = comp.register()?;
+//!
+//!     let snapshot = HalSnapshot::capture()?;
+//!
+//!     for pin in &snapshot.pins {
+//!         println!(
+//!             "{} {:?} {:?} = {:?} (signal {:?})",
+//!             pin.owner, pin.direction, pin.hal_type, pin.value, pin.signal
+//!         );
+//!     }
+//!
+//!     for thread in &snapshot.threads {
+//!         println!("{} every {:?}: {:?}", thread.name, thread.period, thread.functions);
+//!     }
+//!
+//!     Ok(())
+//! }
+//! ```
+
+use crate::ffi::{
+    hal_comp_t, hal_data_t, hal_data_u, hal_funct_entry_t, hal_funct_t, hal_list_t, hal_oldname_t,
+    hal_param_dir_t_HAL_RO, hal_param_t, hal_pin_dir_t_HAL_IN, hal_pin_dir_t_HAL_IO,
+    hal_pin_dir_t_HAL_OUT, hal_pin_t, hal_shmem, hal_sig_t, hal_thread_t, rtapi_mutex_get,
+    rtapi_mutex_give,
+};
+use crate::{
+    error::SnapshotError,
+    hal_parameter::ParameterPermissions,
+    hal_pin::PinDirection,
+    query::{HalType, HalValue},
+};
+use std::{
+    ffi::CStr,
+    os::raw::{c_char, c_ulong},
+    ptr,
+    time::Duration,
+};
+
+/// The kind of a HAL component
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ComponentKind {
+    /// A userspace component loaded with `loadusr`
+    Userspace,
+
+    /// A realtime component loaded with `loadrt`
+    Realtime,
+
+    /// Any other kind of component
+    Other,
+}
+
+/// A HAL component
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ComponentInfo {
+    /// Component ID
+    pub id: i32,
+
+    /// Component name
+    pub name: String,
+
+    /// Whether the component is a userspace or realtime component
+    pub kind: ComponentKind,
+
+    /// Whether the component has called `hal_ready`
+    pub ready: bool,
+
+    /// Process ID of userspace components, or `0` for realtime components
+    pub pid: i32,
+}
+
+/// A HAL pin
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PinInfo {
+    /// Full pin name
+    pub name: String,
+
+    /// Name of the component that owns the pin
+    pub owner: String,
+
+    /// Pin type
+    pub hal_type: HalType,
+
+    /// Pin direction
+    pub direction: PinDirection,
+
+    /// Current value of the pin, or of the signal it is linked to
+    pub value: HalValue,
+
+    /// Name of the signal the pin is linked to, if any
+    pub signal: Option<String>,
+}
+
+/// A HAL parameter
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParameterInfo {
+    /// Full parameter name
+    pub name: String,
+
+    /// Name of the component that owns the parameter
+    pub owner: String,
+
+    /// Parameter type
+    pub hal_type: HalType,
+
+    /// Whether the parameter can be written by LinuxCNC
+    pub permissions: ParameterPermissions,
+
+    /// Current value of the parameter
+    pub value: HalValue,
+}
+
+/// A HAL signal
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SignalInfo {
+    /// Signal name
+    pub name: String,
+
+    /// Signal type
+    pub hal_type: HalType,
+
+    /// Current value of the signal
+    pub value: HalValue,
+
+    /// Number of input pins linked to the signal
+    pub readers: u32,
+
+    /// Number of output pins linked to the signal
+    pub writers: u32,
+
+    /// Number of bidirectional pins linked to the signal
+    pub bidirs: u32,
+}
+
+/// A function exported to the HAL
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FunctionInfo {
+    /// Full function name
+    pub name: String,
+
+    /// Name of the component that exported the function
+    pub owner: String,
+
+    /// Whether the function uses floating point operations
+    pub uses_fp: bool,
+
+    /// Whether the function is reentrant
+    pub reentrant: bool,
+
+    /// Number of threads the function has been added to
+    pub users: u32,
+}
+
+/// A realtime HAL thread
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ThreadInfo {
+    /// Thread name
+    pub name: String,
+
+    /// Thread period
+    pub period: Duration,
+
+    /// Thread priority
+    pub priority: i32,
+
+    /// Whether functions in the thread may use floating point operations
+    pub uses_fp: bool,
+
+    /// Names of the functions run by the thread, in the order they are called
+    pub functions: Vec<String>,
+}
+
+/// A copy of everything in HAL shared memory at one point in time
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HalSnapshot {
+    /// Every loaded component
+    pub components: Vec<ComponentInfo>,
+
+    /// Every pin of every component
+    pub pins: Vec<PinInfo>,
+
+    /// Every parameter of every component
+    pub parameters: Vec<ParameterInfo>,
+
+    /// Every signal
+    pub signals: Vec<SignalInfo>,
+
+    /// Every exported function
+    pub functions: Vec<FunctionInfo>,
+
+    /// Every realtime thread
+    pub threads: Vec<ThreadInfo>,
+}
+
+impl HalSnapshot {
+    /// Capture the current state of the HAL
+    ///
+    /// The HAL mutex is held while the snapshot is captured, so this should not be called from a
+    /// realtime thread.
+    ///
+    /// # Errors
+    ///
+    /// * [`SnapshotError::NotInitialised`] - if no component has been created in this process
+    pub fn capture() -> Result<Self, SnapshotError> {
+        let shmem = Shmem::get()?;
+
+        let _lock = shmem.lock();
+
+        // SAFETY: The HAL mutex is held, so no other process can modify the lists while they are
+        // being walked.
+        let snapshot = unsafe {
+            Self {
+                components: shmem.components(),
+                pins: shmem.pins(),
+                parameters: shmem.parameters(),
+                signals: shmem.signals(),
+                functions: shmem.functions(),
+                threads: shmem.threads(),
+            }
+        };
+
+        Ok(snapshot)
+    }
+
+    /// Get a component by name
+    pub fn component(&self, name: &str) -> Option<&ComponentInfo> {
+        self.components.iter().find(|c| c.name == name)
+    }
+
+    /// Get a pin by its full name
+    pub fn pin(&self, name: &str) -> Option<&PinInfo> {
+        self.pins.iter().find(|p| p.name == name)
+    }
+
+    /// Get a parameter by its full name
+    pub fn parameter(&self, name: &str) -> Option<&ParameterInfo> {
+        self.parameters.iter().find(|p| p.name == name)
+    }
+
+    /// Get a signal by name
+    pub fn signal(&self, name: &str) -> Option<&SignalInfo> {
+        self.signals.iter().find(|s| s.name == name)
+    }
+
+    /// Get an iterator over the pins owned by the given component
+    pub fn component_pins<'a>(&'a self, component: &'a str) -> impl Iterator<Item = &'a PinInfo> {
+        self.pins.iter().filter(move |p| p.owner == component)
+    }
+
+    /// Get an iterator over the pins linked to the given signal
+    pub fn signal_pins<'a>(&'a self, signal: &'a str) -> impl Iterator<Item = &'a PinInfo> {
+        self.pins
+            .iter()
+            .filter(move |p| p.signal.as_deref() == Some(signal))
+    }
+}
+
+/// Take the HAL mutex, which is released when the returned guard is dropped
+///
+/// HAL functions that take the mutex themselves must not be called while it is held.
+pub(crate) fn lock() -> Result<ShmemLock, SnapshotError> {
+    Ok(Shmem::get()?.lock())
+}
+
+/// Look up a single pin by its full name or alias without capturing a whole snapshot
+pub(crate) fn find_pin(pin_name: &str) -> Result<Option<PinInfo>, SnapshotError> {
+    find(
+        |data| data.pin_list_ptr,
+        |pin: &hal_pin_t| pin.next_ptr,
+        |shmem, pin| unsafe { shmem.has_name(&pin.name, pin.oldname, pin_name) },
+        |shmem, pin| unsafe { shmem.pin(pin) },
+    )
+}
+
+/// Look up a single parameter by its full name or alias without capturing a whole snapshot
+pub(crate) fn find_parameter(param_name: &str) -> Result<Option<ParameterInfo>, SnapshotError> {
+    find(
+        |data| data.param_list_ptr,
+        |param: &hal_param_t| param.next_ptr,
+        |shmem, param| unsafe { shmem.has_name(&param.name, param.oldname, param_name) },
+        |shmem, param| unsafe { shmem.parameter(param) },
+    )
+}
+
+/// Look up a single signal by name without capturing a whole snapshot
+pub(crate) fn find_signal(signal_name: &str) -> Result<Option<SignalInfo>, SnapshotError> {
+    find(
+        |data| data.sig_list_ptr,
+        |signal: &hal_sig_t| signal.next_ptr,
+        |_, signal| name(&signal.name) == signal_name,
+        |shmem, signal| unsafe { shmem.signal(signal) },
+    )
+}
+
+/// Look up a single exported function by its full name without capturing a whole snapshot
+pub(crate) fn find_function(funct_name: &str) -> Result<Option<FunctionInfo>, SnapshotError> {
+    find(
+        |data| data.funct_list_ptr,
+        |funct: &hal_funct_t| funct.next_ptr,
+        |_, funct| name(&funct.name) == funct_name,
+        |shmem, funct| Some(unsafe { shmem.function(funct) }),
+    )
+}
+
+/// Look up a single thread by name without capturing a whole snapshot
+pub(crate) fn find_thread(thread_name: &str) -> Result<Option<ThreadInfo>, SnapshotError> {
+    find(
+        |data| data.thread_list_ptr,
+        |thread: &hal_thread_t| thread.next_ptr,
+        |_, thread| name(&thread.name) == thread_name,
+        |shmem, thread| Some(unsafe { shmem.thread(thread) }),
+    )
+}
+
+/// Find the first `T` in a list that matches `filter` while holding the HAL mutex
+fn find<T, I>(
+    head: fn(&hal_data_t) -> i32,
+    next: fn(&T) -> i32,
+    filter: impl Fn(&Shmem, &T) -> bool,
+    info: impl Fn(&Shmem, &T) -> Option<I>,
+) -> Result<Option<I>, SnapshotError> {
+    let shmem = Shmem::get()?;
+
+    let _lock = shmem.lock();
+
+    // SAFETY: The HAL mutex is held, see [`HalSnapshot::capture`]
+    let item = unsafe {
+        shmem
+            .walk::<T>(head(&*shmem.data), next)
+            .into_iter()
+            .find(|item| filter(&shmem, item))
+            .and_then(|item| info(&shmem, item))
+    };
+
+    Ok(item)
+}
+
+/// Access to HAL shared memory
+struct Shmem {
+    base: *mut c_char,
+    data: *mut hal_data_t,
+}
+
+/// Releases the HAL mutex when dropped
+pub(crate) struct ShmemLock {
+    mutex: *mut c_ulong,
+}
+
+impl Drop for ShmemLock {
+    fn drop(&mut self) {
+        // SAFETY: The mutex was taken in `Shmem::lock`, and HAL shared memory stays mapped until
+        // the process exits
+        unsafe { rtapi_mutex_give(self.mutex) };
+    }
+}
+
+impl Shmem {
+    fn get() -> Result<Self, SnapshotError> {
+        let (base, data) = hal_shmem();
+
+        if base.is_null() || data.is_null() {
+            return Err(SnapshotError::NotInitialised);
+        }
+
+        Ok(Self { base, data })
+    }
+
+    /// Take the HAL mutex
+    fn lock(&self) -> ShmemLock {
+        // SAFETY: `hal_data` points to the start of HAL shared memory, see `Shmem::get`
+        let mutex = unsafe { ptr::addr_of_mut!((*self.data).mutex) };
+
+        unsafe { rtapi_mutex_get(mutex) };
+
+        ShmemLock { mutex }
+    }
+
+    /// Convert a shared memory offset into a pointer, equivalent to the `SHMPTR` macro
+    unsafe fn ptr<T>(&self, offset: i32) -> *mut T {
+        self.base.offset(offset as isize).cast()
+    }
+
+    /// Convert a pointer into a shared memory offset, equivalent to the `SHMOFF` macro
+    fn offset<T>(&self, ptr: *const T) -> i32 {
+        (ptr as usize - self.base as usize) as i32
+    }
+
+    /// Walk a singly linked list of `T`s starting at `offset`
+    unsafe fn walk<T>(&self, mut offset: i32, next: fn(&T) -> i32) -> Vec<&T> {
+        let mut items = Vec::new();
+
+        while offset != 0 {
+            let item = &*self.ptr::<T>(offset);
+
+            items.push(item);
+
+            offset = next(item);
+        }
+
+        items
+    }
+
+    /// Whether a pin or parameter has the given name, either as its current name or as the
+    /// original name it had before an alias was set, like `halpr_find_pin_by_name`
+    unsafe fn has_name(&self, current: &[c_char], oldname: i32, wanted: &str) -> bool {
+        name(current) == wanted
+            || (oldname != 0 && name(&(*self.ptr::<hal_oldname_t>(oldname)).name) == wanted)
+    }
+
+    /// Get the name of the component at `offset`
+    unsafe fn component_name(&self, offset: i32) -> String {
+        if offset == 0 {
+            return String::new();
+        }
+
+        name(&(*self.ptr::<hal_comp_t>(offset)).name)
+    }
+
+    unsafe fn components(&self) -> Vec<ComponentInfo> {
+        self.walk::<hal_comp_t>((*self.data).comp_list_ptr, |c| c.next_ptr)
+            .into_iter()
+            .map(|comp| ComponentInfo {
+                id: comp.comp_id,
+                name: name(&comp.name),
+                kind: match comp.type_ {
+                    0 => ComponentKind::Userspace,
+                    1 => ComponentKind::Realtime,
+                    _ => ComponentKind::Other,
+                },
+                ready: comp.ready != 0,
+                pid: comp.pid,
+            })
+            .collect()
+    }
+
+    unsafe fn pins(&self) -> Vec<PinInfo> {
+        self.walk::<hal_pin_t>((*self.data).pin_list_ptr, |p| p.next_ptr)
+            .into_iter()
+            .filter_map(|pin| self.pin(pin))
+            .collect()
+    }
+
+    unsafe fn pin(&self, pin: &hal_pin_t) -> Option<PinInfo> {
+        let hal_type = HalType::from_raw(pin.type_)?;
+
+        // Linked pins read from the signal, unlinked pins from their dummy signal
+        let (data, signal) = if pin.signal != 0 {
+            let signal = &*self.ptr::<hal_sig_t>(pin.signal);
+
+            (
+                self.ptr::<hal_data_u>(signal.data_ptr) as *const hal_data_u,
+                Some(name(&signal.name)),
+            )
+        } else {
+            (&pin.dummysig as *const hal_data_u, None)
+        };
+
+        let direction = match pin.dir {
+            x if x == hal_pin_dir_t_HAL_IN => PinDirection::In,
+            x if x == hal_pin_dir_t_HAL_OUT => PinDirection::Out,
+            x if x == hal_pin_dir_t_HAL_IO => PinDirection::Bidirectional,
+            _ => return None,
+        };
+
+        Some(PinInfo {
+            name: name(&pin.name),
+            owner: self.component_name(pin.owner_ptr),
+            hal_type,
+            direction,
+            value: HalValue::read(hal_type, data),
+            signal,
+        })
+    }
+
+    unsafe fn parameters(&self) -> Vec<ParameterInfo> {
+        self.walk::<hal_param_t>((*self.data).param_list_ptr, |p| p.next_ptr)
+            .into_iter()
+            .filter_map(|param| self.parameter(param))
+            .collect()
+    }
+
+    unsafe fn parameter(&self, param: &hal_param_t) -> Option<ParameterInfo> {
+        let hal_type = HalType::from_raw(param.type_)?;
+
+        Some(ParameterInfo {
+            name: name(&param.name),
+            owner: self.component_name(param.owner_ptr),
+            hal_type,
+            permissions: if param.dir == hal_param_dir_t_HAL_RO {
+                ParameterPermissions::ReadOnly
+            } else {
+                ParameterPermissions::ReadWrite
+            },
+            value: HalValue::read(hal_type, self.ptr(param.data_ptr)),
+        })
+    }
+
+    unsafe fn signals(&self) -> Vec<SignalInfo> {
+        self.walk::<hal_sig_t>((*self.data).sig_list_ptr, |s| s.next_ptr)
+            .into_iter()
+            .filter_map(|signal| self.signal(signal))
+            .collect()
+    }
+
+    unsafe fn signal(&self, signal: &hal_sig_t) -> Option<SignalInfo> {
+        let hal_type = HalType::from_raw(signal.type_)?;
+
+        Some(SignalInfo {
+            name: name(&signal.name),
+            hal_type,
+            value: HalValue::read(hal_type, self.ptr(signal.data_ptr)),
+            readers: signal.readers.max(0) as u32,
+            writers: signal.writers.max(0) as u32,
+            bidirs: signal.bidirs.max(0) as u32,
+        })
+    }
+
+    unsafe fn functions(&self) -> Vec<FunctionInfo> {
+        self.walk::<hal_funct_t>((*self.data).funct_list_ptr, |f| f.next_ptr)
+            .into_iter()
+            .map(|funct| self.function(funct))
+            .collect()
+    }
+
+    unsafe fn function(&self, funct: &hal_funct_t) -> FunctionInfo {
+        FunctionInfo {
+            name: name(&funct.name),
+            owner: self.component_name(funct.owner_ptr),
+            uses_fp: funct.uses_fp != 0,
+            reentrant: funct.reentrant != 0,
+            users: funct.users.max(0) as u32,
+        }
+    }
+
+    unsafe fn threads(&self) -> Vec<ThreadInfo> {
+        self.walk::<hal_thread_t>((*self.data).thread_list_ptr, |t| t.next_ptr)
+            .into_iter()
+            .map(|thread| self.thread(thread))
+            .collect()
+    }
+
+    unsafe fn thread(&self, thread: &hal_thread_t) -> ThreadInfo {
+        ThreadInfo {
+            name: name(&thread.name),
+            period: Duration::from_nanos(thread.period.max(0) as u64),
+            priority: thread.priority,
+            uses_fp: thread.uses_fp != 0,
+            functions: self.thread_functions(&thread.funct_list),
+        }
+    }
+
+    /// Walk the circular list of function entries that starts and ends at `head`
+    unsafe fn thread_functions(&self, head: &hal_list_t) -> Vec<String> {
+        let head_offset = self.offset(head);
+        let mut functions = Vec::new();
+        let mut offset = head.next;
+
+        while offset != head_offset && offset != 0 {
+            let entry = &*self.ptr::<hal_funct_entry_t>(offset);
+
+            functions.push(name(&(*self.ptr::<hal_funct_t>(entry.funct_ptr)).name));
+
+            offset = entry.links.next;
+        }
+
+        functions
+    }
+}
+
+/// Convert a fixed size, nul terminated name into a `String`
+fn name(name: &[c_char]) -> String {
+    let bytes = unsafe { &*(name as *const [c_char] as *const [u8]) };
+
+    CStr::from_bytes_until_nul(bytes)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| String::from_utf8_lossy(bytes).into_owned())
+}
+
+#[cfg(all(test, feature = "mock"))]
+mod tests {
+    use super::*;
+    use crate::{
+        error::ResourcesError,
+        hal_pin::{InputPin, OutputPin},
+        prelude::*,
+        signal::Signal,
+        HalComponent, Parameter, RegisterResources, Resources,
+    };
+
+    struct Pins<'comp> {
+        input: InputPin<'comp, f64>,
+        output: OutputPin<'comp, u32>,
+        _scale: Parameter<'comp, f64>,
+    }
+
+    impl<'comp> Resources<'comp> for Pins<'comp> {
+        type RegisterError = ResourcesError;
+
+        fn register_resources(
+            comp: &RegisterResources<'comp>,
+        ) -> Result<Self, Self::RegisterError> {
+            Ok(Pins {
+                input: comp.register_pin_with_alias("in", "snap-alias")?,
+                output: comp.register_pin("out")?,
+                _scale: comp.register_readonly_parameter("scale")?,
+            })
+        }
+    }
+
+    #[test]
+    fn capture() {
+        assert_eq!(HalSnapshot::capture(), Err(SnapshotError::NotInitialised));
+
+        let comp = HalComponent::new("snap").unwrap();
+        let pins: Pins = comp.register().unwrap();
+
+        pins.output.set_value(7).unwrap();
+
+        let signal = Signal::<u32>::new(&comp, "snap-out").unwrap();
+
+        signal.link(&pins.output).unwrap();
+
+        let snapshot = HalSnapshot::capture().unwrap();
+
+        let component = snapshot.component("snap").unwrap();
+
+        assert!(component.ready);
+        assert_eq!(component.kind, ComponentKind::Userspace);
+
+        assert_eq!(
+            snapshot.pin("snap.out"),
+            Some(&PinInfo {
+                name: "snap.out".to_string(),
+                owner: "snap".to_string(),
+                hal_type: HalType::U32,
+                direction: PinDirection::Out,
+                value: HalValue::U32(7),
+                signal: Some("snap-out".to_string()),
+            })
+        );
+
+        // Like LinuxCNC, an alias replaces the name of the pin
+        assert!(snapshot.pin("snap.in").is_none());
+        assert_eq!(
+            snapshot.pin("snap-alias").map(|p| p.direction),
+            Some(PinDirection::In)
+        );
+        assert_eq!(pins.input.name(), "snap.in");
+        assert_eq!(snapshot.component_pins("snap").count(), 2);
+
+        assert_eq!(
+            snapshot.parameter("snap.scale").map(|p| p.permissions),
+            Some(ParameterPermissions::ReadOnly)
+        );
+
+        assert_eq!(
+            snapshot.signal("snap-out"),
+            Some(&SignalInfo {
+                name: "snap-out".to_string(),
+                hal_type: HalType::U32,
+                value: HalValue::U32(7),
+                readers: 0,
+                writers: 1,
+                bidirs: 0,
+            })
+        );
+        assert_eq!(
+            snapshot
+                .signal_pins("snap-out")
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>(),
+            ["snap.out"]
+        );
+    }
+
+    #[cfg(not(feature = "ulapi"))]
+    #[test]
+    fn capture_threads() {
+        use crate::{
+            hal_function::{FunctionOptions, HalFunction},
+            thread::{HalThread, ThreadOptions},
+        };
+
+        struct Functions {
+            first: HalFunction,
+            second: HalFunction,
+        }
+
+        impl Resources<'static> for Functions {
+            type RegisterError = ResourcesError;
+
+            fn register_resources(
+                comp: &RegisterResources<'static>,
+            ) -> Result<Self, Self::RegisterError> {
+                Ok(Functions {
+                    first: comp.export_function(
+                        "first",
+                        FunctionOptions::default(),
+                        (),
+                        |_, _| {},
+                    )?,
+                    second: comp.export_function(
+                        "second",
+                        FunctionOptions::default(),
+                        (),
+                        |_, _| {},
+                    )?,
+                })
+            }
+        }
+
+        let comp: &'static HalComponent = Box::leak(Box::new(HalComponent::new("funct").unwrap()));
+        let functions: Functions = comp.register().unwrap();
+
+        let thread = HalThread::new(
+            comp,
+            "snap-thread",
+            Duration::from_millis(1),
+            ThreadOptions::default(),
+        )
+        .unwrap();
+
+        thread.add_function(&functions.second, 1).unwrap();
+        thread.add_function(&functions.first, 1).unwrap();
+
+        let snapshot = HalSnapshot::capture().unwrap();
+
+        assert_eq!(snapshot.functions.len(), 2);
+        assert!(snapshot
+            .functions
+            .iter()
+            .all(|f| f.owner == "funct" && f.users == 1));
+        assert_eq!(
+            snapshot.threads,
+            [ThreadInfo {
+                name: "snap-thread".to_string(),
+                period: Duration::from_millis(1),
+                priority: 0,
+                uses_fp: true,
+                functions: vec!["funct.first".to_string(), "funct.second".to_string()],
+            }]
+        );
+    }
+}
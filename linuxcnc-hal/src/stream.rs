@@ -0,0 +1,554 @@
+//! HAL streams
+//!
+//! A HAL stream is a FIFO in shared memory that passes fixed size samples between a realtime and a
+//! userspace component. It is the mechanism used by LinuxCNC's `streamer` and `sampler`
+//! components.
+//!
+//! Each sample is made up of one to [`HAL_STREAM_MAX_PINS`] values. The types of these values are
+//! described by the stream's type string, e.g. `"ffb"` for two floats and a bit. The type string
+//! is derived from the [`StreamSample`] type a [`StreamWriter`] or [`StreamReader`] is created
+//! with, so both ends of a stream must use samples with the same field types in the same order.
+//!
+//! | Sample field type | Type string character | HAL type |
+//! | ----------------- | --------------------- | -------- |
+//! | `f64`             | `f`                   | float    |
+//! | `bool`            | `b`                   | bit      |
+//! | `i32`             | `s`                   | s32      |
+//! | `u32`             | `u`                   | u32      |
+//!
+//! [`StreamSample`] is implemented for these types and tuples of them up to
+//! [`HAL_STREAM_MAX_PINS`] fields long. With the `derive` feature enabled, it can also be derived
+//! for structs whose fields are all of these types.
+//!
+//! Streams are opened through [`RegisterResources`](crate::RegisterResources), either by creating a
+//! new stream with [`create_stream`](crate::RegisterResources::create_stream) or by attaching to a
+//! stream created by another component with
+//! [`attach_stream`](crate::RegisterResources::attach_stream).
+//!
+//! # Examples
+//!
+//! ## Feed samples to a realtime component
+//!
+//! This example attaches to a stream created by a realtime `streamer` instance with
+//! `loadrt streamer depth=1000 cfg=ffb`. The shared memory key of `streamer.0` is `0x48535430`.
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::{
+//!    error::StreamError,
+//!    stream::StreamWriter,
+//!    HalComponent, RegisterResources, Resources,
+//! };
+//! use std::{error::Error, thread, time::Duration};
+//!
+//! struct Streams<'comp> {
+//!    /// X position, Y position and a spindle enable flag
+//!    trajectory: StreamWriter<'comp, (f64, f64, bool)>,
+//! }
+//!
+//! impl<'comp> Resources<'comp> for Streams<'comp> {
+//!    type RegisterError = StreamError;
+//!
+//!    fn register_resources(comp: &RegisterResources<'comp>) -> Result<Self, Self::RegisterError> {
+//!        Ok(Streams {
+//!            trajectory: comp.attach_stream(0x48535430)?,
+//!        })
+//!    }
+//! }
+//!
+//! fn main() -> Result<(), Box<dyn Error>> {
+//!    let comp = HalComponent::new("planner")?;
+//!
+//!    let Streams { trajectory } = comp.register()?;
+//!
+//!    let mut t = 0.0f64;
+//!
+//!    while !comp.should_exit() {
+//!        while trajectory.writable() {
+//!            trajectory.write((t.sin(), t.cos(), true))?;
+//!
+//!            t += 0.001;
+//!        }
+//!
+//!        thread::sleep(Duration::from_millis(10));
+//!    }
+//!
+//!    Ok(())
+//! }
+//! ```
+
+use crate::error::{HalErrno, StreamError};
+use crate::ffi::{
+    hal_stream_attach, hal_stream_create, hal_stream_depth, hal_stream_destroy, hal_stream_detach,
+    hal_stream_maxdepth, hal_stream_num_overruns, hal_stream_num_underruns, hal_stream_read,
+    hal_stream_readable, hal_stream_t, hal_stream_writable, hal_stream_write, HAL_STREAM_MAX_PINS,
+};
+use crate::HalComponent;
+use std::{cell::UnsafeCell, ffi::CString, marker::PhantomData, ptr};
+
+#[cfg(feature = "derive")]
+pub use linuxcnc_hal_derive::StreamSample;
+/// A single value in a stream, as read from or written to HAL shared memory
+pub use linuxcnc_hal_sys::hal_stream_data;
+
+/// A single value in a stream sample
+///
+/// Implemented for the scalar types that can be passed through a HAL stream.
+pub trait StreamElement: Copy {
+    /// The character representing this type in a stream type string
+    const TYPE_CHAR: char;
+
+    /// Convert the value into its stream representation
+    fn into_data(self) -> hal_stream_data;
+
+    /// Read a value from its stream representation
+    fn from_data(data: hal_stream_data) -> Self;
+}
+
+macro_rules! impl_stream_element {
+    ($type:ty, $char:literal, $field:ident) => {
+        impl StreamElement for $type {
+            const TYPE_CHAR: char = $char;
+
+            fn into_data(self) -> hal_stream_data {
+                hal_stream_data { $field: self }
+            }
+
+            fn from_data(data: hal_stream_data) -> Self {
+                // NOTE: The stream type string is checked when a stream is opened, so the field
+                // read here is the same type as the field written by the other end of the stream.
+                unsafe { data.$field }
+            }
+        }
+    };
+}
+
+impl_stream_element!(f64, 'f', f);
+impl_stream_element!(i32, 's', s);
+impl_stream_element!(u32, 'u', u);
+
+impl StreamElement for bool {
+    const TYPE_CHAR: char = 'b';
+
+    fn into_data(self) -> hal_stream_data {
+        hal_stream_data { b: self }
+    }
+
+    fn from_data(data: hal_stream_data) -> Self {
+        // NOTE: C code may write any non-zero byte as `true`, and reading such a byte as a Rust
+        // `bool` is undefined behaviour, so the byte is read and compared instead. Every field of
+        // the union starts at offset 0.
+        let byte = unsafe { *ptr::addr_of!(data).cast::<u8>() };
+
+        byte != 0
+    }
+}
+
+/// A sample that can be sent through a HAL stream
+///
+/// Implemented for every [`StreamElement`] and for tuples of up to [`HAL_STREAM_MAX_PINS`]
+/// elements. It can be derived for structs with the `derive` feature, or implemented by hand, as
+/// long as the fields are written and read in the same order.
+pub trait StreamSample: Sized {
+    /// The number of values in each sample
+    const LEN: usize;
+
+    /// The stream type string for this sample, e.g. `"ffb"`
+    fn type_string() -> String;
+
+    /// Write each value in the sample into `buf`
+    ///
+    /// `buf` is exactly [`LEN`](StreamSample::LEN) items long.
+    fn write_data(&self, buf: &mut [hal_stream_data]);
+
+    /// Read a sample from `buf`
+    ///
+    /// `buf` is exactly [`LEN`](StreamSample::LEN) items long.
+    fn read_data(buf: &[hal_stream_data]) -> Self;
+}
+
+macro_rules! impl_stream_sample_scalar {
+    ($type:ty) => {
+        impl StreamSample for $type {
+            const LEN: usize = 1;
+
+            fn type_string() -> String {
+                <$type as StreamElement>::TYPE_CHAR.to_string()
+            }
+
+            fn write_data(&self, buf: &mut [hal_stream_data]) {
+                buf[0] = self.into_data();
+            }
+
+            fn read_data(buf: &[hal_stream_data]) -> Self {
+                <$type as StreamElement>::from_data(buf[0])
+            }
+        }
+    };
+}
+
+impl_stream_sample_scalar!(f64);
+impl_stream_sample_scalar!(bool);
+impl_stream_sample_scalar!(i32);
+impl_stream_sample_scalar!(u32);
+
+macro_rules! impl_stream_sample_tuple {
+    ($( ($name:ident $idx:tt) )+) => {
+        impl<$($name: StreamElement),+> StreamSample for ($($name,)+) {
+            const LEN: usize = [$($idx),+].len();
+
+            fn type_string() -> String {
+                [$($name::TYPE_CHAR),+].iter().collect()
+            }
+
+            fn write_data(&self, buf: &mut [hal_stream_data]) {
+                $( buf[$idx] = self.$idx.into_data(); )+
+            }
+
+            fn read_data(buf: &[hal_stream_data]) -> Self {
+                ($($name::from_data(buf[$idx]),)+)
+            }
+        }
+    };
+}
+
+/// Implement [`StreamSample`] for every prefix of the given list of tuple fields
+macro_rules! impl_stream_sample_tuples {
+    ([$($done:tt)*] $next:tt $($rest:tt)*) => {
+        impl_stream_sample_tuple!($($done)* $next);
+        impl_stream_sample_tuples!([$($done)* $next] $($rest)*);
+    };
+    ([$($done:tt)*]) => {};
+}
+
+impl_stream_sample_tuples!([]
+    (A 0) (B 1) (C 2) (D 3) (E 4) (F 5) (G 6) (H 7) (I 8) (J 9) (K 10)
+    (L 11) (M 12) (N 13) (O 14) (P 15) (Q 16) (R 17) (S 18) (T 19) (U 20)
+);
+
+/// Empty stream data buffer large enough to hold any sample
+fn empty_buffer() -> [hal_stream_data; HAL_STREAM_MAX_PINS as usize] {
+    [hal_stream_data { u: 0 }; HAL_STREAM_MAX_PINS as usize]
+}
+
+/// Trait implemented by both ends of a HAL stream
+///
+/// Used by [`RegisterResources::create_stream`](crate::RegisterResources::create_stream) and
+/// [`RegisterResources::attach_stream`](crate::RegisterResources::attach_stream) to open a stream.
+///
+/// Streams borrow the component that opened them for `'comp`, as the stream is detached from the
+/// component when it exits.
+pub trait HalStream<'comp>: Sized {
+    /// The type of each sample in the stream
+    type Sample: StreamSample;
+
+    /// Create a new stream with the given shared memory key and depth
+    fn create(key: i32, depth: usize, comp: &'comp HalComponent) -> Result<Self, StreamError>;
+
+    /// Attach to an existing stream with the given shared memory key
+    fn attach(key: i32, comp: &'comp HalComponent) -> Result<Self, StreamError>;
+}
+
+/// Stream handle shared by both ends of the stream
+#[derive(Debug)]
+struct RawStream<'comp, T> {
+    key: i32,
+
+    /// Whether this handle created the stream and should destroy it on drop
+    owner: bool,
+
+    stream: UnsafeCell<hal_stream_t>,
+
+    _sample: PhantomData<T>,
+
+    _component: PhantomData<&'comp ()>,
+}
+
+impl<T> RawStream<'_, T>
+where
+    T: StreamSample,
+{
+    fn type_string(key: i32) -> Result<CString, StreamError> {
+        if T::LEN == 0 || T::LEN > HAL_STREAM_MAX_PINS as usize {
+            return Err(StreamError::Length { key });
+        }
+
+        let types = T::type_string();
+
+        CString::new(types.clone()).map_err(|_| StreamError::TypeString { key, types })
+    }
+
+    fn create(key: i32, depth: usize, component_id: i32) -> Result<Self, StreamError> {
+        let types = Self::type_string(key)?;
+
+        let depth = i32::try_from(depth).map_err(|_| StreamError::Depth { key, depth })?;
+
+        let mut stream = hal_stream_t {
+            comp_id: 0,
+            shmem_id: 0,
+            fifo: ptr::null_mut(),
+        };
+
+        let ret =
+            unsafe { hal_stream_create(&mut stream, component_id, key, depth, types.as_ptr()) };
+
+        debug!(
+            "Create stream {:#x} with types {:?} returned {}",
+            key, types, ret
+        );
+
+        Self::decode_open(ret, key, true, stream)
+    }
+
+    fn attach(key: i32, component_id: i32) -> Result<Self, StreamError> {
+        let types = Self::type_string(key)?;
+
+        let mut stream = hal_stream_t {
+            comp_id: 0,
+            shmem_id: 0,
+            fifo: ptr::null_mut(),
+        };
+
+        let ret = unsafe { hal_stream_attach(&mut stream, component_id, key, types.as_ptr()) };
+
+        debug!(
+            "Attach stream {:#x} with types {:?} returned {}",
+            key, types, ret
+        );
+
+        Self::decode_open(ret, key, false, stream)
+    }
+
+    fn decode_open(
+        ret: i32,
+        key: i32,
+        owner: bool,
+        stream: hal_stream_t,
+    ) -> Result<Self, StreamError> {
+        HalErrno::check(ret).map_err(|source| StreamError::Hal { key, source })?;
+
+        Ok(Self {
+            key,
+            owner,
+            stream: UnsafeCell::new(stream),
+            _sample: PhantomData,
+            _component: PhantomData,
+        })
+    }
+
+    fn ptr(&self) -> *mut hal_stream_t {
+        self.stream.get()
+    }
+
+    fn depth(&self) -> usize {
+        unsafe { hal_stream_depth(self.ptr()) as usize }
+    }
+
+    fn max_depth(&self) -> usize {
+        unsafe { hal_stream_maxdepth(self.ptr()) as usize }
+    }
+}
+
+impl<T> Drop for RawStream<'_, T> {
+    fn drop(&mut self) {
+        debug!("Drop stream {:#x}", self.key);
+
+        unsafe {
+            if self.owner {
+                hal_stream_destroy(self.stream.get());
+            } else {
+                hal_stream_detach(self.stream.get());
+            }
+        }
+    }
+}
+
+/// The writing end of a HAL stream
+///
+/// See the [module documentation](crate::stream) for more information.
+#[derive(Debug)]
+pub struct StreamWriter<'comp, T> {
+    inner: RawStream<'comp, T>,
+}
+
+impl<T> StreamWriter<'_, T>
+where
+    T: StreamSample,
+{
+    /// Write a sample to the stream
+    ///
+    /// # Errors
+    ///
+    /// * [`StreamError::Overrun`] - The stream is full. The sample is discarded and the stream's
+    ///   overrun counter is incremented.
+    pub fn write(&self, sample: T) -> Result<(), StreamError> {
+        let mut buf = empty_buffer();
+
+        sample.write_data(&mut buf[0..T::LEN]);
+
+        let ret = unsafe { hal_stream_write(self.inner.ptr(), buf.as_mut_ptr()) };
+
+        match HalErrno::check(ret) {
+            Ok(_) => Ok(()),
+            Err(HalErrno::NoSpace) => Err(StreamError::Overrun {
+                key: self.inner.key,
+                overruns: self.overruns(),
+            }),
+            Err(source) => Err(StreamError::Hal {
+                key: self.inner.key,
+                source,
+            }),
+        }
+    }
+
+    /// Whether there is space in the stream for another sample
+    pub fn writable(&self) -> bool {
+        unsafe { hal_stream_writable(self.inner.ptr()) }
+    }
+
+    /// The number of samples currently in the stream
+    pub fn depth(&self) -> usize {
+        self.inner.depth()
+    }
+
+    /// The maximum number of samples the stream can hold
+    pub fn max_depth(&self) -> usize {
+        self.inner.max_depth()
+    }
+
+    /// The number of samples that were discarded because the stream was full
+    pub fn overruns(&self) -> u32 {
+        unsafe { hal_stream_num_overruns(self.inner.ptr()) as u32 }
+    }
+}
+
+impl<'comp, T> HalStream<'comp> for StreamWriter<'comp, T>
+where
+    T: StreamSample,
+{
+    type Sample = T;
+
+    fn create(key: i32, depth: usize, comp: &'comp HalComponent) -> Result<Self, StreamError> {
+        RawStream::create(key, depth, comp.id()).map(|inner| Self { inner })
+    }
+
+    fn attach(key: i32, comp: &'comp HalComponent) -> Result<Self, StreamError> {
+        RawStream::attach(key, comp.id()).map(|inner| Self { inner })
+    }
+}
+
+/// The reading end of a HAL stream
+///
+/// See the [module documentation](crate::stream) for more information.
+#[derive(Debug)]
+pub struct StreamReader<'comp, T> {
+    inner: RawStream<'comp, T>,
+}
+
+impl<T> StreamReader<'_, T>
+where
+    T: StreamSample,
+{
+    /// Read the next sample from the stream
+    ///
+    /// # Errors
+    ///
+    /// * [`StreamError::Underrun`] - The stream is empty. The stream's underrun counter is
+    ///   incremented.
+    pub fn read(&self) -> Result<T, StreamError> {
+        self.read_numbered().map(|(sample, _)| sample)
+    }
+
+    /// Read the next sample from the stream along with its sample number
+    ///
+    /// Sample numbers increase by one for every sample written to the stream, so a gap between
+    /// two consecutive reads means samples were lost.
+    ///
+    /// # Errors
+    ///
+    /// * [`StreamError::Underrun`] - The stream is empty. The stream's underrun counter is
+    ///   incremented.
+    pub fn read_numbered(&self) -> Result<(T, u32), StreamError> {
+        let mut buf = empty_buffer();
+        let mut sample_number = 0;
+
+        let ret =
+            unsafe { hal_stream_read(self.inner.ptr(), buf.as_mut_ptr(), &mut sample_number) };
+
+        match HalErrno::check(ret) {
+            Ok(_) => Ok((T::read_data(&buf[0..T::LEN]), sample_number)),
+            Err(HalErrno::Again) => Err(StreamError::Underrun {
+                key: self.inner.key,
+                underruns: self.underruns(),
+            }),
+            Err(source) => Err(StreamError::Hal {
+                key: self.inner.key,
+                source,
+            }),
+        }
+    }
+
+    /// Whether there is at least one sample in the stream to read
+    pub fn readable(&self) -> bool {
+        unsafe { hal_stream_readable(self.inner.ptr()) }
+    }
+
+    /// The number of samples currently in the stream
+    pub fn depth(&self) -> usize {
+        self.inner.depth()
+    }
+
+    /// The maximum number of samples the stream can hold
+    pub fn max_depth(&self) -> usize {
+        self.inner.max_depth()
+    }
+
+    /// The number of reads that were attempted while the stream was empty
+    pub fn underruns(&self) -> u32 {
+        unsafe { hal_stream_num_underruns(self.inner.ptr()) as u32 }
+    }
+}
+
+impl<'comp, T> HalStream<'comp> for StreamReader<'comp, T>
+where
+    T: StreamSample,
+{
+    type Sample = T;
+
+    fn create(key: i32, depth: usize, comp: &'comp HalComponent) -> Result<Self, StreamError> {
+        RawStream::create(key, depth, comp.id()).map(|inner| Self { inner })
+    }
+
+    fn attach(key: i32, comp: &'comp HalComponent) -> Result<Self, StreamError> {
+        RawStream::attach(key, comp.id()).map(|inner| Self { inner })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tuple_type_strings() {
+        assert_eq!(<(f64, f64, bool)>::type_string(), "ffb");
+        assert_eq!(<(f64, f64, bool)>::LEN, 3);
+        assert_eq!(<(u32,)>::type_string(), "u");
+        assert_eq!(i32::type_string(), "s");
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut buf = empty_buffer();
+
+        let sample = (1.5f64, true, -3i32, 7u32);
+
+        sample.write_data(&mut buf[0..4]);
+
+        assert_eq!(<(f64, bool, i32, u32)>::read_data(&buf[0..4]), sample);
+    }
+
+    #[test]
+    fn non_canonical_bool() {
+        assert!(bool::from_data(hal_stream_data { u: 0x0202_0202 }));
+        assert!(!bool::from_data(hal_stream_data { u: 0 }));
+    }
+}
@@ -0,0 +1,535 @@
+//! Realtime HAL threads
+//!
+//! A [`HalThread`] runs HAL functions periodically, the same as a thread created by `loadrt
+//! threads` in a `.hal` file. Functions exported by the component are added to a thread with
+//! [`HalThread::add_function`], and functions exported by other components with [`add_function`].
+//! Both take a position like `addf`:
+//!
+//! | Position | Runs                   |
+//! | -------- | ---------------------- |
+//! | `1`      | First                  |
+//! | `5`      | Fifth                  |
+//! | `-2`     | Next to last           |
+//! | `-1`     | Last (`addf` default)  |
+//!
+//! Threads only run once [`start_threads`] is called, and are deleted when the [`HalThread`] is
+//! dropped. A thread stored in a component's [`Resources`](crate::Resources) is deleted before
+//! the component exits.
+//!
+//! LinuxCNC only allows threads to be created from realtime components, so [`HalThread::new`]
+//! must be called from a component loaded with `loadrt`, e.g. one using `rtapi_app!`, and is not
+//! available with the `ulapi` feature. Functions can be added to or removed from existing threads,
+//! and threads started and stopped, from any component.
+//!
+//! # Examples
+//!
+//! ```rust,no_run
+//! use linuxcnc_hal::{
+//!     error::ResourcesError,
+//!     hal_function::{FunctionOptions, HalFunction},
+//!     hal_pin::OutputPin,
+//!     prelude::*,
+//!     rtapi_app,
+//!     thread::{self, HalThread, ThreadOptions},
+//!     HalComponent, RegisterResources, Resources,
+//! };
+//! use std::{error::Error, time::Duration};
+//!
+//! struct Comp {
+//!     count: HalFunction,
+//!     thread: HalThread<'static>,
+//! }
+//!
+//! impl Resources<'static> for Comp {
+//!     type RegisterError = ResourcesError;
+//!
+//!     fn register_resources(comp: &RegisterResources<'static>) -> Result<Self, Self::RegisterError> {
+//!         let out: OutputPin<u32> = comp.register_pin("out")?;
+//!
+//!         let count = comp.export_function(
+//!             "count",
+//!             FunctionOptions::default(),
+//!             (out, 0u32),
+//!             |(out, count), _period| {
+//!                 *count = count.wrapping_add(1);
+//!
+//!                 out.set_value(*count).ok();
+//!             },
+//!         )?;
+//!
+//!         // Equivalent to `loadrt threads name1=count-thread period1=1000000`
+//!         let thread = HalThread::new(
+//!             comp.component(),
+//!             "count-thread",
+//!             Duration::from_millis(1),
+//!             ThreadOptions::default(),
+//!         )?;
+//!
+//!         Ok(Comp { count, thread })
+//!     }
+//! }
+//!
+//! fn init(_comp: &HalComponent, resources: &Comp) -> Result<(), Box<dyn Error>> {
+//!     // Equivalent to `addf counter.count count-thread` and `start`
+//!     resources.thread.add_function(&resources.count, -1)?;
+//!
+//!     thread::start_threads()?;
+//!
+//!     Ok(())
+//! }
+//!
+//! rtapi_app! {
+//!     name: "counter",
+//!     resources: Comp,
+//!     init: init,
+//! }
+//! ```
+
+// `HalThread` is linked from the docs above but is not available with the `ulapi` feature
+#![cfg_attr(feature = "ulapi", allow(rustdoc::broken_intra_doc_links))]
+
+use crate::error::{HalErrno, ThreadError};
+use crate::ffi::{
+    hal_add_funct_to_thread, hal_del_funct_from_thread, hal_start_threads, hal_stop_threads,
+    HAL_NAME_LEN,
+};
+#[cfg(not(feature = "ulapi"))]
+use crate::ffi::{hal_create_thread, hal_thread_delete};
+#[cfg(feature = "linuxcnc-2-9")]
+use crate::snapshot;
+#[cfg(not(feature = "ulapi"))]
+use crate::{hal_function::HalFunction, HalComponent};
+use std::ffi::CString;
+#[cfg(not(feature = "ulapi"))]
+use std::{convert::TryInto, marker::PhantomData, os::raw::c_ulong, time::Duration};
+
+/// Options used when creating a thread
+#[cfg(not(feature = "ulapi"))]
+#[derive(Copy, Clone, Debug)]
+pub struct ThreadOptions {
+    /// Whether functions in the thread may use floating point operations
+    ///
+    /// Functions that use floating point cannot be added to a thread without it. Defaults to
+    /// `true`.
+    pub uses_fp: bool,
+}
+
+#[cfg(not(feature = "ulapi"))]
+impl Default for ThreadOptions {
+    fn default() -> Self {
+        Self { uses_fp: true }
+    }
+}
+
+/// A realtime HAL thread
+///
+/// The thread is deleted from the HAL when this handle is dropped, which also removes every
+/// function from it.
+///
+/// Threads can only be created by realtime components, so this type is not available with the
+/// `ulapi` feature. The thread borrows the component it was created with for `'comp`.
+#[cfg(not(feature = "ulapi"))]
+#[derive(Debug)]
+pub struct HalThread<'comp> {
+    name: String,
+    id: i32,
+    period: Duration,
+    uses_fp: bool,
+    _component: PhantomData<&'comp ()>,
+}
+
+#[cfg(not(feature = "ulapi"))]
+impl<'comp> HalThread<'comp> {
+    /// Create a new thread
+    ///
+    /// Threads must be created from fastest to slowest, and every thread's period must be a
+    /// multiple of the fastest thread's period. Threads created later get a lower priority.
+    ///
+    /// # Errors
+    ///
+    /// * [`ThreadError::Period`] - if the period is zero or too long to pass to the HAL
+    /// * [`ThreadError::Hal`] - with [`HalErrno::Invalid`] if a thread with this name already
+    ///   exists, the period is not a multiple of the fastest thread, or this is not a realtime
+    ///   component, [`HalErrno::LockedHal`] if the HAL is locked, or [`HalErrno::Memory`] if there
+    ///   is not enough shared memory to create the thread
+    pub fn new(
+        _comp: &'comp HalComponent,
+        name: impl Into<String>,
+        period: Duration,
+        options: ThreadOptions,
+    ) -> Result<Self, ThreadError> {
+        let name = name.into();
+        let name_ffi = name_to_c(&name)?;
+
+        let period_nsec: c_ulong = period
+            .as_nanos()
+            .try_into()
+            .ok()
+            .filter(|nsec| *nsec > 0)
+            .ok_or_else(|| ThreadError::Period(name.clone()))?;
+
+        let id =
+            unsafe { hal_create_thread(name_ffi.as_ptr(), period_nsec, options.uses_fp as i32) };
+
+        let id = HalErrno::check(id).map_err(|source| ThreadError::Hal {
+            name: name.clone(),
+            source,
+        })?;
+
+        debug!(
+            "Created thread {} with ID {}, period {:?}",
+            name, id, period
+        );
+
+        Ok(Self {
+            name,
+            id,
+            period,
+            uses_fp: options.uses_fp,
+            _component: PhantomData,
+        })
+    }
+
+    /// Get the thread name
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Get the HAL-assigned ID for this thread
+    pub fn id(&self) -> i32 {
+        self.id
+    }
+
+    /// Get the period the thread was created with
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    /// Whether functions in the thread may use floating point operations
+    pub fn uses_fp(&self) -> bool {
+        self.uses_fp
+    }
+
+    /// Add a function exported by this component to this thread
+    ///
+    /// See [`add_function`] for details, and to add a function exported by another component.
+    pub fn add_function(&self, function: &HalFunction, position: i32) -> Result<(), ThreadError> {
+        add_function(function.name(), &self.name, position)
+    }
+
+    /// Remove a function exported by this component from this thread
+    ///
+    /// See [`remove_function`] for details.
+    pub fn remove_function(&self, function: &HalFunction) -> Result<(), ThreadError> {
+        remove_function(function.name(), &self.name)
+    }
+}
+
+#[cfg(not(feature = "ulapi"))]
+impl Drop for HalThread<'_> {
+    /// Delete the thread from the HAL
+    fn drop(&mut self) {
+        let ret = match name_to_c(&self.name) {
+            Ok(name_ffi) => unsafe { hal_thread_delete(name_ffi.as_ptr()) },
+            Err(_) => return,
+        };
+
+        match HalErrno::check(ret) {
+            Ok(_) => debug!("Deleted thread {}", self.name),
+            Err(e) => error!("Failed to delete thread {}: {}", self.name, e),
+        }
+    }
+}
+
+/// Add a function to a thread by name, like `addf <function> <thread> <position>`
+///
+/// A positive `position` counts from the start of the thread, so `1` runs the function first. A
+/// negative `position` counts from the end, so `-1` runs the function last.
+///
+/// # Errors
+///
+/// * [`ThreadError::Position`] - if `position` is zero
+/// * [`ThreadError::FunctionNotFound`] - if no function with this name exists
+/// * [`ThreadError::ThreadNotFound`] - if no thread with this name exists
+/// * [`ThreadError::AlreadyAdded`] - if the function is not reentrant and is already in a thread
+/// * [`ThreadError::FloatingPoint`] - if the function uses floating point and the thread does not
+/// * [`ThreadError::Hal`] - with [`HalErrno::Invalid`] if `position` is past the end of the
+///   thread, [`HalErrno::LockedHal`] if the HAL is locked, or [`HalErrno::Memory`] if there is not
+///   enough shared memory to add the function
+pub fn add_function(function: &str, thread: &str, position: i32) -> Result<(), ThreadError> {
+    if position == 0 {
+        return Err(ThreadError::Position(function.to_string()));
+    }
+
+    let function_ffi = name_to_c(function)?;
+    let thread_ffi = name_to_c(thread)?;
+
+    let ret =
+        unsafe { hal_add_funct_to_thread(function_ffi.as_ptr(), thread_ffi.as_ptr(), position) };
+
+    match HalErrno::check(ret) {
+        Ok(_) => {
+            debug!(
+                "Added function {} to thread {} at position {}",
+                function, thread, position
+            );
+
+            Ok(())
+        }
+        Err(HalErrno::Invalid) => Err(add_error(function, thread)),
+        Err(source) => Err(ThreadError::Hal {
+            name: thread.to_string(),
+            source,
+        }),
+    }
+}
+
+/// Remove a function from a thread by name, like `delf <function> <thread>`
+///
+/// # Errors
+///
+/// * [`ThreadError::FunctionNotFound`] - if no function with this name exists
+/// * [`ThreadError::ThreadNotFound`] - if no thread with this name exists
+/// * [`ThreadError::Hal`] - with [`HalErrno::Invalid`] if the function is not in the thread, or
+///   [`HalErrno::LockedHal`] if the HAL is locked
+pub fn remove_function(function: &str, thread: &str) -> Result<(), ThreadError> {
+    let function_ffi = name_to_c(function)?;
+    let thread_ffi = name_to_c(thread)?;
+
+    let ret = unsafe { hal_del_funct_from_thread(function_ffi.as_ptr(), thread_ffi.as_ptr()) };
+
+    match HalErrno::check(ret) {
+        Ok(_) => {
+            debug!("Removed function {} from thread {}", function, thread);
+
+            Ok(())
+        }
+        Err(HalErrno::Invalid) => Err(lookup_error(function, thread)),
+        Err(source) => Err(ThreadError::Hal {
+            name: thread.to_string(),
+            source,
+        }),
+    }
+}
+
+/// Start every thread, like `start` in a `.hal` file
+///
+/// # Errors
+///
+/// * [`HalErrno::LockedHal`] - if the HAL is locked
+pub fn start_threads() -> Result<(), HalErrno> {
+    let ret = unsafe { hal_start_threads() };
+
+    HalErrno::check(ret)?;
+
+    debug!("Started threads");
+
+    Ok(())
+}
+
+/// Stop every thread, like `stop` in a `.hal` file
+///
+/// # Errors
+///
+/// * [`HalErrno::LockedHal`] - if the HAL is locked
+pub fn stop_threads() -> Result<(), HalErrno> {
+    let ret = unsafe { hal_stop_threads() };
+
+    HalErrno::check(ret)?;
+
+    debug!("Stopped threads");
+
+    Ok(())
+}
+
+fn name_to_c(name: &str) -> Result<CString, ThreadError> {
+    if name.len() > HAL_NAME_LEN as usize {
+        return Err(ThreadError::NameLength(name.to_string()));
+    }
+
+    CString::new(name).map_err(|e| {
+        error!("Failed to convert name to C string: {}", e);
+
+        ThreadError::NameConversion(name.to_string())
+    })
+}
+
+/// Work out why [`hal_del_funct_from_thread`] returned `EINVAL`
+#[cfg(feature = "linuxcnc-2-9")]
+fn lookup_error(function: &str, thread: &str) -> ThreadError {
+    match (
+        snapshot::find_function(function),
+        snapshot::find_thread(thread),
+    ) {
+        (Ok(None), _) => ThreadError::FunctionNotFound(function.to_string()),
+        (_, Ok(None)) => ThreadError::ThreadNotFound(thread.to_string()),
+        _ => invalid(thread),
+    }
+}
+
+/// Work out why [`hal_add_funct_to_thread`] returned `EINVAL`, as it is used for every kind of
+/// failure
+#[cfg(feature = "linuxcnc-2-9")]
+fn add_error(function_name: &str, thread_name: &str) -> ThreadError {
+    let (Ok(function), Ok(thread)) = (
+        snapshot::find_function(function_name),
+        snapshot::find_thread(thread_name),
+    ) else {
+        return invalid(thread_name);
+    };
+
+    match (function, thread) {
+        (None, _) => ThreadError::FunctionNotFound(function_name.to_string()),
+        (_, None) => ThreadError::ThreadNotFound(thread_name.to_string()),
+        (Some(function), _) if !function.reentrant && function.users > 0 => {
+            ThreadError::AlreadyAdded(function_name.to_string())
+        }
+        (Some(function), Some(thread)) if function.uses_fp && !thread.uses_fp => {
+            ThreadError::FloatingPoint {
+                function: function_name.to_string(),
+                thread: thread_name.to_string(),
+            }
+        }
+        _ => invalid(thread_name),
+    }
+}
+
+/// Functions and threads can only be looked up in HAL shared memory, which has a known layout for
+/// LinuxCNC 2.9 only
+#[cfg(not(feature = "linuxcnc-2-9"))]
+fn lookup_error(_function: &str, thread: &str) -> ThreadError {
+    invalid(thread)
+}
+
+#[cfg(not(feature = "linuxcnc-2-9"))]
+fn add_error(_function: &str, thread: &str) -> ThreadError {
+    invalid(thread)
+}
+
+/// The error for an `EINVAL` that isn't explained by a missing function or thread
+fn invalid(thread: &str) -> ThreadError {
+    ThreadError::Hal {
+        name: thread.to_string(),
+        source: HalErrno::Invalid,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_position() {
+        assert_eq!(
+            add_function("comp.funct", "servo-thread", 0),
+            Err(ThreadError::Position("comp.funct".to_string()))
+        );
+    }
+
+    #[cfg(all(feature = "mock", not(feature = "ulapi")))]
+    #[test]
+    fn add_remove_order() {
+        use crate::{
+            error::ResourcesError, hal_function::FunctionOptions, mock, RegisterResources,
+            Resources,
+        };
+        use std::sync::Mutex;
+
+        static LOG: Mutex<Vec<&str>> = Mutex::new(Vec::new());
+
+        struct Functions {
+            first: HalFunction,
+            second: HalFunction,
+            third: HalFunction,
+        }
+
+        impl Resources<'static> for Functions {
+            type RegisterError = ResourcesError;
+
+            fn register_resources(
+                comp: &RegisterResources<'static>,
+            ) -> Result<Self, Self::RegisterError> {
+                let export = |name: &'static str| {
+                    comp.export_function(name, FunctionOptions::default(), (), move |_, _| {
+                        LOG.lock().unwrap().push(name)
+                    })
+                };
+
+                Ok(Functions {
+                    first: export("first")?,
+                    second: export("second")?,
+                    third: export("third")?,
+                })
+            }
+        }
+
+        let comp: &'static HalComponent = Box::leak(Box::new(HalComponent::new("order").unwrap()));
+        let functions: Functions = comp.register().unwrap();
+
+        let thread = HalThread::new(
+            comp,
+            "order-thread",
+            Duration::from_millis(1),
+            ThreadOptions::default(),
+        )
+        .unwrap();
+
+        thread.add_function(&functions.second, -1).unwrap();
+        thread.add_function(&functions.third, -1).unwrap();
+        thread.add_function(&functions.first, 1).unwrap();
+
+        mock::run_thread("order-thread").unwrap();
+
+        assert_eq!(*LOG.lock().unwrap(), ["first", "second", "third"]);
+
+        thread.remove_function(&functions.second).unwrap();
+
+        assert_eq!(
+            thread.remove_function(&functions.second),
+            Err(invalid("order-thread"))
+        );
+
+        LOG.lock().unwrap().clear();
+        mock::run_thread("order-thread").unwrap();
+
+        assert_eq!(*LOG.lock().unwrap(), ["first", "third"]);
+
+        assert_eq!(
+            thread.add_function(&functions.first, -1),
+            Err(ThreadError::AlreadyAdded("order.first".to_string()))
+        );
+        assert_eq!(
+            add_function("order.missing", "order-thread", -1),
+            Err(ThreadError::FunctionNotFound("order.missing".to_string()))
+        );
+        assert_eq!(
+            add_function("order.second", "missing-thread", -1),
+            Err(ThreadError::ThreadNotFound("missing-thread".to_string()))
+        );
+        assert_eq!(
+            remove_function("order.second", "missing-thread"),
+            Err(ThreadError::ThreadNotFound("missing-thread".to_string()))
+        );
+
+        let integer = HalThread::new(
+            comp,
+            "order-integer",
+            Duration::from_millis(2),
+            ThreadOptions { uses_fp: false },
+        )
+        .unwrap();
+
+        assert_eq!(
+            integer.add_function(&functions.second, -1),
+            Err(ThreadError::FloatingPoint {
+                function: "order.second".to_string(),
+                thread: "order-integer".to_string()
+            })
+        );
+        assert_eq!(
+            ResourcesError::from(integer.add_function(&functions.second, -1).unwrap_err())
+                .to_string(),
+            "function `order.second` uses floating point but thread `order-integer` does not"
+        );
+    }
+}
@@ -0,0 +1,93 @@
+//! Register a `#[derive(Resources)]` struct against the mock HAL
+
+#![cfg(all(feature = "derive", feature = "mock"))]
+
+use linuxcnc_hal::{
+    error::ResourcesError,
+    hal_pin::{InputPin, OutputPin, PinDirection},
+    mock,
+    prelude::*,
+    query::{self, HalValue, ParameterValue},
+    HalComponent, Parameter, ParameterPermissions, Resources,
+};
+
+#[derive(Resources)]
+struct Comp<'comp> {
+    input_1: InputPin<'comp, f64>,
+
+    #[hal(alias = "x-axis.home-sw")]
+    home_sw: InputPin<'comp, bool>,
+
+    #[hal(name = "out", init = 4)]
+    output: OutputPin<'comp, u32>,
+
+    #[hal(readonly, init = 2.5)]
+    scale: Parameter<'comp, f64>,
+
+    #[hal(skip)]
+    counter: u32,
+}
+
+#[test]
+fn register_derived() {
+    let comp = HalComponent::new("derived").unwrap();
+
+    let resources: Comp = comp.register().unwrap();
+
+    assert!(mock::is_ready("derived"));
+
+    assert_eq!(query::pin("derived.out").unwrap().value, HalValue::U32(4));
+    assert_eq!(
+        query::parameter("derived.scale"),
+        Ok(ParameterValue {
+            value: HalValue::Float(2.5),
+            permissions: ParameterPermissions::ReadOnly,
+        })
+    );
+    assert_eq!(resources.counter, 0);
+
+    mock::set_pin("derived.input-1", HalValue::Float(1.5)).unwrap();
+    mock::set_pin("x-axis.home-sw", HalValue::Bit(true)).unwrap();
+
+    assert_eq!(resources.input_1.value(), Ok(&1.5));
+    assert_eq!(resources.home_sw.value(), Ok(&true));
+    assert_eq!(resources.home_sw.name(), "derived.home-sw");
+    assert_eq!(
+        query::pin("derived.home-sw").unwrap().direction,
+        PinDirection::In
+    );
+
+    resources.output.set_value(5).unwrap();
+    resources.scale.set_value(0.5).unwrap();
+
+    assert_eq!(query::pin("derived.out").unwrap().value, HalValue::U32(5));
+    assert_eq!(resources.scale.value(), Ok(&0.5));
+}
+
+#[derive(Resources)]
+struct Duplicate<'comp> {
+    #[hal(name = "in")]
+    _first: InputPin<'comp, f64>,
+
+    #[hal(name = "in")]
+    _second: InputPin<'comp, f64>,
+}
+
+#[test]
+fn field_error() {
+    let comp = HalComponent::new("duplicate").unwrap();
+
+    let Err(err) = comp.register::<Duplicate>() else {
+        panic!("registering the same pin name twice should fail");
+    };
+
+    let linuxcnc_hal::error::ComponentInitError::ResourceRegistration(ResourcesError::Field {
+        field,
+        ..
+    }) = err
+    else {
+        panic!("expected a field error, got {:?}", err);
+    };
+
+    assert_eq!(field, "_second");
+}
@@ -28,3 +28,9 @@ work by you, as defined in the Apache-2.0 license, shall be dual licensed as abo
 additional terms or conditions.
 
 [`log`]: https://docs.rs/log
+[`set_level`]: https://docs.rs/rtapi-logger/latest/rtapi_logger/fn.set_level.html
+[`sync_level`]: https://docs.rs/rtapi-logger/latest/rtapi_logger/fn.sync_level.html
+[`MAX_MESSAGE_LEN`]: https://docs.rs/rtapi-logger/latest/rtapi_logger/constant.MAX_MESSAGE_LEN.html
+[`capture`]: https://docs.rs/rtapi-logger/latest/rtapi_logger/capture/index.html
+[`Builder`]: https://docs.rs/rtapi-logger/latest/rtapi_logger/struct.Builder.html
+[`Builder::layer`]: https://docs.rs/rtapi-logger/latest/rtapi_logger/struct.Builder.html#method.layer
@@ -0,0 +1,112 @@
+use crate::{message::Message, sync_level, RtapiLogger};
+use log::SetLoggerError;
+use std::fmt::Write;
+
+/// Configure and install an [`RtapiLogger`]
+///
+/// Each message can be prefixed with a fixed string, such as the component name, and with the
+/// target and module path of the log record. With several Rust components loaded into LinuxCNC,
+/// this shows which component printed which message.
+///
+/// ```rust,no_run
+/// rtapi_logger::Builder::new()
+///     .prefix("my-comp")
+///     .module_path(true)
+///     .init()
+///     .ok();
+///
+/// // Prints e.g. `my-comp: my_comp::servo: Following error too large`
+/// log::error!("Following error too large");
+/// ```
+///
+/// Every component is built as its own library with its own copy of [`log`], so each component
+/// can install a logger with a different prefix, even when loaded into the same process.
+#[derive(Debug, Clone, Default)]
+pub struct Builder {
+    format: Format,
+}
+
+impl Builder {
+    /// Create a builder with no prefix, target or module path
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Prefix every message with `prefix`, e.g. the component name
+    pub fn prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.format.prefix = Some(prefix.into());
+
+        self
+    }
+
+    /// Include the target of the record in every message
+    pub fn target(mut self, target: bool) -> Self {
+        self.format.target = target;
+
+        self
+    }
+
+    /// Include the module path of the record in every message, if it is known
+    pub fn module_path(mut self, module_path: bool) -> Self {
+        self.format.module_path = module_path;
+
+        self
+    }
+
+    /// Create the logger without installing it
+    pub fn build(self) -> RtapiLogger {
+        RtapiLogger {
+            format: self.format,
+        }
+    }
+
+    /// Create a [`tracing-subscriber`](tracing_subscriber) layer with the same message format
+    ///
+    /// Requires the `tracing` feature.
+    #[cfg(feature = "tracing")]
+    pub fn layer(self) -> crate::RtapiLayer {
+        crate::RtapiLayer {
+            format: self.format,
+        }
+    }
+
+    /// Install the logger as the global [`log`] logger
+    ///
+    /// The maximum log level is set from LinuxCNC's current RTAPI message level.
+    pub fn init(self) -> Result<(), SetLoggerError> {
+        let logger: &'static RtapiLogger = Box::leak(Box::new(self.build()));
+
+        log::set_logger(logger).map(|()| sync_level())
+    }
+}
+
+/// Which details are written before each message
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Format {
+    prefix: Option<String>,
+    target: bool,
+    module_path: bool,
+}
+
+impl Format {
+    /// Write the prefix, target and module path to `message`, followed by `: `
+    pub(crate) fn write_header(
+        &self,
+        message: &mut Message,
+        target: &str,
+        module_path: Option<&str>,
+    ) {
+        // Long messages are truncated instead of returning an error, so writes can't fail
+        if let Some(prefix) = &self.prefix {
+            let _ = write!(message, "{}: ", prefix);
+        }
+
+        if self.target {
+            let _ = write!(message, "[{}] ", target);
+        }
+
+        if let Some(module_path) = module_path.filter(|_| self.module_path) {
+            let _ = write!(message, "{}: ", module_path);
+        }
+    }
+}
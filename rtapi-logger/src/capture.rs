@@ -0,0 +1,281 @@
+//! Capture RTAPI messages from every component
+//!
+//! [`MessageCapture`] replaces LinuxCNC's RTAPI message handler with one that forwards every
+//! message printed with `rtapi_print` or `rtapi_print_msg` in this process into [`log`], or into a
+//! custom sink. This includes messages from C components and from the HAL library itself. The
+//! previous handler is restored when the capture is dropped.
+//!
+//! ```rust,no_run
+//! use rtapi_logger::capture::MessageCapture;
+//!
+//! // Set up a logger for the supervisor, e.g. `env_logger`, here
+//!
+//! let _capture = MessageCapture::to_log()?;
+//!
+//! // Messages printed by any HAL component in this process are now logged with the target
+//! // `rtapi`
+//! # Ok::<(), rtapi_logger::capture::AlreadyCaptured>(())
+//! ```
+//!
+//! A custom sink receives the level and text of each message:
+//!
+//! ```rust,no_run
+//! use rtapi_logger::{capture::MessageCapture, RtapiLogLevel};
+//!
+//! let _capture = MessageCapture::with_sink(|level: RtapiLogLevel, message: &str| {
+//!     eprintln!("[{:?}] {}", level, message);
+//! })?;
+//! # Ok::<(), rtapi_logger::capture::AlreadyCaptured>(())
+//! ```
+//!
+//! Only one capture can be active at a time. While it is active, messages are not passed on to
+//! the previous handler, so they no longer appear in `linuxcnc.log` or the kernel log.
+//!
+//! Messages printed while the sink is running on the same thread are dropped. This stops messages
+//! from looping forever when they are forwarded into [`log`] with
+//! [`RtapiLogger`](crate::RtapiLogger) installed as the logger.
+//!
+//! Panics in the sink are caught, as they must not unwind into the C code that printed the message.
+//! The message is dropped.
+//!
+//! The message handler is global to the process, so this only captures messages printed in the
+//! process that installs it. For a userspace supervisor, these are the messages printed by the HAL
+//! library and any component running in the supervisor's process.
+
+use crate::{RtapiLogLevel, MAX_MESSAGE_LEN};
+use linuxcnc_hal_sys::{
+    __va_list_tag, msg_level_t, rtapi_get_msg_handler, rtapi_msg_handler_t, rtapi_set_msg_handler,
+};
+use std::{
+    cell::Cell,
+    error::Error,
+    fmt,
+    os::raw::{c_char, c_int},
+    panic::{self, AssertUnwindSafe},
+    sync::Mutex,
+};
+
+/// A function that receives captured messages
+type Sink = Box<dyn Fn(RtapiLogLevel, &str) + Send>;
+
+/// The sink of the active capture, if any
+static SINK: Mutex<Option<Sink>> = Mutex::new(None);
+
+thread_local! {
+    /// Whether this thread is currently running the sink
+    static IN_HANDLER: Cell<bool> = const { Cell::new(false) };
+}
+
+extern "C" {
+    fn vsnprintf(s: *mut c_char, n: usize, format: *const c_char, ap: *mut __va_list_tag) -> c_int;
+}
+
+/// An active RTAPI message capture
+///
+/// Dropping this value restores the previous message handler.
+pub struct MessageCapture {
+    /// The handler that was installed before this capture
+    previous: rtapi_msg_handler_t,
+}
+
+impl MessageCapture {
+    /// Forward every RTAPI message into [`log`]
+    ///
+    /// Messages are logged with the target `rtapi`, at the `log` level matching their RTAPI
+    /// level. Messages printed with `rtapi_print` are logged at `Trace` level.
+    pub fn to_log() -> Result<Self, AlreadyCaptured> {
+        Self::with_sink(|level: RtapiLogLevel, message: &str| {
+            if let Some(level) = level.to_level_filter().to_level() {
+                log::log!(target: "rtapi", level, "{}", message);
+            }
+        })
+    }
+
+    /// Forward every RTAPI message to `sink`
+    ///
+    /// The sink is called with the message level and text, without a trailing newline. Messages
+    /// longer than [`MAX_MESSAGE_LEN`] bytes are truncated.
+    pub fn with_sink<F>(sink: F) -> Result<Self, AlreadyCaptured>
+    where
+        F: Fn(RtapiLogLevel, &str) + Send + 'static,
+    {
+        {
+            let mut current = SINK.lock().unwrap_or_else(|e| e.into_inner());
+
+            if current.is_some() {
+                return Err(AlreadyCaptured);
+            }
+
+            *current = Some(Box::new(sink));
+        }
+
+        // The lock must be released before installing the handler, as logging below may call it
+        let previous = unsafe { rtapi_get_msg_handler() };
+
+        unsafe { rtapi_set_msg_handler(Some(handle_message)) };
+
+        log::debug!("Capturing RTAPI messages");
+
+        Ok(Self { previous })
+    }
+}
+
+impl fmt::Debug for MessageCapture {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MessageCapture").finish_non_exhaustive()
+    }
+}
+
+impl Drop for MessageCapture {
+    fn drop(&mut self) {
+        unsafe { rtapi_set_msg_handler(self.previous) };
+
+        *SINK.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Another [`MessageCapture`] is already active
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct AlreadyCaptured;
+
+impl fmt::Display for AlreadyCaptured {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("RTAPI messages are already being captured")
+    }
+}
+
+impl Error for AlreadyCaptured {}
+
+/// RTAPI message handler that formats the message and passes it to the sink
+unsafe extern "C" fn handle_message(
+    level: msg_level_t,
+    format: *const c_char,
+    ap: *mut __va_list_tag,
+) {
+    let mut buf = [0u8; MAX_MESSAGE_LEN + 2];
+
+    let len = vsnprintf(buf.as_mut_ptr().cast(), buf.len(), format, ap);
+
+    // `vsnprintf` returns the length the message would have had without truncation
+    let len = (len.max(0) as usize).min(buf.len() - 1);
+
+    let message = String::from_utf8_lossy(&buf[..len]);
+
+    forward(
+        RtapiLogLevel::from_raw(level as i32),
+        message.trim_end_matches('\n'),
+    );
+}
+
+/// Pass a message to the sink of the active capture, if any
+///
+/// Re-entrant messages from the sink are dropped and panics are caught.
+fn forward(level: RtapiLogLevel, message: &str) {
+    let Some(_guard) = HandlerGuard::enter() else {
+        return;
+    };
+
+    let _ = panic::catch_unwind(AssertUnwindSafe(|| {
+        if let Some(sink) = SINK.lock().unwrap_or_else(|e| e.into_inner()).as_ref() {
+            sink(level, message);
+        }
+    }));
+}
+
+/// Marks this thread as running the sink until dropped
+struct HandlerGuard;
+
+impl HandlerGuard {
+    /// Returns `None` if this thread is already running the sink
+    fn enter() -> Option<Self> {
+        if IN_HANDLER.with(|in_handler| in_handler.replace(true)) {
+            None
+        } else {
+            Some(Self)
+        }
+    }
+}
+
+impl Drop for HandlerGuard {
+    fn drop(&mut self) {
+        IN_HANDLER.with(|in_handler| in_handler.set(false));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, MutexGuard};
+
+    /// Tests share the global sink, so they must not run at the same time
+    static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Messages received by a fake sink
+    type Received = Arc<Mutex<Vec<(RtapiLogLevel, String)>>>;
+
+    /// Install a fake sink that records every message, without replacing the RTAPI handler
+    fn fake_sink(
+        sink: impl Fn(RtapiLogLevel, &str) + Send + 'static,
+    ) -> (MutexGuard<'static, ()>, Received) {
+        let lock = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let received = Received::default();
+        let messages = received.clone();
+
+        *SINK.lock().unwrap_or_else(|e| e.into_inner()) =
+            Some(Box::new(move |level, message: &str| {
+                messages.lock().unwrap().push((level, message.to_string()));
+
+                sink(level, message);
+            }));
+
+        (lock, received)
+    }
+
+    #[test]
+    fn forwards_to_sink() {
+        let (_lock, received) = fake_sink(|_, _| {});
+
+        forward(RtapiLogLevel::Warn, "hello");
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            [(RtapiLogLevel::Warn, "hello".to_string())]
+        );
+    }
+
+    #[test]
+    fn drops_messages_from_sink() {
+        let (_lock, received) = fake_sink(|_, message| {
+            if message == "outer" {
+                forward(RtapiLogLevel::Info, "inner");
+            }
+        });
+
+        forward(RtapiLogLevel::Info, "outer");
+        forward(RtapiLogLevel::Info, "next");
+
+        assert_eq!(
+            *received.lock().unwrap(),
+            [
+                (RtapiLogLevel::Info, "outer".to_string()),
+                (RtapiLogLevel::Info, "next".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn catches_panics() {
+        let (_lock, received) = fake_sink(|_, message| {
+            if message == "panic" {
+                panic!("sink panicked");
+            }
+        });
+
+        forward(RtapiLogLevel::Err, "panic");
+        forward(RtapiLogLevel::Err, "after");
+
+        assert_eq!(received.lock().unwrap().len(), 2);
+        assert!(!IN_HANDLER.with(Cell::get));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{builder::Format, level, message::Message, print_msg, RtapiLogLevel};
+use std::fmt::{self, Write};
+use tracing_core::{
+    field::{Field, Visit},
+    Event, Level, Subscriber,
+};
+use tracing_subscriber::{layer::Context, registry::LookupSpan, Layer};
+
+/// [`tracing-subscriber`](tracing_subscriber) layer that prints events with `rtapi_print_msg`
+///
+/// Events are printed at the RTAPI message level matching their [`tracing`](tracing_core) level,
+/// using the same level mapping and filtering as [`RtapiLogger`](crate::RtapiLogger). Each event is
+/// preceded by the names of the spans it occurred in, from outermost to innermost, and followed by
+/// its fields as `name=value`.
+///
+/// Create a layer with [`RtapiLayer::new`], or with [`Builder::layer`](crate::Builder::layer) to
+/// add a prefix, target or module path. Requires the `tracing` feature.
+///
+/// ```rust,no_run
+/// use tracing_subscriber::prelude::*;
+///
+/// tracing_subscriber::registry()
+///     .with(rtapi_logger::Builder::new().prefix("my-comp").layer())
+///     .init();
+///
+/// let span = tracing::info_span!("homing", joint = 0);
+/// let _enter = span.enter();
+///
+/// // Prints `my-comp: homing: Switch found position=12.5`
+/// tracing::info!(position = 12.5, "Switch found");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RtapiLayer {
+    pub(crate) format: Format,
+}
+
+impl RtapiLayer {
+    /// Create a layer with no prefix, target or module path
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Layer<S> for RtapiLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let event_level = RtapiLogLevel::from(*metadata.level());
+
+        if event_level > level() {
+            return;
+        }
+
+        let mut message = Message::new();
+
+        self.format
+            .write_header(&mut message, metadata.target(), metadata.module_path());
+
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                // Long messages are truncated instead of returning an error, so this can't fail
+                let _ = write!(message, "{}: ", span.name());
+            }
+        }
+
+        event.record(&mut FieldVisitor {
+            message: &mut message,
+            first: true,
+        });
+
+        print_msg(event_level, message.as_c_str());
+    }
+}
+
+impl From<Level> for RtapiLogLevel {
+    fn from(other: Level) -> Self {
+        match other {
+            Level::ERROR => Self::Err,
+            Level::WARN => Self::Warn,
+            Level::INFO => Self::Info,
+            Level::DEBUG => Self::Dbg,
+            Level::TRACE => Self::All,
+        }
+    }
+}
+
+/// Write the fields of an event to a message, separated by spaces
+struct FieldVisitor<'a> {
+    message: &'a mut Message,
+
+    /// Whether no field has been written yet
+    first: bool,
+}
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.record_debug(field, &format_args!("{}", value));
+        } else {
+            self.record_debug(field, &value);
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let separator = if self.first { "" } else { " " };
+
+        self.first = false;
+
+        // Long messages are truncated instead of returning an error, so these can't fail
+        if field.name() == "message" {
+            let _ = write!(self.message, "{}{:?}", separator, value);
+        } else {
+            let _ = write!(self.message, "{}{}={:?}", separator, field.name(), value);
+        }
+    }
+}
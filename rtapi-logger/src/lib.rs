@@ -5,29 +5,130 @@
 //! machinery to prevent these problems, whilst also allowing the use of the convenient macros
 //! provided by [`log`].
 //!
-//! Please note this crate is still somewhat experimental. For example, currently all messages are
-//! logged at the `ERR` level provided by LinuxCNC.
+//! Records are printed with `rtapi_print_msg` at the matching RTAPI message level:
+//!
+//! | `log` level | RTAPI level      |
+//! | ----------- | ---------------- |
+//! | `Error`     | `RTAPI_MSG_ERR`  |
+//! | `Warn`      | `RTAPI_MSG_WARN` |
+//! | `Info`      | `RTAPI_MSG_INFO` |
+//! | `Debug`     | `RTAPI_MSG_DBG`  |
+//! | `Trace`     | `RTAPI_MSG_ALL`  |
+//!
+//! The maximum level is taken from LinuxCNC's message level (`rtapi_get_msg_level`) when the
+//! logger is initialised. It can be changed at runtime with [`set_level`], which updates both
+//! LinuxCNC and [`log`]. If the level is changed elsewhere, e.g. with `halcmd debug`, call
+//! [`sync_level`] to pick up the new level.
+//!
+//! ```rust,no_run
+//! use rtapi_logger::RtapiLogLevel;
+//!
+//! rtapi_logger::init().ok();
+//!
+//! log::debug!("Not shown at the default level");
+//!
+//! rtapi_logger::set_level(RtapiLogLevel::Dbg);
+//!
+//! log::debug!("Shown in linuxcnc.log at debug level");
+//! ```
+//!
+//! # Realtime use
+//!
+//! Logging a record does not allocate, so the [`log`] macros can be used in realtime HAL functions.
+//! Each message is formatted into a buffer on the stack and passed to `rtapi_print_msg`, which
+//! queues it in LinuxCNC's message ring to be written out by `rtapi_msgd`. Messages longer than
+//! [`MAX_MESSAGE_LEN`] bytes are truncated.
+//!
+//! Formatting still calls the `Display` and `Debug` implementations of the logged values, so these
+//! must not allocate either for a log call to be realtime safe.
+//!
+//! # Message format
+//!
+//! [`Builder`] configures a prefix for every message, such as the component name, and whether
+//! to include the target and module path of each record.
+//!
+//! With the `tracing` feature, [`Builder::layer`] creates a `tracing-subscriber` layer which prints
+//! [`tracing`](https://docs.rs/tracing) events in the same way. See `RtapiLayer` for details.
+//!
+//! # Capturing messages
+//!
+//! The [`capture`] module does the opposite: it forwards RTAPI messages from every component in
+//! the process, including C components, into [`log`] or a custom sink.
 
-use linuxcnc_hal_sys::{rtapi_get_msg_level, rtapi_print_msg};
+use builder::Format;
+use linuxcnc_hal_sys::{
+    msg_level_t_RTAPI_MSG_ALL, msg_level_t_RTAPI_MSG_DBG, msg_level_t_RTAPI_MSG_ERR,
+    msg_level_t_RTAPI_MSG_INFO, msg_level_t_RTAPI_MSG_NONE, msg_level_t_RTAPI_MSG_WARN,
+    rtapi_get_msg_level, rtapi_set_msg_level,
+};
 use log::{Level, Metadata, Record};
-use std::ffi::CString;
+use message::Message;
+use std::{ffi::CStr, fmt::Write};
+
+mod builder;
+pub mod capture;
+#[cfg(feature = "tracing")]
+mod layer;
+mod message;
+
+pub use builder::Builder;
+#[cfg(feature = "tracing")]
+pub use layer::RtapiLayer;
+pub use message::MAX_MESSAGE_LEN;
 
 use log::{LevelFilter, SetLoggerError};
 
-/// Log level.
-///
-/// Defined in the LinuxCNC source as `msg_level_t`.
+/// RTAPI message level
 ///
-/// Note that this currently does nothing; all messages are logged at `ERR` level.
-#[derive(Debug, Copy, Clone)]
-enum RtapiLogLevel {
-    #[allow(unused)]
-    None = 0,
-    Err = 1,
-    Warn = 2,
-    Info = 3,
-    Dbg = 4,
-    All = 5,
+/// Defined in the LinuxCNC source as `msg_level_t`. Levels are ordered from least to most verbose.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum RtapiLogLevel {
+    /// No messages are printed
+    None = msg_level_t_RTAPI_MSG_NONE as isize,
+
+    /// Errors
+    Err = msg_level_t_RTAPI_MSG_ERR as isize,
+
+    /// Warnings
+    Warn = msg_level_t_RTAPI_MSG_WARN as isize,
+
+    /// Informational messages
+    Info = msg_level_t_RTAPI_MSG_INFO as isize,
+
+    /// Debug messages
+    Dbg = msg_level_t_RTAPI_MSG_DBG as isize,
+
+    /// Every message
+    All = msg_level_t_RTAPI_MSG_ALL as isize,
+}
+
+impl RtapiLogLevel {
+    /// Convert a raw `msg_level_t` value into a level
+    ///
+    /// Values above `RTAPI_MSG_ALL` are treated as [`RtapiLogLevel::All`], and negative values as
+    /// [`RtapiLogLevel::None`].
+    pub fn from_raw(level: i32) -> Self {
+        match level {
+            x if x <= msg_level_t_RTAPI_MSG_NONE as i32 => Self::None,
+            x if x == msg_level_t_RTAPI_MSG_ERR as i32 => Self::Err,
+            x if x == msg_level_t_RTAPI_MSG_WARN as i32 => Self::Warn,
+            x if x == msg_level_t_RTAPI_MSG_INFO as i32 => Self::Info,
+            x if x == msg_level_t_RTAPI_MSG_DBG as i32 => Self::Dbg,
+            _ => Self::All,
+        }
+    }
+
+    /// Get the most verbose [`log`] level that is printed at this RTAPI level
+    pub fn to_level_filter(self) -> LevelFilter {
+        match self {
+            Self::None => LevelFilter::Off,
+            Self::Err => LevelFilter::Error,
+            Self::Warn => LevelFilter::Warn,
+            Self::Info => LevelFilter::Info,
+            Self::Dbg => LevelFilter::Debug,
+            Self::All => LevelFilter::Trace,
+        }
+    }
 }
 
 impl From<Level> for RtapiLogLevel {
@@ -42,45 +143,96 @@ impl From<Level> for RtapiLogLevel {
     }
 }
 
-static LOGGER: RtapiLogger = RtapiLogger;
+impl From<LevelFilter> for RtapiLogLevel {
+    fn from(other: LevelFilter) -> Self {
+        other.to_level().map_or(Self::None, Self::from)
+    }
+}
 
+/// Install the RTAPI logger as the global [`log`] logger
+///
+/// The maximum log level is set from LinuxCNC's current RTAPI message level. Use [`Builder`] to
+/// add a prefix, target or module path to each message.
 pub fn init() -> Result<(), SetLoggerError> {
-    let _rtapi_level = unsafe { rtapi_get_msg_level() };
+    Builder::new().init()
+}
+
+/// Get LinuxCNC's current RTAPI message level
+pub fn level() -> RtapiLogLevel {
+    RtapiLogLevel::from_raw(unsafe { rtapi_get_msg_level() })
+}
 
-    println!("Log level {}", _rtapi_level);
+/// Set LinuxCNC's RTAPI message level, and the maximum [`log`] level to match
+pub fn set_level(level: RtapiLogLevel) {
+    unsafe { rtapi_set_msg_level(level as i32) };
 
-    log::set_logger(&LOGGER).map(|()| log::set_max_level(LevelFilter::Info))
-    // Log everything by default
-    // log::set_logger(&LOGGER).map(|()| log::set_max_level(LevelFilter::Trace))
+    log::set_max_level(level.to_level_filter());
 }
 
-pub struct RtapiLogger;
+/// Set the maximum [`log`] level from LinuxCNC's current RTAPI message level
+///
+/// Call this if the RTAPI message level has been changed outside this crate, e.g. by
+/// `rtapi_set_msg_level` in another component or by `halcmd debug`. [`RtapiLogger`] checks the
+/// RTAPI level for every record, so a less verbose level takes effect immediately. A more verbose
+/// level only takes effect once this function is called.
+pub fn sync_level() {
+    log::set_max_level(level().to_level_filter());
+}
+
+/// [`log`] logger that prints records with `rtapi_print_msg`
+///
+/// Created with [`Builder`].
+#[derive(Debug)]
+pub struct RtapiLogger {
+    format: Format,
+}
 
 impl log::Log for RtapiLogger {
-    // FIXME: This should not just return true - performance will take a hit. LinuxCNC's logging
-    // system does the filtering, so that works at least.
-    fn enabled(&self, _metadata: &Metadata) -> bool {
-        // metadata.level() <= Level::Info
-        true
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        RtapiLogLevel::from(metadata.level()) <= level()
     }
 
     fn log(&self, record: &Record) {
         if self.enabled(record.metadata()) {
-            let out = format!("{}\n", record.args());
+            let mut message = Message::new();
 
-            // FIXME: LinuxCNC seems to always use `Err` level regardless of DEBUG config value.
-            // let level: RtapiLogLevel = record.level().into();
-            let level = RtapiLogLevel::Err;
+            self.format
+                .write_header(&mut message, record.target(), record.module_path());
 
-            if let Ok(f) = CString::new(out) {
-                unsafe { rtapi_print_msg(level as u32, f.as_ptr()) };
-            } else {
-                let fail = CString::new("failed to build log message string").unwrap();
+            // Long messages are truncated instead of returning an error, so this can't fail
+            let _ = write!(message, "{}", record.args());
 
-                unsafe { rtapi_print_msg(level as u32, fail.as_ptr()) }
-            }
+            print_msg(RtapiLogLevel::from(record.level()), message.as_c_str());
         }
     }
 
     fn flush(&self) {}
 }
+
+/// Format string passed to `rtapi_print_msg`, so `%` in messages is printed as is
+const FORMAT: &[u8] = b"%s\0";
+
+/// Print a message with `rtapi_print_msg`
+#[cfg(not(feature = "dlopen"))]
+fn print_msg(level: RtapiLogLevel, msg: &CStr) {
+    unsafe {
+        linuxcnc_hal_sys::rtapi_print_msg(level as u32, FORMAT.as_ptr().cast(), msg.as_ptr())
+    };
+}
+
+/// Print a message with `rtapi_print_msg` from the HAL library loaded at runtime
+///
+/// Variadic functions can't be wrapped by `linuxcnc-hal-sys`, so the function pointer is used
+/// directly. Messages are dropped if the library or function is not available.
+#[cfg(feature = "dlopen")]
+fn print_msg(level: RtapiLogLevel, msg: &CStr) {
+    use linuxcnc_hal_sys::dynamic;
+
+    let print = dynamic::load()
+        .ok()
+        .and_then(|library| library.functions().rtapi_print_msg);
+
+    if let Some(print) = print {
+        unsafe { print(level as u32, FORMAT.as_ptr().cast(), msg.as_ptr()) };
+    }
+}
@@ -0,0 +1,132 @@
+//! Fixed size message buffer
+
+use std::{ffi::CStr, fmt};
+
+/// Maximum length of a log message in bytes, not including the trailing newline
+///
+/// Longer messages are truncated.
+pub const MAX_MESSAGE_LEN: usize = 254;
+
+/// A message formatted into a stack buffer, ready to pass to `rtapi_print_msg`
+///
+/// Writes past the end of the buffer are truncated at a character boundary instead of allocating.
+/// Formatting stops at the first NUL byte, as C would.
+pub(crate) struct Message {
+    /// Message bytes, followed by a newline and at least one NUL
+    buf: [u8; MAX_MESSAGE_LEN + 2],
+
+    /// Length of the message, not including the newline
+    len: usize,
+
+    /// Whether anything has been truncated
+    truncated: bool,
+}
+
+impl Message {
+    pub(crate) fn new() -> Self {
+        Self {
+            buf: [0; MAX_MESSAGE_LEN + 2],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Get the message with a trailing newline as a C string
+    pub(crate) fn as_c_str(&mut self) -> &CStr {
+        self.buf[self.len] = b'\n';
+        self.buf[self.len + 1] = 0;
+
+        // NOTE: Unwrap is safe as the buffer always contains a NUL after the newline
+        CStr::from_bytes_until_nul(&self.buf).unwrap()
+    }
+
+    /// Whether the message was truncated to fit the buffer
+    #[cfg(test)]
+    fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl fmt::Write for Message {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if self.truncated {
+            return Ok(());
+        }
+
+        let s = match s.find('\0') {
+            Some(nul) => {
+                self.truncated = true;
+
+                &s[..nul]
+            }
+            None => s,
+        };
+
+        let remaining = MAX_MESSAGE_LEN - self.len;
+
+        let len = if s.len() > remaining {
+            self.truncated = true;
+
+            // Don't split a multi-byte character
+            (0..=remaining)
+                .rev()
+                .find(|i| s.is_char_boundary(*i))
+                .unwrap_or(0)
+        } else {
+            s.len()
+        };
+
+        self.buf[self.len..self.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn short_message() {
+        let mut message = Message::new();
+
+        write!(message, "value {}", 42).unwrap();
+
+        assert!(!message.is_truncated());
+        assert_eq!(message.as_c_str().to_bytes(), b"value 42\n");
+    }
+
+    #[test]
+    fn truncate_long_message() {
+        let mut message = Message::new();
+
+        write!(message, "{}", "a".repeat(MAX_MESSAGE_LEN + 10)).unwrap();
+
+        assert!(message.is_truncated());
+        assert_eq!(message.as_c_str().to_bytes().len(), MAX_MESSAGE_LEN + 1);
+    }
+
+    #[test]
+    fn truncate_on_char_boundary() {
+        let mut message = Message::new();
+
+        write!(message, "{}é", "a".repeat(MAX_MESSAGE_LEN - 1)).unwrap();
+
+        assert!(message.is_truncated());
+        assert_eq!(
+            message.as_c_str().to_str().unwrap(),
+            format!("{}\n", "a".repeat(MAX_MESSAGE_LEN - 1))
+        );
+    }
+
+    #[test]
+    fn stop_at_nul() {
+        let mut message = Message::new();
+
+        write!(message, "before\0after").unwrap();
+
+        assert_eq!(message.as_c_str().to_bytes(), b"before\n");
+    }
+}